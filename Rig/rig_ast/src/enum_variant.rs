@@ -1,9 +1,14 @@
 use crate::expr::Expr;
+use rig_span::Span;
+use std::fmt::{Display, Formatter};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct EnumVariant {
     pub name: String,
     pub fields: Option<Vec<EnumVariantField>>,
+
+    /// Span of the name of the variant
+    pub span: Span,
 }
 
 impl EnumVariant {
@@ -14,9 +19,9 @@ impl EnumVariant {
             res.push(
                 fields
                     .iter()
-                    .map(|f| format!("{}{}", "\t".repeat(depth + 1), f.to_string()))
+                    .map(|f| format!("{}{}", "\t".repeat(depth + 1), f))
                     .collect::<Vec<String>>()
-                    .join(","),
+                    .join(",\n"),
             );
 
             res.push("\t".repeat(depth) + "}");
@@ -31,10 +36,11 @@ impl EnumVariant {
 pub struct EnumVariantField {
     pub name: String,
     pub ty: Expr,
+    pub span: Span,
 }
 
-impl ToString for EnumVariantField {
-    fn to_string(&self) -> String {
-        format!("{}: {}", self.name, self.ty.to_string(0))
+impl Display for EnumVariantField {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.name, self.ty.to_string(0))
     }
 }
@@ -89,20 +89,15 @@ impl Expr {
             Expr::AssignmentExpr { name, value, .. } => {
                 format!("{} = {}", name, value.to_string(depth))
             }
-            Expr::BinaryExpr { lhs, op, rhs, .. } => format!(
-                "({} {} {})",
-                lhs.to_string(depth),
-                op.to_string(),
-                rhs.to_string(depth)
-            ),
-            Expr::LogicalExpr { lhs, op, rhs, .. } => format!(
-                "({} {} {})",
-                lhs.to_string(depth),
-                op.to_string(),
-                rhs.to_string(depth)
-            ),
+            // parentheses of the source are kept as grouping expressions
+            Expr::BinaryExpr { lhs, op, rhs, .. } => {
+                format!("{} {} {}", lhs.to_string(depth), op, rhs.to_string(depth))
+            }
+            Expr::LogicalExpr { lhs, op, rhs, .. } => {
+                format!("{} {} {}", lhs.to_string(depth), op, rhs.to_string(depth))
+            }
             Expr::UnaryExpr { op, rhs, .. } => {
-                format!("({}{})", op.to_string(), rhs.to_string(depth))
+                format!("{}{}", op, rhs.to_string(depth))
             }
             Expr::GetExpr { name, object, .. } => format!("{}.{}", object.to_string(depth), name),
             Expr::PathExpr { path, .. } => path.join("::"),
@@ -111,7 +106,15 @@ impl Expr {
             Expr::IntegerLiteralExpr { value, .. } => value.to_string(),
             Expr::BooleanLiteralExpr { value, .. } => value.to_string(),
             Expr::NullLiteralExpr { .. } => String::from("null"),
-            Expr::FloatLiteralExpr { value, .. } => value.to_string(),
+            Expr::FloatLiteralExpr { value, .. } => {
+                // `1.0` would print as `1`, which is an integer
+                let value = value.to_string();
+                if value.contains('.') {
+                    value
+                } else {
+                    value + ".0"
+                }
+            }
             Expr::SetExpr {
                 object,
                 name,
@@ -1,7 +1,7 @@
 use crate::expr::Expr;
 use crate::visibility::Visibility;
 use rig_span::Span;
-use std::fmt::{Debug, Formatter};
+use std::fmt::{Debug, Display, Formatter};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Prototype {
@@ -10,23 +10,22 @@ pub struct Prototype {
     pub args: Vec<Argument>,
     pub return_ty: Option<Expr>,
     pub fn_type: FnType,
-}
 
-impl ToString for Prototype {
-    fn to_string(&self) -> String {
-        let mut args_string = if self.fn_type == FnType::Method {
-            String::from("self, ")
-        } else {
-            String::new()
-        };
+    /// From the name of the function to the end of its return type
+    pub span: Span,
+}
 
-        args_string = args_string
-            + &self
-                .args
-                .iter()
-                .map(|a| a.to_string())
-                .collect::<Vec<String>>()
-                .join(", ");
+impl Display for Prototype {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut args = self
+            .args
+            .iter()
+            .map(|a| a.to_string())
+            .collect::<Vec<String>>();
+        if self.fn_type == FnType::Method {
+            args.insert(0, String::from("self"));
+        }
+        let args_string = args.join(", ");
 
         let ty_string = if let Some(ty) = &self.return_ty {
             format!(" -> {}", ty.to_string(0))
@@ -34,9 +33,10 @@ impl ToString for Prototype {
             String::new()
         };
 
-        format!(
+        write!(
+            f,
             "{}{}fn {}({}){}",
-            self.visibility.to_string(),
+            self.visibility,
             if self.visibility == Visibility::Pub {
                 " "
             } else {
@@ -62,14 +62,14 @@ pub struct Argument {
     pub span: Span,
 }
 
-impl ToString for Argument {
-    fn to_string(&self) -> String {
-        format!("{}: {}", self.name, self.type_.to_string(0))
+impl Display for Argument {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.name, self.type_.to_string(0))
     }
 }
 
 impl Debug for Argument {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        write!(f, "{}", self.to_string())
+        write!(f, "{}", self)
     }
 }
@@ -6,4 +6,6 @@ pub mod op;
 pub mod stmt;
 pub mod struct_field;
 pub mod token;
+pub mod tree;
+pub mod use_tree;
 pub mod visibility;
@@ -1,3 +1,4 @@
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -12,9 +13,9 @@ pub enum LogicalOperator {
     LessEq,
 }
 
-impl ToString for LogicalOperator {
-    fn to_string(&self) -> String {
-        String::from(match self {
+impl Display for LogicalOperator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
             LogicalOperator::And => "&&",
             LogicalOperator::Or => "||",
             LogicalOperator::Equal => "==",
@@ -59,9 +60,9 @@ pub enum BinaryOperator {
     RightShift,
 }
 
-impl ToString for BinaryOperator {
-    fn to_string(&self) -> String {
-        String::from(match self {
+impl Display for BinaryOperator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
             BinaryOperator::Plus => "+",
             BinaryOperator::Minus => "-",
             BinaryOperator::Multiply => "*",
@@ -121,9 +122,9 @@ pub enum UnaryOperator {
     Negate,
 }
 
-impl ToString for UnaryOperator {
-    fn to_string(&self) -> String {
-        String::from(match self {
+impl Display for UnaryOperator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
             UnaryOperator::Not => "!",
             UnaryOperator::Negate => "-",
         })
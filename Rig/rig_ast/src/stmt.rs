@@ -3,13 +3,14 @@ use crate::expr::Expr;
 use crate::function_prototype::Prototype;
 use crate::match_arms::MatchArm;
 use crate::struct_field::StructField;
+use crate::use_tree::UseTree;
 use crate::visibility::Visibility;
 use rig_span::Span;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Stmt {
     UseStmt {
-        path: Expr,
+        tree: UseTree,
         visibility: Visibility,
         span: Span,
     },
@@ -103,14 +104,14 @@ impl Stmt {
     pub fn to_string(&self, block_depth: usize) -> String {
         let res = match self {
             Stmt::UseStmt {
-                path, visibility, ..
+                tree, visibility, ..
             } => {
                 let mut vis = visibility.to_string();
                 if !vis.is_empty() {
                     vis.push(' ');
                 }
 
-                format!("{}use {};", vis, path.to_string(block_depth))
+                format!("{}use {};", vis, tree)
             }
             Stmt::StructStmt {
                 visibility,
@@ -129,7 +130,7 @@ impl Stmt {
                 }
 
                 for field in fields {
-                    res += &format!("{}{},\n", "\t".repeat(block_depth + 1), field.to_string());
+                    res += &format!("{}{},\n", "\t".repeat(block_depth + 1), field);
                 }
 
                 res += &format!("{}}}", "\t".repeat(block_depth));
@@ -158,7 +159,7 @@ impl Stmt {
             }
             Stmt::FnStmt {
                 prototype, body, ..
-            } => format!("{} {}", prototype.to_string(), body.to_string(block_depth)),
+            } => format!("{} {}", prototype, body.to_string(block_depth)),
             Stmt::LetStmt {
                 name,
                 value,
@@ -246,9 +247,9 @@ impl Stmt {
             Stmt::ExternStmt { prototypes, .. } => {
                 let stringified_prototypes = prototypes
                     .iter()
-                    .map(|p| format!("{}{};", "\t".repeat(block_depth + 1), p.to_string()))
+                    .map(|p| format!("{}{};", "\t".repeat(block_depth + 1), p))
                     .collect::<Vec<String>>()
-                    .join(";\n");
+                    .join("\n");
 
                 let newline = if !stringified_prototypes.is_empty() {
                     "\n"
@@ -263,8 +264,18 @@ impl Stmt {
                     "\t".repeat(block_depth)
                 )
             }
-            Stmt::ModStmt { name, body, .. } => {
-                let mut res = vec![format!("mod {}", name)];
+            Stmt::ModStmt {
+                name,
+                body,
+                visibility,
+                ..
+            } => {
+                let mut vis = visibility.to_string();
+                if !vis.is_empty() {
+                    vis.push(' ');
+                }
+
+                let mut res = vec![format!("{}mod {}", vis, name)];
 
                 if let Some(body) = body {
                     res[0] += " {";
@@ -279,7 +290,7 @@ impl Stmt {
 
                     res.push(format!("{}}}", "\t".repeat(block_depth)));
                 } else {
-                    res.push(";".to_string());
+                    res[0] += ";";
                 }
 
                 res.join("\n")
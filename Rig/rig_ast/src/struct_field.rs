@@ -1,21 +1,24 @@
 use crate::expr::Expr;
 use crate::visibility::Visibility;
+use rig_span::Span;
+use std::fmt::{Display, Formatter};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct StructField {
     pub visibility: Visibility,
     pub name: String,
     pub ty: Expr,
+    pub span: Span,
 }
 
-impl ToString for StructField {
-    fn to_string(&self) -> String {
+impl Display for StructField {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let mut vis = self.visibility.to_string();
         if self.visibility == Visibility::Pub {
             vis.push(' ');
         }
 
-        format!("{}{}: {}", vis, self.name, self.ty.to_string(0))
+        write!(f, "{}{}: {}", vis, self.name, self.ty.to_string(0))
     }
 }
 
@@ -23,6 +26,7 @@ impl ToString for StructField {
 pub struct StructExprField {
     pub name: String,
     pub val: Expr,
+    pub span: Span,
 }
 
 impl StructExprField {
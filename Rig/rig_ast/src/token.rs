@@ -167,8 +167,12 @@ pub enum TokenType {
     EOF,
 }
 
-pub const KEYWORDS: [&str; 24] = [
+pub const KEYWORDS: [&str; 27] = [
     "use", "pub", "extern", "fn", "struct", "impl", "for", "loop", "while", "let", "if", "else",
     "self", "true", "false", "null", "in", "continue", "break", "return", "print", "mod", "enum",
-    "match",
+    "match", "as", "super", "crate",
 ];
+
+/// Keywords that can start a path, `self::f`, `super::f` and `crate::f` are relative to the
+/// current module, its parent and the root module
+pub const PATH_PREFIXES: [&str; 3] = ["self", "super", "crate"];
@@ -0,0 +1,334 @@
+use crate::expr::Expr;
+use crate::stmt::Stmt;
+use crate::visibility::Visibility;
+use rig_span::Span;
+
+/// Node of the tree printed by `--unpretty ast`
+pub struct TreeNode {
+    pub label: String,
+    pub span: Option<Span>,
+    pub children: Vec<TreeNode>,
+}
+
+impl TreeNode {
+    fn new(label: String, span: &Span, children: Vec<TreeNode>) -> Self {
+        Self {
+            label,
+            span: Some(span.clone()),
+            children,
+        }
+    }
+
+    /// Node grouping the children of a node, like the condition of an `if`
+    fn group(label: &str, children: Vec<TreeNode>) -> Self {
+        Self {
+            label: label.to_string(),
+            span: None,
+            children,
+        }
+    }
+
+    /// Renders the node and its children below it. Spans are left out without `with_spans`,
+    /// so the same program parsed at different positions renders the same
+    pub fn render(&self, with_spans: bool) -> String {
+        let mut res = String::new();
+        self.render_into(&mut res, "", "", with_spans);
+        res
+    }
+
+    fn render_into(&self, res: &mut String, prefix: &str, child_prefix: &str, with_spans: bool) {
+        res.push_str(prefix);
+        res.push_str(&self.label);
+        if let (Some(span), true) = (&self.span, with_spans) {
+            res.push_str(&format!(
+                " @ {}:{}-{}:{}",
+                span.starting_line,
+                span.starting_line_offset + 1,
+                span.ending_line,
+                span.ending_line_end_offset + 1
+            ));
+        }
+        res.push('\n');
+
+        for (idx, child) in self.children.iter().enumerate() {
+            let (branch, indent) = if idx + 1 == self.children.len() {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+
+            child.render_into(
+                res,
+                &format!("{}{}", child_prefix, branch),
+                &format!("{}{}", child_prefix, indent),
+                with_spans,
+            );
+        }
+    }
+}
+
+/// Renders the statements of a module as a tree
+pub fn render_ast(stmts: &[Stmt], with_spans: bool) -> String {
+    stmts
+        .iter()
+        .map(|stmt| stmt_node(stmt).render(with_spans))
+        .collect()
+}
+
+fn with_visibility(visibility: Visibility, label: &str) -> String {
+    match visibility {
+        Visibility::Pub => format!("pub {}", label),
+        Visibility::NotPub => label.to_string(),
+    }
+}
+
+fn stmt_node(stmt: &Stmt) -> TreeNode {
+    let span = &stmt.get_span();
+
+    match stmt {
+        Stmt::UseStmt {
+            tree, visibility, ..
+        } => TreeNode::new(
+            with_visibility(*visibility, &format!("Use {}", tree)),
+            span,
+            vec![],
+        ),
+        Stmt::StructStmt {
+            name,
+            fields,
+            visibility,
+            ..
+        } => TreeNode::new(
+            with_visibility(*visibility, &format!("Struct {}", name)),
+            span,
+            fields
+                .iter()
+                .map(|field| TreeNode::new(format!("Field {}", field), &field.span, vec![]))
+                .collect(),
+        ),
+        Stmt::ImplStmt {
+            struct_name,
+            methods,
+            ..
+        } => TreeNode::new(
+            format!("Impl {}", struct_name),
+            span,
+            methods.iter().map(|method| stmt_node(method)).collect(),
+        ),
+        Stmt::ExternStmt { prototypes, .. } => TreeNode::new(
+            String::from("Extern"),
+            span,
+            prototypes
+                .iter()
+                .map(|prototype| {
+                    TreeNode::new(format!("Prototype {}", prototype), &prototype.span, vec![])
+                })
+                .collect(),
+        ),
+        Stmt::FnStmt {
+            prototype, body, ..
+        } => TreeNode::new(format!("Fn {}", prototype), span, vec![stmt_node(body)]),
+        Stmt::LetStmt {
+            name,
+            ty,
+            value,
+            visibility,
+            ..
+        } => {
+            let mut children = vec![];
+            if let Some(ty) = ty {
+                children.push(TreeNode::group("Type", vec![expr_node(ty)]));
+            }
+            children.push(expr_node(value));
+
+            TreeNode::new(
+                with_visibility(*visibility, &format!("Let {}", name)),
+                span,
+                children,
+            )
+        }
+        Stmt::IfStmt {
+            condition,
+            body,
+            else_branch,
+            ..
+        } => {
+            let mut children = vec![
+                TreeNode::group("Condition", vec![expr_node(condition)]),
+                stmt_node(body),
+            ];
+            if let Some(else_branch) = else_branch {
+                children.push(TreeNode::group("Else", vec![stmt_node(else_branch)]));
+            }
+
+            TreeNode::new(String::from("If"), span, children)
+        }
+        Stmt::WhileStmt {
+            condition, body, ..
+        } => TreeNode::new(
+            String::from("While"),
+            span,
+            vec![
+                TreeNode::group("Condition", vec![expr_node(condition)]),
+                stmt_node(body),
+            ],
+        ),
+        Stmt::ForStmt {
+            var,
+            iterable,
+            body,
+            ..
+        } => TreeNode::new(
+            format!("For {}", var),
+            span,
+            vec![
+                TreeNode::group("Iterable", vec![expr_node(iterable)]),
+                stmt_node(body),
+            ],
+        ),
+        Stmt::PrintStmt { expr, .. } => {
+            TreeNode::new(String::from("Print"), span, vec![expr_node(expr)])
+        }
+        Stmt::ReturnStmt { expr, .. } => {
+            TreeNode::new(String::from("Return"), span, vec![expr_node(expr)])
+        }
+        Stmt::BlockStmt { exprs, .. } => TreeNode::new(
+            String::from("Block"),
+            span,
+            exprs.iter().map(|stmt| stmt_node(stmt)).collect(),
+        ),
+        Stmt::ExprStmt { expr, .. } => {
+            TreeNode::new(String::from("Expr"), span, vec![expr_node(expr)])
+        }
+        Stmt::BreakStmt { .. } => TreeNode::new(String::from("Break"), span, vec![]),
+        Stmt::ContinueStmt { .. } => TreeNode::new(String::from("Continue"), span, vec![]),
+        Stmt::ModStmt {
+            name,
+            body,
+            visibility,
+            ..
+        } => match body {
+            Some(body) => TreeNode::new(
+                with_visibility(*visibility, &format!("Mod {}", name)),
+                span,
+                body.iter().map(stmt_node).collect(),
+            ),
+            None => TreeNode::new(
+                with_visibility(*visibility, &format!("Mod {} (file)", name)),
+                span,
+                vec![],
+            ),
+        },
+        Stmt::EnumStmt {
+            name,
+            variants,
+            visibility,
+            ..
+        } => TreeNode::new(
+            with_visibility(*visibility, &format!("Enum {}", name)),
+            span,
+            variants
+                .iter()
+                .map(|variant| {
+                    TreeNode::new(
+                        format!("Variant {}", variant.name),
+                        &variant.span,
+                        variant
+                            .fields
+                            .iter()
+                            .flatten()
+                            .map(|field| {
+                                TreeNode::new(format!("Field {}", field), &field.span, vec![])
+                            })
+                            .collect(),
+                    )
+                })
+                .collect(),
+        ),
+        Stmt::MatchStmt { matched, arms, .. } => {
+            let mut children = vec![TreeNode::group("Matched", vec![expr_node(matched)])];
+            children.extend(arms.iter().map(|arm| {
+                TreeNode::group("Arm", vec![expr_node(&arm.match_), stmt_node(&arm.body)])
+            }));
+
+            TreeNode::new(String::from("Match"), span, children)
+        }
+    }
+}
+
+fn expr_node(expr: &Expr) -> TreeNode {
+    let span = &expr.get_span();
+
+    match expr {
+        Expr::AssignmentExpr { name, value, .. } => {
+            TreeNode::new(format!("Assign {}", name), span, vec![expr_node(value)])
+        }
+        Expr::BinaryExpr { lhs, op, rhs, .. } => TreeNode::new(
+            format!("Binary {}", op),
+            span,
+            vec![expr_node(lhs), expr_node(rhs)],
+        ),
+        Expr::LogicalExpr { lhs, op, rhs, .. } => TreeNode::new(
+            format!("Logical {}", op),
+            span,
+            vec![expr_node(lhs), expr_node(rhs)],
+        ),
+        Expr::UnaryExpr { op, rhs, .. } => {
+            TreeNode::new(format!("Unary {}", op), span, vec![expr_node(rhs)])
+        }
+        Expr::GetExpr { object, name, .. } => {
+            TreeNode::new(format!("Get {}", name), span, vec![expr_node(object)])
+        }
+        Expr::PathExpr { path, .. } => {
+            TreeNode::new(format!("Path {}", path.join("::")), span, vec![])
+        }
+        Expr::GroupingExpr { expr, .. } => {
+            TreeNode::new(String::from("Grouping"), span, vec![expr_node(expr)])
+        }
+        Expr::StringLiteralExpr { value, .. } => {
+            TreeNode::new(format!("String {:?}", value), span, vec![])
+        }
+        Expr::IntegerLiteralExpr { value, .. } => {
+            TreeNode::new(format!("Integer {}", value), span, vec![])
+        }
+        Expr::BooleanLiteralExpr { value, .. } => {
+            TreeNode::new(format!("Boolean {}", value), span, vec![])
+        }
+        Expr::NullLiteralExpr { .. } => TreeNode::new(String::from("Null"), span, vec![]),
+        Expr::FloatLiteralExpr { value, .. } => {
+            TreeNode::new(format!("Float {:?}", value), span, vec![])
+        }
+        Expr::SetExpr {
+            object,
+            name,
+            value,
+            ..
+        } => TreeNode::new(
+            format!("Set {}", name),
+            span,
+            vec![expr_node(object), expr_node(value)],
+        ),
+        Expr::VariableExpr { name, .. } => {
+            TreeNode::new(format!("Variable {}", name), span, vec![])
+        }
+        Expr::SelfExpr { .. } => TreeNode::new(String::from("Self"), span, vec![]),
+        Expr::CallExpr { name, args, .. } => {
+            let mut children = vec![expr_node(name)];
+            children.extend(args.iter().map(expr_node));
+
+            TreeNode::new(String::from("Call"), span, children)
+        }
+        Expr::StructExpr { name, vals, .. } => {
+            let mut children = vec![expr_node(name)];
+            children.extend(vals.iter().map(|field| {
+                TreeNode::new(
+                    format!("Field {}", field.name),
+                    &field.span,
+                    vec![expr_node(&field.val)],
+                )
+            }));
+
+            TreeNode::new(String::from("StructLiteral"), span, children)
+        }
+    }
+}
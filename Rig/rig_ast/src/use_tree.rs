@@ -0,0 +1,66 @@
+use rig_span::Span;
+use std::fmt::{Display, Formatter};
+
+/// Paths imported by a `use` statement. `use a::{b, c::*}` is a group with prefix `a`
+#[derive(Debug, Clone, PartialEq)]
+pub enum UseTree {
+    /// `a::b` or `a::b as c`
+    Path {
+        path: Vec<String>,
+        alias: Option<String>,
+        span: Span,
+    },
+
+    /// `a::*`
+    Glob { prefix: Vec<String>, span: Span },
+
+    /// `a::{b, c}`
+    Group {
+        prefix: Vec<String>,
+        children: Vec<UseTree>,
+        span: Span,
+    },
+}
+
+impl UseTree {
+    pub fn span(&self) -> &Span {
+        match self {
+            UseTree::Path { span, .. }
+            | UseTree::Glob { span, .. }
+            | UseTree::Group { span, .. } => span,
+        }
+    }
+}
+
+impl Display for UseTree {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let with_prefix = |prefix: &[String], rest: String| {
+            if prefix.is_empty() {
+                rest
+            } else {
+                format!("{}::{}", prefix.join("::"), rest)
+            }
+        };
+
+        match self {
+            UseTree::Path {
+                path,
+                alias: Some(alias),
+                ..
+            } => write!(f, "{} as {}", path.join("::"), alias),
+            UseTree::Path { path, .. } => write!(f, "{}", path.join("::")),
+            UseTree::Glob { prefix, .. } => write!(f, "{}", with_prefix(prefix, String::from("*"))),
+            UseTree::Group {
+                prefix, children, ..
+            } => {
+                let children = children
+                    .iter()
+                    .map(|child| child.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                write!(f, "{}", with_prefix(prefix, format!("{{{}}}", children)))
+            }
+        }
+    }
+}
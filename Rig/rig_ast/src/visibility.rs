@@ -1,3 +1,4 @@
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -16,11 +17,11 @@ impl From<bool> for Visibility {
     }
 }
 
-impl ToString for Visibility {
-    fn to_string(&self) -> String {
+impl Display for Visibility {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Pub => String::from("pub"),
-            Self::NotPub => String::new(),
+            Self::Pub => write!(f, "pub"),
+            Self::NotPub => Ok(()),
         }
     }
 }
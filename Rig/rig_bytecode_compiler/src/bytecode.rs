@@ -0,0 +1,328 @@
+/// [OpCode] is a single instruction understood by the virtual machine.
+///
+/// Each opcode is encoded as one byte and may be followed by operands. Unless
+/// stated otherwise an operand is an unsigned 16 bit little-endian integer.
+/// "Top" refers to the value on top of the value stack.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpCode {
+    /// `Constant(index)`: push `constants[index]`
+    Constant,
+
+    /// Push `null`
+    Null,
+
+    /// Push `true`
+    True,
+
+    /// Push `false`
+    False,
+
+    /// Pop top and discard it
+    Pop,
+
+    /// `GetLocal(slot)`: push the local stored in `slot` of the current frame
+    GetLocal,
+
+    /// `SetLocal(slot)`: store top in `slot` of the current frame, leaving top on the stack
+    SetLocal,
+
+    /// `GetGlobal(slot)`: push the global stored in `slot`
+    GetGlobal,
+
+    /// `SetGlobal(slot)`: store top in the global `slot`, leaving top on the stack
+    SetGlobal,
+
+    /// `GetField(name)`: pop a struct and push its field named `constants[name]`
+    GetField,
+
+    /// `SetField(name)`: pop a value and a struct, store the value in the field named
+    /// `constants[name]` and push the value back
+    SetField,
+
+    /// `Struct(layout)`: pop one value per field of `structs[layout]`(in declaration order)
+    /// and push a new struct built from them
+    Struct,
+
+    /// `EnumVariant(layout, variant)`: pop one value per field of the variant and push a new
+    /// enum value of `enums[layout]`
+    EnumVariant,
+
+    /// `IsVariant(variant)`: pop an enum value and push whether it is of `variant`
+    IsVariant,
+
+    /// `GetVariantField(index)`: pop an enum value and push its field at `index`
+    GetVariantField,
+
+    /// Pop `b` and `a`, push `a + b`. Works on numbers and strings
+    Add,
+
+    /// Pop `b` and `a`, push `a - b`
+    Subtract,
+
+    /// Pop `b` and `a`, push `a * b`
+    Multiply,
+
+    /// Pop `b` and `a`, push `a / b`
+    Divide,
+
+    /// Pop `b` and `a`, push `a % b`
+    Modulus,
+
+    /// Pop `b` and `a`, push `a & b`
+    BitAnd,
+
+    /// Pop `b` and `a`, push `a | b`
+    BitOr,
+
+    /// Pop `b` and `a`, push `a ^ b`
+    BitXor,
+
+    /// Pop `b` and `a`, push `a << b`
+    ShiftLeft,
+
+    /// Pop `b` and `a`, push `a >> b`
+    ShiftRight,
+
+    /// Pop `b` and `a`, push `a == b`
+    Equal,
+
+    /// Pop `b` and `a`, push `a != b`
+    NotEqual,
+
+    /// Pop `b` and `a`, push `a > b`
+    Greater,
+
+    /// Pop `b` and `a`, push `a >= b`
+    GreaterEqual,
+
+    /// Pop `b` and `a`, push `a < b`
+    Less,
+
+    /// Pop `b` and `a`, push `a <= b`
+    LessEqual,
+
+    /// Pop a boolean and push its negation
+    Not,
+
+    /// Pop a number and push its negation
+    Negate,
+
+    /// `Jump(offset)`: move the instruction pointer `offset` bytes forward
+    Jump,
+
+    /// `JumpIfFalse(offset)`: move the instruction pointer `offset` bytes forward if top is
+    /// `false`. Top is left on the stack
+    JumpIfFalse,
+
+    /// `Loop(offset)`: move the instruction pointer `offset` bytes backward
+    Loop,
+
+    /// `Call(argc)`: call the function below the top `argc` values. `argc` is a single byte
+    Call,
+
+    /// Return top from the current function
+    Return,
+
+    /// Pop top and print it followed by a newline
+    Print,
+
+    /// Pop an iterable value and push an iterator over it
+    GetIter,
+
+    /// `ForIter(offset)`: push the next value of the iterator on top, or pop the iterator and
+    /// move the instruction pointer `offset` bytes forward when it's exhausted
+    ForIter,
+}
+
+impl OpCode {
+    /// Every opcode in declaration order, so that `ALL[op as usize] == op`
+    const ALL: [OpCode; 41] = [
+        OpCode::Constant,
+        OpCode::Null,
+        OpCode::True,
+        OpCode::False,
+        OpCode::Pop,
+        OpCode::GetLocal,
+        OpCode::SetLocal,
+        OpCode::GetGlobal,
+        OpCode::SetGlobal,
+        OpCode::GetField,
+        OpCode::SetField,
+        OpCode::Struct,
+        OpCode::EnumVariant,
+        OpCode::IsVariant,
+        OpCode::GetVariantField,
+        OpCode::Add,
+        OpCode::Subtract,
+        OpCode::Multiply,
+        OpCode::Divide,
+        OpCode::Modulus,
+        OpCode::BitAnd,
+        OpCode::BitOr,
+        OpCode::BitXor,
+        OpCode::ShiftLeft,
+        OpCode::ShiftRight,
+        OpCode::Equal,
+        OpCode::NotEqual,
+        OpCode::Greater,
+        OpCode::GreaterEqual,
+        OpCode::Less,
+        OpCode::LessEqual,
+        OpCode::Not,
+        OpCode::Negate,
+        OpCode::Jump,
+        OpCode::JumpIfFalse,
+        OpCode::Loop,
+        OpCode::Call,
+        OpCode::Return,
+        OpCode::Print,
+        OpCode::GetIter,
+        OpCode::ForIter,
+    ];
+
+    /// Number of bytes of operands following the opcode
+    pub fn operands_size(&self) -> usize {
+        match self {
+            OpCode::Constant
+            | OpCode::GetLocal
+            | OpCode::SetLocal
+            | OpCode::GetGlobal
+            | OpCode::SetGlobal
+            | OpCode::GetField
+            | OpCode::SetField
+            | OpCode::Struct
+            | OpCode::IsVariant
+            | OpCode::GetVariantField
+            | OpCode::Jump
+            | OpCode::JumpIfFalse
+            | OpCode::Loop
+            | OpCode::ForIter => 2,
+            OpCode::EnumVariant => 4,
+            OpCode::Call => 1,
+            _ => 0,
+        }
+    }
+
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        Self::ALL
+            .get(byte as usize)
+            .copied()
+            .filter(|op| *op as u8 == byte)
+    }
+}
+
+/// [Constant] is an entry in the constant pool of a [CompiledModule]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Constant {
+    Integer(i64),
+    Float(f64),
+    String(String),
+
+    /// Index into [CompiledModule::functions]
+    Function(usize),
+}
+
+/// [CodeObject] holds the bytecode of a single function
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CodeObject {
+    /// Name of the function
+    pub name: String,
+
+    /// Number of arguments. They occupy the first local slots
+    pub arity: usize,
+
+    /// Number of local slots including the arguments
+    pub locals: usize,
+
+    /// Encoded instructions
+    pub code: Vec<u8>,
+
+    /// `(offset, line)` pairs. The instructions starting at `offset` belong to `line` until the
+    /// next entry
+    pub lines: Vec<(usize, usize)>,
+}
+
+impl CodeObject {
+    pub fn new(name: &str, arity: usize) -> Self {
+        Self {
+            name: name.to_string(),
+            arity,
+            locals: arity,
+            ..Default::default()
+        }
+    }
+
+    pub fn write_byte(&mut self, byte: u8, line: usize) {
+        if self.lines.last().map(|(_, l)| *l) != Some(line) {
+            self.lines.push((self.code.len(), line));
+        }
+
+        self.code.push(byte);
+    }
+
+    pub fn write_op(&mut self, op: OpCode, line: usize) {
+        self.write_byte(op as u8, line);
+    }
+
+    pub fn write_u16(&mut self, value: u16, line: usize) {
+        for byte in value.to_le_bytes() {
+            self.write_byte(byte, line);
+        }
+    }
+
+    pub fn read_u16(&self, offset: usize) -> u16 {
+        u16::from_le_bytes([self.code[offset], self.code[offset + 1]])
+    }
+
+    /// Returns the source line of the instruction at `offset`
+    pub fn line_at(&self, offset: usize) -> usize {
+        match self.lines.binary_search_by_key(&offset, |(o, _)| *o) {
+            Ok(idx) => self.lines[idx].1,
+            Err(0) => 0,
+            Err(idx) => self.lines[idx - 1].1,
+        }
+    }
+}
+
+/// [StructLayout] describes the fields of a struct in declaration order
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructLayout {
+    pub name: String,
+    pub fields: Vec<String>,
+}
+
+/// [EnumLayout] describes the variants of an enum in declaration order
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumLayout {
+    pub name: String,
+    pub variants: Vec<StructLayout>,
+}
+
+/// [CompiledModule] is everything the virtual machine needs to run a program
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompiledModule {
+    /// Name of the entry point module
+    pub name: String,
+
+    /// Names of the modules the entry point module imports, directly or not
+    pub imports: Vec<String>,
+
+    /// Constant pool shared by every function
+    pub constants: Vec<Constant>,
+
+    /// Code objects of every function
+    pub functions: Vec<CodeObject>,
+
+    /// Names of global variables. A global's slot is its index
+    pub globals: Vec<String>,
+
+    /// Struct layouts referenced by [OpCode::Struct]
+    pub structs: Vec<StructLayout>,
+
+    /// Enum layouts referenced by [OpCode::EnumVariant]
+    pub enums: Vec<EnumLayout>,
+
+    /// Index of the function that starts the program
+    pub entry_point: usize,
+}
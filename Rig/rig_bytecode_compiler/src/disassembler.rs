@@ -0,0 +1,179 @@
+use crate::bytecode::{CodeObject, CompiledModule, Constant, OpCode};
+use std::fmt::Write;
+
+/// Returns a human readable listing of every function of the module.
+///
+/// Example:
+/// ```text
+/// fn #1 test::main (arity: 0, locals: 1)
+///   offset  line  instruction
+///        0     2  Constant         #0      // 5
+///        3     2  SetLocal         0
+///        6     2  Pop
+/// ```
+pub fn disassemble(module: &CompiledModule) -> String {
+    let mut output = String::new();
+
+    writeln!(output, "module {}", module.name).unwrap();
+    writeln!(output, "entry point: #{}", module.entry_point).unwrap();
+
+    writeln!(output, "\nimports:").unwrap();
+    for import in &module.imports {
+        writeln!(output, "  {}", import).unwrap();
+    }
+
+    writeln!(output, "\nconstants:").unwrap();
+    for (idx, constant) in module.constants.iter().enumerate() {
+        writeln!(
+            output,
+            "  #{:<5} {}",
+            idx,
+            describe_constant(module, constant)
+        )
+        .unwrap();
+    }
+
+    writeln!(output, "\nglobals:").unwrap();
+    for (slot, name) in module.globals.iter().enumerate() {
+        writeln!(output, "  {:<6} {}", slot, name).unwrap();
+    }
+
+    for (idx, function) in module.functions.iter().enumerate() {
+        output.push('\n');
+        output.push_str(&disassemble_function(module, idx, function));
+    }
+
+    output
+}
+
+/// Returns a listing of a single function. `idx` is its index in [CompiledModule::functions]
+pub fn disassemble_function(module: &CompiledModule, idx: usize, function: &CodeObject) -> String {
+    let mut output = String::new();
+
+    writeln!(
+        output,
+        "fn #{} {} (arity: {}, locals: {})",
+        idx, function.name, function.arity, function.locals
+    )
+    .unwrap();
+    writeln!(output, "  offset  line  instruction").unwrap();
+
+    let mut offset = 0;
+    while offset < function.code.len() {
+        let (text, size) = disassemble_instruction(module, function, offset);
+        writeln!(
+            output,
+            "  {:>6}  {:>4}  {}",
+            offset,
+            function.line_at(offset),
+            text
+        )
+        .unwrap();
+
+        offset += size;
+    }
+
+    output
+}
+
+/// Decodes the instruction at `offset`. Returns its text and its size in bytes
+fn disassemble_instruction(
+    module: &CompiledModule,
+    function: &CodeObject,
+    offset: usize,
+) -> (String, usize) {
+    let byte = function.code[offset];
+    let op = match OpCode::from_byte(byte) {
+        Some(op) => op,
+        None => return (format!("<invalid opcode 0x{:02x}>", byte), 1),
+    };
+
+    let operands_size = op.operands_size();
+    if offset + 1 + operands_size > function.code.len() {
+        return (format!("{:<16} <truncated>", format!("{:?}", op)), 1);
+    }
+
+    let u16_at = |idx: usize| function.read_u16(offset + 1 + idx * 2) as usize;
+    let next_instruction = offset + 1 + operands_size;
+
+    let (operands, comment) = match op {
+        OpCode::Constant => {
+            let idx = u16_at(0);
+            (
+                format!("#{}", idx),
+                module
+                    .constants
+                    .get(idx)
+                    .map(|c| describe_constant(module, c)),
+            )
+        }
+        OpCode::GetLocal | OpCode::SetLocal => (u16_at(0).to_string(), None),
+        OpCode::GetGlobal | OpCode::SetGlobal => {
+            let slot = u16_at(0);
+            (slot.to_string(), module.globals.get(slot).cloned())
+        }
+        OpCode::GetField | OpCode::SetField => {
+            let idx = u16_at(0);
+            (
+                format!("#{}", idx),
+                module
+                    .constants
+                    .get(idx)
+                    .map(|c| describe_constant(module, c)),
+            )
+        }
+        OpCode::Struct => {
+            let layout = u16_at(0);
+            (
+                layout.to_string(),
+                module.structs.get(layout).map(|s| s.name.clone()),
+            )
+        }
+        OpCode::EnumVariant => {
+            let (layout, variant) = (u16_at(0), u16_at(1));
+            let name = module.enums.get(layout).and_then(|e| {
+                e.variants
+                    .get(variant)
+                    .map(|v| format!("{}::{}", e.name, v.name))
+            });
+
+            (format!("{}, {}", layout, variant), name)
+        }
+        OpCode::IsVariant | OpCode::GetVariantField => (u16_at(0).to_string(), None),
+        OpCode::Jump | OpCode::JumpIfFalse | OpCode::ForIter => {
+            let distance = u16_at(0);
+            (
+                distance.to_string(),
+                Some(format!("-> {}", next_instruction + distance)),
+            )
+        }
+        OpCode::Loop => {
+            let distance = u16_at(0);
+            (
+                distance.to_string(),
+                Some(format!("-> {}", next_instruction.wrapping_sub(distance))),
+            )
+        }
+        OpCode::Call => (function.code[offset + 1].to_string(), None),
+        _ => (String::new(), None),
+    };
+
+    let mut text = format!("{:<16} {}", format!("{:?}", op), operands);
+    if let Some(comment) = comment {
+        text = format!("{:<24} // {}", text, comment);
+    }
+
+    (text.trim_end().to_string(), 1 + operands_size)
+}
+
+fn describe_constant(module: &CompiledModule, constant: &Constant) -> String {
+    match constant {
+        Constant::Integer(value) => value.to_string(),
+        Constant::Float(value) => format!("{:?}", value),
+        Constant::String(value) => format!("{:?}", value),
+        Constant::Function(idx) => match module.functions.get(*idx) {
+            Some(function) => format!("fn #{} {}", idx, function.name),
+            None => format!("fn #{}", idx),
+        },
+    }
+}
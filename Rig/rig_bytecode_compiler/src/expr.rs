@@ -0,0 +1,375 @@
+use crate::bytecode::{Constant, OpCode};
+use crate::{Compiler, FunctionState};
+use rig_ast::op::{BinaryOperator, LogicalOperator, UnaryOperator};
+use rig_error::ErrorCode;
+use rig_span::Span;
+use rig_types::checked_expr::{
+    CheckedCall, CheckedExpr, CheckedLogical, CheckedPath, CheckedStruct, CheckedVariable,
+};
+use rig_types::{ModuleId, TypeIdOrModuleId};
+use rig_utils::bug;
+
+impl<'p> Compiler<'p> {
+    pub(crate) fn compile_expr(&mut self, state: &mut FunctionState, expr: &CheckedExpr) {
+        match expr {
+            CheckedExpr::Variable(variable) => self.compile_variable(state, variable),
+            CheckedExpr::Int(int) => {
+                self.emit_constant(state, Constant::Integer(int.value), &int.span)
+            }
+            CheckedExpr::Float(float) => {
+                self.emit_constant(state, Constant::Float(float.value), &float.span)
+            }
+            CheckedExpr::String(string) => {
+                self.emit_constant(state, Constant::String(string.value.clone()), &string.span)
+            }
+            CheckedExpr::Boolean(boolean) => {
+                let op = if boolean.value {
+                    OpCode::True
+                } else {
+                    OpCode::False
+                };
+                state.emit_op(op, &boolean.span);
+            }
+            CheckedExpr::Null(null) => state.emit_op(OpCode::Null, &null.span),
+            CheckedExpr::SelfLit(self_lit) => match state.resolve_local("self") {
+                Some(slot) => self.emit_u16_operand(
+                    state,
+                    OpCode::GetLocal,
+                    slot,
+                    "Too many local variables in one function",
+                    &self_lit.span,
+                ),
+                None => bug!(self_lit, "`self` used outside of a method"),
+            },
+            CheckedExpr::Grouping(grouping) => self.compile_expr(state, &grouping.expr),
+            CheckedExpr::Get(get) => {
+                self.compile_expr(state, &get.object);
+                let name = self.make_constant(state, Constant::String(get.name.clone()), &get.span);
+                state.emit_op(OpCode::GetField, &get.span);
+                state.code.write_u16(name, get.span.starting_line);
+            }
+            CheckedExpr::Set(set) => {
+                self.compile_expr(state, &set.object);
+                self.compile_expr(state, &set.value);
+                let name = self.make_constant(state, Constant::String(set.name.clone()), &set.span);
+                state.emit_op(OpCode::SetField, &set.span);
+                state.code.write_u16(name, set.span.starting_line);
+            }
+            CheckedExpr::Path(path) => self.compile_path(state, path),
+            CheckedExpr::Call(call) => self.compile_call(state, call),
+            CheckedExpr::Struct(struct_) => self.compile_struct(state, struct_),
+            CheckedExpr::Assignment(assignment) => {
+                self.compile_expr(state, &assignment.value);
+
+                if let Some(slot) = state.resolve_local(&assignment.name) {
+                    self.emit_u16_operand(
+                        state,
+                        OpCode::SetLocal,
+                        slot,
+                        "Too many local variables in one function",
+                        &assignment.span,
+                    );
+                } else if let Some(slot) = self
+                    .globals
+                    .get(&(state.module_id, assignment.name.clone()))
+                    .copied()
+                {
+                    self.emit_u16_operand(
+                        state,
+                        OpCode::SetGlobal,
+                        slot,
+                        "Too many global variables",
+                        &assignment.span,
+                    );
+                } else {
+                    bug!(assignment, "assignment to an unknown variable");
+                }
+            }
+            CheckedExpr::Binary(binary) => {
+                self.compile_expr(state, &binary.lhs);
+                self.compile_expr(state, &binary.rhs);
+
+                let op = match binary.op {
+                    BinaryOperator::Plus => OpCode::Add,
+                    BinaryOperator::Minus => OpCode::Subtract,
+                    BinaryOperator::Multiply => OpCode::Multiply,
+                    BinaryOperator::Divide => OpCode::Divide,
+                    BinaryOperator::Modulus => OpCode::Modulus,
+                    BinaryOperator::Or => OpCode::BitOr,
+                    BinaryOperator::Xor => OpCode::BitXor,
+                    BinaryOperator::And => OpCode::BitAnd,
+                    BinaryOperator::LeftShift => OpCode::ShiftLeft,
+                    BinaryOperator::RightShift => OpCode::ShiftRight,
+                };
+                state.emit_op(op, &binary.span);
+            }
+            CheckedExpr::Logical(logical) => self.compile_logical(state, logical),
+            CheckedExpr::Unary(unary) => {
+                self.compile_expr(state, &unary.rhs);
+
+                let op = match unary.op {
+                    UnaryOperator::Not => OpCode::Not,
+                    UnaryOperator::Negate => OpCode::Negate,
+                };
+                state.emit_op(op, &unary.span);
+            }
+        }
+    }
+
+    fn compile_variable(&mut self, state: &mut FunctionState, variable: &CheckedVariable) {
+        if let Some(slot) = state.resolve_local(&variable.name) {
+            self.emit_u16_operand(
+                state,
+                OpCode::GetLocal,
+                slot,
+                "Too many local variables in one function",
+                &variable.span,
+            );
+        } else if let Some(slot) = self
+            .globals
+            .get(&(state.module_id, variable.name.clone()))
+            .copied()
+        {
+            self.emit_u16_operand(
+                state,
+                OpCode::GetGlobal,
+                slot,
+                "Too many global variables",
+                &variable.span,
+            );
+        } else if let Some(function) = self.function_code(variable.ty) {
+            self.emit_constant(state, Constant::Function(function), &variable.span);
+        } else if self.extern_function(state, variable.ty, &variable.span) {
+            state.emit_op(OpCode::Null, &variable.span);
+        } else {
+            bug!(variable, "reference to an unknown variable");
+        }
+    }
+
+    fn compile_path(&mut self, state: &mut FunctionState, path: &CheckedPath) {
+        let (name, prefix) = match path.path.split_last() {
+            Some(split) => split,
+            None => bug!(path, "empty path"),
+        };
+
+        let global = self
+            .path_module(state.module_id, prefix)
+            .and_then(|module_id| self.globals.get(&(module_id, name.clone())))
+            .copied();
+
+        if let Some(slot) = global {
+            self.emit_u16_operand(
+                state,
+                OpCode::GetGlobal,
+                slot,
+                "Too many global variables",
+                &path.span,
+            );
+        } else if let Some(function) = self.function_code(path.ty) {
+            self.emit_constant(state, Constant::Function(function), &path.span);
+        } else if self.extern_function(state, path.ty, &path.span) {
+            state.emit_op(OpCode::Null, &path.span);
+        } else if let Some(layout) = self.enum_layout(path.ty) {
+            // a variant without fields
+            let variant = self
+                .project
+                .get_enum(path.ty)
+                .and_then(|enum_| enum_.variants.iter().position(|v| &v.name == name));
+
+            match variant {
+                Some(variant) => self.emit_enum_variant(state, layout, variant, &path.span),
+                None => bug!(path, "path doesn't refer to an enum variant"),
+            }
+        } else {
+            bug!(path, "path doesn't refer to a value");
+        }
+    }
+
+    /// Resolves the module part of a path relative to `module_id`
+    fn path_module(&self, module_id: ModuleId, prefix: &[String]) -> Option<ModuleId> {
+        if prefix.is_empty() {
+            return Some(module_id);
+        }
+
+        if let Some(module) = self.project.modules.iter().find(|m| m.location == prefix) {
+            return Some(module.id);
+        }
+
+        match self
+            .project
+            .get_module(module_id)
+            .try_import(&self.project.modules, prefix)
+        {
+            Ok(TypeIdOrModuleId::ModuleId(module_id, _)) => Some(module_id),
+            _ => None,
+        }
+    }
+
+    fn compile_call(&mut self, state: &mut FunctionState, call: &CheckedCall) {
+        let mut argc = call.args.len();
+
+        match self.method_callee(call) {
+            Some((function, receiver)) => {
+                self.emit_constant(state, Constant::Function(function), &call.span);
+                self.compile_expr(state, receiver);
+                argc += 1;
+            }
+            None => self.compile_expr(state, &call.name),
+        }
+
+        for arg in &call.args {
+            self.compile_expr(state, arg);
+        }
+
+        let argc = match u8::try_from(argc) {
+            Ok(argc) => argc,
+            Err(_) => {
+                self.error(
+                    state,
+                    ErrorCode::E0014,
+                    "Too many arguments in one call",
+                    &call.span,
+                );
+                0
+            }
+        };
+
+        state.emit_op(OpCode::Call, &call.span);
+        state.code.write_byte(argc, call.span.starting_line);
+    }
+
+    /// Returns the code object of the method and the receiver if `call` is a method call
+    fn method_callee<'c>(&self, call: &'c CheckedCall) -> Option<(usize, &'c CheckedExpr)> {
+        let get = match call.name.as_ref() {
+            CheckedExpr::Get(get) => get,
+            _ => return None,
+        };
+        let struct_ = self.project.get_struct(get.object.ty())?;
+
+        struct_
+            .methods
+            .iter()
+            .find(|method| {
+                self.project
+                    .get_function(**method)
+                    .and_then(|f| f.location.last())
+                    == Some(&get.name)
+            })
+            .and_then(|method| self.function_code(*method))
+            .map(|function| (function, get.object.as_ref()))
+    }
+
+    fn compile_struct(&mut self, state: &mut FunctionState, struct_: &CheckedStruct) {
+        let project = self.project;
+
+        let (fields, target) = if let Some(layout) = self.struct_layout(struct_.ty) {
+            let fields = match project.get_struct(struct_.ty) {
+                Some(s) => s.fields.iter().map(|f| &f.name).collect::<Vec<_>>(),
+                None => bug!(struct_, "struct layout without a struct"),
+            };
+
+            (fields, StructTarget::Struct(layout))
+        } else if let Some(layout) = self.enum_layout(struct_.ty) {
+            let name = match struct_.name.as_ref() {
+                CheckedExpr::Path(path) => path.path.last(),
+                CheckedExpr::Variable(variable) => Some(&variable.name),
+                _ => None,
+            };
+            let variant = project.get_enum(struct_.ty).and_then(|enum_| {
+                enum_
+                    .variants
+                    .iter()
+                    .enumerate()
+                    .find(|(_, v)| Some(&v.name) == name)
+            });
+
+            match variant {
+                Some((idx, variant)) => (
+                    variant.value.iter().map(|f| &f.name).collect(),
+                    StructTarget::EnumVariant(layout, idx),
+                ),
+                None => bug!(struct_, "struct expression of an unknown enum variant"),
+            }
+        } else {
+            bug!(struct_, "struct expression of a type that isn't a struct");
+        };
+
+        // fields are pushed in declaration order no matter how they were written
+        for field in fields {
+            match struct_.fields.iter().find(|f| &f.name == field) {
+                Some(value) => self.compile_expr(state, &value.value),
+                None => bug!(struct_, "missing field in struct expression"),
+            }
+        }
+
+        match target {
+            StructTarget::Struct(layout) => self.emit_u16_operand(
+                state,
+                OpCode::Struct,
+                layout,
+                "Too many structs in one program",
+                &struct_.span,
+            ),
+            StructTarget::EnumVariant(layout, variant) => {
+                self.emit_enum_variant(state, layout, variant, &struct_.span)
+            }
+        }
+    }
+
+    fn emit_enum_variant(
+        &mut self,
+        state: &mut FunctionState,
+        layout: usize,
+        variant: usize,
+        span: &Span,
+    ) {
+        let layout = self.checked_u16(state, layout, "Too many enums in one program", span);
+        let variant = self.checked_u16(state, variant, "Too many enum variants", span);
+
+        state.emit_op(OpCode::EnumVariant, span);
+        state.code.write_u16(layout, span.starting_line);
+        state.code.write_u16(variant, span.starting_line);
+    }
+
+    fn compile_logical(&mut self, state: &mut FunctionState, logical: &CheckedLogical) {
+        match logical.op {
+            LogicalOperator::And => {
+                self.compile_expr(state, &logical.lhs);
+                let end = self.emit_jump(state, OpCode::JumpIfFalse, &logical.span);
+                state.emit_op(OpCode::Pop, &logical.span);
+                self.compile_expr(state, &logical.rhs);
+                self.patch_jump(state, end, &logical.span);
+            }
+            LogicalOperator::Or => {
+                self.compile_expr(state, &logical.lhs);
+                let rhs = self.emit_jump(state, OpCode::JumpIfFalse, &logical.span);
+                let end = self.emit_jump(state, OpCode::Jump, &logical.span);
+                self.patch_jump(state, rhs, &logical.span);
+                state.emit_op(OpCode::Pop, &logical.span);
+                self.compile_expr(state, &logical.rhs);
+                self.patch_jump(state, end, &logical.span);
+            }
+            _ => {
+                self.compile_expr(state, &logical.lhs);
+                self.compile_expr(state, &logical.rhs);
+
+                let op = match logical.op {
+                    LogicalOperator::Equal => OpCode::Equal,
+                    LogicalOperator::NotEqual => OpCode::NotEqual,
+                    LogicalOperator::Greater => OpCode::Greater,
+                    LogicalOperator::Less => OpCode::Less,
+                    LogicalOperator::GreaterEq => OpCode::GreaterEqual,
+                    LogicalOperator::LessEq => OpCode::LessEqual,
+                    LogicalOperator::And | LogicalOperator::Or => unreachable!(),
+                };
+                state.emit_op(op, &logical.span);
+            }
+        }
+    }
+}
+
+/// What a struct expression builds
+enum StructTarget {
+    Struct(usize),
+    EnumVariant(usize, usize),
+}
@@ -1,8 +1,426 @@
-#[cfg(test)]
-mod tests {
-    #[test]
-    fn it_works() {
-        let result = 2 + 2;
-        assert_eq!(result, 4);
+pub mod bytecode;
+pub mod disassembler;
+mod expr;
+pub mod rigc;
+mod stmt;
+
+use crate::bytecode::{CodeObject, CompiledModule, Constant, EnumLayout, OpCode, StructLayout};
+use rig_ast::function_prototype::FnType;
+use rig_error::{ErrorCode, ErrorType, RigError};
+use rig_project::Project;
+use rig_span::Span;
+use rig_types::checked_stmt::CheckedStmt;
+use rig_types::{ModuleId, TypeId};
+use std::collections::HashMap;
+
+/// Compiles every module of the project into a single [CompiledModule].
+///
+/// The project must be free of type errors. Function `0` of the output initializes the global
+/// variables of every module and then calls `main` of the entry point module, if there's one.
+pub fn compile(project: &Project) -> (CompiledModule, Vec<(ModuleId, RigError)>) {
+    let mut compiler = Compiler::new(project);
+    compiler.compile_project();
+
+    (compiler.output, compiler.errors)
+}
+
+pub(crate) struct Compiler<'p> {
+    project: &'p Project,
+    output: CompiledModule,
+
+    /// Code object of every function, keyed by module and index in `Module::functions`
+    functions: HashMap<(ModuleId, usize), usize>,
+
+    /// Layout of every struct, keyed by module and index in `Module::structs`
+    structs: HashMap<(ModuleId, usize), usize>,
+
+    /// Layout of every enum, keyed by module and index in `Module::enums`
+    enums: HashMap<(ModuleId, usize), usize>,
+
+    /// Slot of every global variable
+    globals: HashMap<(ModuleId, String), usize>,
+
+    errors: Vec<(ModuleId, RigError)>,
+}
+
+/// [FunctionState] is the state of the function currently being compiled
+pub(crate) struct FunctionState {
+    code: CodeObject,
+    module_id: ModuleId,
+
+    /// Local variables visible in each nested block with their slots
+    scopes: Vec<Vec<(String, usize)>>,
+
+    /// First unused local slot
+    next_slot: usize,
+
+    /// Innermost loop is the last one
+    loops: Vec<LoopState>,
+
+    /// `let` statements outside of blocks declare globals
+    is_module_init: bool,
+}
+
+pub(crate) struct LoopState {
+    /// Offset `continue` jumps back to
+    start: usize,
+
+    /// Operands of the jumps emitted by `break`, patched once the end of the loop is known
+    breaks: Vec<usize>,
+
+    /// `for` loops keep their iterator on the stack, `break` has to discard it
+    has_iterator: bool,
+}
+
+impl FunctionState {
+    fn new(name: &str, arity: usize, module_id: ModuleId) -> Self {
+        Self {
+            code: CodeObject::new(name, arity),
+            module_id,
+            scopes: vec![],
+            next_slot: 0,
+            loops: vec![],
+            is_module_init: false,
+        }
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(vec![]);
+    }
+
+    fn end_scope(&mut self) {
+        if let Some(scope) = self.scopes.pop() {
+            if let Some((_, first_slot)) = scope.first() {
+                // slots of a finished block can be reused by the next one
+                self.next_slot = *first_slot;
+            }
+        }
+    }
+
+    fn declare_local(&mut self, name: &str) -> usize {
+        let slot = self.next_slot;
+        self.next_slot += 1;
+        self.code.locals = self.code.locals.max(self.next_slot);
+
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push((name.to_string(), slot));
+        }
+
+        slot
+    }
+
+    fn resolve_local(&self, name: &str) -> Option<usize> {
+        self.scopes
+            .iter()
+            .rev()
+            .flat_map(|scope| scope.iter().rev())
+            .find(|(local, _)| local == name)
+            .map(|(_, slot)| *slot)
+    }
+
+    fn emit_op(&mut self, op: OpCode, span: &Span) {
+        self.code.write_op(op, span.starting_line);
+    }
+}
+
+impl<'p> Compiler<'p> {
+    fn new(project: &'p Project) -> Self {
+        Self {
+            project,
+            output: CompiledModule {
+                name: project.get_entry_point().location.join("::"),
+                imports: project
+                    .modules
+                    .iter()
+                    .filter(|module| module.id != project.entry_point)
+                    .map(|module| module.location.join("::"))
+                    .collect(),
+                ..Default::default()
+            },
+            functions: HashMap::new(),
+            structs: HashMap::new(),
+            enums: HashMap::new(),
+            globals: HashMap::new(),
+            errors: vec![],
+        }
+    }
+
+    fn compile_project(&mut self) {
+        let project = self.project;
+
+        // the initializer comes first, its code is generated after everything else is known
+        self.output.functions.push(CodeObject::new("<init>", 0));
+        self.output.entry_point = 0;
+
+        for module in &project.modules {
+            for (idx, struct_) in module.structs.iter().enumerate() {
+                self.structs
+                    .insert((module.id, idx), self.output.structs.len());
+                self.output.structs.push(StructLayout {
+                    name: struct_.location.last().cloned().unwrap_or_default(),
+                    fields: struct_.fields.iter().map(|f| f.name.clone()).collect(),
+                });
+            }
+
+            for (idx, enum_) in module.enums.iter().enumerate() {
+                self.enums.insert((module.id, idx), self.output.enums.len());
+                self.output.enums.push(EnumLayout {
+                    name: enum_.location.last().cloned().unwrap_or_default(),
+                    variants: enum_
+                        .variants
+                        .iter()
+                        .map(|v| StructLayout {
+                            name: v.name.clone(),
+                            fields: v.value.iter().map(|f| f.name.clone()).collect(),
+                        })
+                        .collect(),
+                });
+            }
+
+            // functions may refer to globals that are initialized after them
+            for stmt in &module.checked_ast {
+                if let CheckedStmt::Let(let_stmt) = stmt {
+                    self.declare_global(module.id, &let_stmt.name);
+                }
+            }
+
+            // there are no native functions yet, so extern functions don't get a code object
+            for (idx, _) in module
+                .functions
+                .iter()
+                .enumerate()
+                .filter(|(_, function)| !function.is_extern)
+            {
+                self.functions
+                    .insert((module.id, idx), self.output.functions.len());
+                self.output.functions.push(CodeObject::default());
+            }
+        }
+
+        for module in &project.modules {
+            for (idx, function) in module.functions.iter().enumerate() {
+                if function.is_extern {
+                    continue;
+                }
+
+                let mut arity = function.args.len();
+                if function.fn_type == FnType::Method {
+                    arity += 1;
+                }
+
+                let mut state = FunctionState::new(&function.location.join("::"), arity, module.id);
+                state.begin_scope();
+                if function.fn_type == FnType::Method {
+                    state.declare_local("self");
+                }
+                for arg in &function.args {
+                    state.declare_local(&arg.name);
+                }
+
+                self.compile_block(&mut state, &function.body);
+                state.emit_op(OpCode::Null, &function.span);
+                state.emit_op(OpCode::Return, &function.span);
+
+                let code_idx = self.functions[&(module.id, idx)];
+                self.output.functions[code_idx] = state.code;
+            }
+        }
+
+        self.compile_initializer();
+    }
+
+    fn compile_initializer(&mut self) {
+        let project = self.project;
+        let entry_point = project.get_entry_point();
+        let mut state = FunctionState::new("<init>", 0, entry_point.id);
+        state.is_module_init = true;
+
+        // imported modules are inserted after the modules importing them, so initialize them first
+        for module in project.modules.iter().rev() {
+            state.module_id = module.id;
+
+            for stmt in &module.checked_ast {
+                if let CheckedStmt::Let(_) = stmt {
+                    self.compile_stmt(&mut state, stmt);
+                }
+            }
+        }
+
+        let main = entry_point.functions.iter().position(|f| {
+            f.location.len() == entry_point.location.len() + 1
+                && f.location.last().map(|name| name.as_str()) == Some("main")
+                && f.args.is_empty()
+        });
+        let span = Span::for_single_char(&entry_point.absolute_path, 1, 0);
+
+        if let Some(main) = main {
+            let main = self.functions[&(entry_point.id, main)];
+            state.module_id = entry_point.id;
+            self.emit_constant(&mut state, Constant::Function(main), &span);
+            state.emit_op(OpCode::Call, &span);
+            state.code.write_byte(0, span.starting_line);
+            state.emit_op(OpCode::Pop, &span);
+        }
+
+        state.emit_op(OpCode::Null, &span);
+        state.emit_op(OpCode::Return, &span);
+        self.output.functions[0] = state.code;
+    }
+
+    fn declare_global(&mut self, module_id: ModuleId, name: &str) -> usize {
+        let slot = self.output.globals.len();
+        let module = self.project.get_module(module_id);
+
+        self.output
+            .globals
+            .push([module.location.join("::"), name.to_string()].join("::"));
+        self.globals.insert((module_id, name.to_string()), slot);
+
+        slot
+    }
+
+    /// Returns the code object of the function referred to by `type_id`
+    fn function_code(&self, type_id: TypeId) -> Option<usize> {
+        match self.project.get_type(type_id) {
+            rig_types::Type::Function(idx) => self
+                .functions
+                .get(&(type_id.get_scope_id().get_module_id(), *idx))
+                .copied(),
+            _ => None,
+        }
+    }
+
+    /// Reports a reference to a function declared in an `extern` block, returns `false` if
+    /// `type_id` isn't one
+    fn extern_function(&mut self, state: &FunctionState, type_id: TypeId, span: &Span) -> bool {
+        let name = match self.project.get_function(type_id) {
+            Some(function) if function.is_extern => function.location.join("::"),
+            _ => return false,
+        };
+
+        self.error(
+            state,
+            ErrorCode::E0032,
+            &format!(
+                "`{}` is declared in an `extern` block, but there's no native function to call",
+                name
+            ),
+            span,
+        );
+        true
+    }
+
+    fn struct_layout(&self, type_id: TypeId) -> Option<usize> {
+        match self.project.get_type(type_id) {
+            rig_types::Type::Struct(idx) => self
+                .structs
+                .get(&(type_id.get_scope_id().get_module_id(), *idx))
+                .copied(),
+            _ => None,
+        }
+    }
+
+    fn enum_layout(&self, type_id: TypeId) -> Option<usize> {
+        match self.project.get_type(type_id) {
+            rig_types::Type::Enum(idx) => self
+                .enums
+                .get(&(type_id.get_scope_id().get_module_id(), *idx))
+                .copied(),
+            _ => None,
+        }
+    }
+
+    fn emit_constant(&mut self, state: &mut FunctionState, constant: Constant, span: &Span) {
+        let idx = self.make_constant(state, constant, span);
+
+        state.emit_op(OpCode::Constant, span);
+        state.code.write_u16(idx, span.starting_line);
+    }
+
+    fn make_constant(&mut self, state: &FunctionState, constant: Constant, span: &Span) -> u16 {
+        let idx = match self
+            .output
+            .constants
+            .iter()
+            .position(|c| is_same_constant(c, &constant))
+        {
+            Some(idx) => idx,
+            None => {
+                self.output.constants.push(constant);
+                self.output.constants.len() - 1
+            }
+        };
+
+        self.checked_u16(state, idx, "Too many constants in one program", span)
+    }
+
+    fn emit_u16_operand(
+        &mut self,
+        state: &mut FunctionState,
+        op: OpCode,
+        operand: usize,
+        limit_message: &str,
+        span: &Span,
+    ) {
+        let operand = self.checked_u16(state, operand, limit_message, span);
+
+        state.emit_op(op, span);
+        state.code.write_u16(operand, span.starting_line);
+    }
+
+    /// Emits a forward jump and returns the offset of its operand
+    fn emit_jump(&mut self, state: &mut FunctionState, op: OpCode, span: &Span) -> usize {
+        state.emit_op(op, span);
+        state.code.write_u16(u16::MAX, span.starting_line);
+
+        state.code.code.len() - 2
+    }
+
+    /// Makes the jump whose operand is at `operand_offset` land on the next instruction
+    fn patch_jump(&mut self, state: &mut FunctionState, operand_offset: usize, span: &Span) {
+        let distance = state.code.code.len() - operand_offset - 2;
+        let distance = self.checked_u16(state, distance, "Too much code to jump over", span);
+
+        state.code.code[operand_offset..operand_offset + 2]
+            .copy_from_slice(&distance.to_le_bytes());
+    }
+
+    fn emit_loop(&mut self, state: &mut FunctionState, loop_start: usize, span: &Span) {
+        state.emit_op(OpCode::Loop, span);
+
+        let distance = state.code.code.len() + 2 - loop_start;
+        let distance = self.checked_u16(state, distance, "Loop body is too large", span);
+        state.code.write_u16(distance, span.starting_line);
+    }
+
+    fn checked_u16(
+        &mut self,
+        state: &FunctionState,
+        value: usize,
+        message: &str,
+        span: &Span,
+    ) -> u16 {
+        match u16::try_from(value) {
+            Ok(value) => value,
+            Err(_) => {
+                self.error(state, ErrorCode::E0014, message, span);
+                0
+            }
+        }
+    }
+
+    fn error(&mut self, state: &FunctionState, code: ErrorCode, message: &str, span: &Span) {
+        self.errors.push((
+            state.module_id,
+            RigError::with_no_hint_and_notes(ErrorType::Hard, code, message, span.clone()),
+        ));
+    }
+}
+
+/// `0.0` and `-0.0` are equal but aren't the same constant
+fn is_same_constant(a: &Constant, b: &Constant) -> bool {
+    match (a, b) {
+        (Constant::Float(a), Constant::Float(b)) => a.to_bits() == b.to_bits(),
+        _ => a == b,
     }
 }
@@ -0,0 +1,581 @@
+use crate::bytecode::{CodeObject, CompiledModule, Constant, EnumLayout, OpCode, StructLayout};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+
+/// First bytes of every `.rigc` file
+pub const MAGIC: [u8; 4] = *b"RIGC";
+
+/// Bumped whenever the layout of the file or the instruction set changes
+pub const FORMAT_VERSION: u16 = 1;
+
+const CONSTANT_INTEGER: u8 = 0;
+const CONSTANT_FLOAT: u8 = 1;
+const CONSTANT_STRING: u8 = 2;
+const CONSTANT_FUNCTION: u8 = 3;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RigcError {
+    NotARigcFile,
+    VersionMismatch {
+        found: u16,
+    },
+    UnexpectedEof,
+    InvalidConstantTag(u8),
+    InvalidString,
+    InvalidEntryPoint(u64),
+    InvalidFunctionConstant(usize),
+    InvalidCode {
+        function: String,
+        offset: usize,
+        reason: String,
+    },
+    TrailingBytes,
+}
+
+impl Display for RigcError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RigcError::NotARigcFile => write!(f, "Not a compiled rig module(bad magic number)"),
+            RigcError::VersionMismatch { found } => write!(
+                f,
+                "Compiled module has bytecode format version {}, but this VM only runs version {}. \
+                Recompile it from source",
+                found, FORMAT_VERSION
+            ),
+            RigcError::UnexpectedEof => write!(f, "Compiled module is truncated"),
+            RigcError::InvalidConstantTag(tag) => {
+                write!(
+                    f,
+                    "Compiled module contains a constant of unknown kind {}",
+                    tag
+                )
+            }
+            RigcError::InvalidString => {
+                write!(
+                    f,
+                    "Compiled module contains a string that isn't valid UTF-8"
+                )
+            }
+            RigcError::InvalidEntryPoint(idx) => {
+                write!(f, "Compiled module's entry point #{} doesn't exist", idx)
+            }
+            RigcError::InvalidFunctionConstant(idx) => {
+                write!(
+                    f,
+                    "Compiled module refers to function #{} which doesn't exist",
+                    idx
+                )
+            }
+            RigcError::InvalidCode {
+                function,
+                offset,
+                reason,
+            } => write!(
+                f,
+                "Compiled module has invalid code in `{}` at offset {}: {}",
+                function, offset, reason
+            ),
+            RigcError::TrailingBytes => {
+                write!(f, "Compiled module has unexpected bytes after its end")
+            }
+        }
+    }
+}
+
+/// Returns whether `bytes` start like a `.rigc` file
+pub fn is_rigc(bytes: &[u8]) -> bool {
+    bytes.starts_with(&MAGIC)
+}
+
+/// Serializes a compiled module into the `.rigc` format.
+///
+/// Every integer is little-endian. Lengths, counts and indices are stored as `u64`, strings as
+/// a length followed by UTF-8 bytes. A file consists of the following parts, in order:
+///
+/// | Part          | Contents                                                          |
+/// |---------------|-------------------------------------------------------------------|
+/// | Header        | [MAGIC], [FORMAT_VERSION] as `u16`                                |
+/// | Module        | name, entry point                                                 |
+/// | Imports       | count, name of every imported module                              |
+/// | Constants     | count, every constant as a tag byte followed by its payload       |
+/// | Globals       | count, name of every global                                       |
+/// | Layouts       | struct layouts, then enum layouts                                 |
+/// | Functions     | count, every code object: name, arity, locals, code, line table   |
+pub fn write_rigc(module: &CompiledModule) -> Vec<u8> {
+    let mut writer = Writer { bytes: vec![] };
+
+    writer.bytes.extend_from_slice(&MAGIC);
+    writer
+        .bytes
+        .extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+
+    writer.string(&module.name);
+    writer.usize(module.entry_point);
+
+    writer.strings(&module.imports);
+
+    writer.usize(module.constants.len());
+    for constant in &module.constants {
+        match constant {
+            Constant::Integer(value) => {
+                writer.bytes.push(CONSTANT_INTEGER);
+                writer.bytes.extend_from_slice(&value.to_le_bytes());
+            }
+            Constant::Float(value) => {
+                writer.bytes.push(CONSTANT_FLOAT);
+                writer
+                    .bytes
+                    .extend_from_slice(&value.to_bits().to_le_bytes());
+            }
+            Constant::String(value) => {
+                writer.bytes.push(CONSTANT_STRING);
+                writer.string(value);
+            }
+            Constant::Function(idx) => {
+                writer.bytes.push(CONSTANT_FUNCTION);
+                writer.usize(*idx);
+            }
+        }
+    }
+
+    writer.strings(&module.globals);
+
+    writer.usize(module.structs.len());
+    for struct_ in &module.structs {
+        writer.struct_layout(struct_);
+    }
+
+    writer.usize(module.enums.len());
+    for enum_ in &module.enums {
+        writer.string(&enum_.name);
+        writer.usize(enum_.variants.len());
+        for variant in &enum_.variants {
+            writer.struct_layout(variant);
+        }
+    }
+
+    writer.usize(module.functions.len());
+    for function in &module.functions {
+        writer.string(&function.name);
+        writer.usize(function.arity);
+        writer.usize(function.locals);
+
+        writer.usize(function.code.len());
+        writer.bytes.extend_from_slice(&function.code);
+
+        writer.usize(function.lines.len());
+        for (offset, line) in &function.lines {
+            writer.usize(*offset);
+            writer.usize(*line);
+        }
+    }
+
+    writer.bytes
+}
+
+pub fn read_rigc(bytes: &[u8]) -> Result<CompiledModule, RigcError> {
+    if !is_rigc(bytes) {
+        return Err(RigcError::NotARigcFile);
+    }
+
+    let mut reader = Reader {
+        bytes,
+        pos: MAGIC.len(),
+    };
+
+    let version = u16::from_le_bytes(reader.array()?);
+    if version != FORMAT_VERSION {
+        return Err(RigcError::VersionMismatch { found: version });
+    }
+
+    let name = reader.string()?;
+    let entry_point = reader.u64()?;
+    let imports = reader.strings()?;
+
+    let mut constants = vec![];
+    for _ in 0..reader.u64()? {
+        let constant = match reader.byte()? {
+            CONSTANT_INTEGER => Constant::Integer(i64::from_le_bytes(reader.array()?)),
+            CONSTANT_FLOAT => Constant::Float(f64::from_bits(u64::from_le_bytes(reader.array()?))),
+            CONSTANT_STRING => Constant::String(reader.string()?),
+            CONSTANT_FUNCTION => Constant::Function(reader.usize()?),
+            tag => return Err(RigcError::InvalidConstantTag(tag)),
+        };
+
+        constants.push(constant);
+    }
+
+    let globals = reader.strings()?;
+
+    let mut structs = vec![];
+    for _ in 0..reader.u64()? {
+        structs.push(reader.struct_layout()?);
+    }
+
+    let mut enums = vec![];
+    for _ in 0..reader.u64()? {
+        let name = reader.string()?;
+        let mut variants = vec![];
+        for _ in 0..reader.u64()? {
+            variants.push(reader.struct_layout()?);
+        }
+
+        enums.push(EnumLayout { name, variants });
+    }
+
+    let mut functions = vec![];
+    for _ in 0..reader.u64()? {
+        let name = reader.string()?;
+        let arity = reader.usize()?;
+        let locals = reader.usize()?;
+
+        let code_len = reader.usize()?;
+        let code = reader.take(code_len)?.to_vec();
+
+        let mut lines = vec![];
+        for _ in 0..reader.u64()? {
+            lines.push((reader.usize()?, reader.usize()?));
+        }
+
+        functions.push(CodeObject {
+            name,
+            arity,
+            locals,
+            code,
+            lines,
+        });
+    }
+
+    if reader.pos != bytes.len() {
+        return Err(RigcError::TrailingBytes);
+    }
+
+    if entry_point as usize >= functions.len() {
+        return Err(RigcError::InvalidEntryPoint(entry_point));
+    }
+
+    let module = CompiledModule {
+        name,
+        imports,
+        constants,
+        functions,
+        globals,
+        structs,
+        enums,
+        entry_point: entry_point as usize,
+    };
+    verify(&module)?;
+
+    Ok(module)
+}
+
+/// Checks that every instruction refers to constants, slots, layouts and jump targets that exist
+/// and never pops more values than its function pushed, so that a corrupted file is rejected
+/// instead of crashing the VM. Values of the wrong type are left to the VM to report
+fn verify(module: &CompiledModule) -> Result<(), RigcError> {
+    for constant in &module.constants {
+        if let Constant::Function(idx) = constant {
+            if *idx >= module.functions.len() {
+                return Err(RigcError::InvalidFunctionConstant(*idx));
+            }
+        }
+    }
+
+    for function in &module.functions {
+        verify_code(module, function)?;
+    }
+
+    Ok(())
+}
+
+fn verify_code(module: &CompiledModule, function: &CodeObject) -> Result<(), RigcError> {
+    let invalid = |offset: usize, reason: String| RigcError::InvalidCode {
+        function: function.name.clone(),
+        offset,
+        reason,
+    };
+
+    // decode everything first, so that jumps can be checked to land on an instruction
+    let code = &function.code;
+    let mut instructions = vec![];
+    let mut offset = 0;
+    while offset < code.len() {
+        let op = OpCode::from_byte(code[offset])
+            .ok_or_else(|| invalid(offset, format!("unknown opcode {:#04x}", code[offset])))?;
+        let next = offset + 1 + op.operands_size();
+        let operands = code
+            .get(offset + 1..next)
+            .ok_or_else(|| invalid(offset, format!("{:?} is missing its operands", op)))?;
+
+        instructions.push((offset, op, operands));
+        offset = next;
+    }
+
+    if !matches!(
+        instructions.last(),
+        Some((_, OpCode::Return | OpCode::Jump | OpCode::Loop, _))
+    ) {
+        return Err(invalid(
+            code.len(),
+            "execution can run past the end of the code".to_string(),
+        ));
+    }
+
+    let starts = instructions
+        .iter()
+        .map(|(offset, _, _)| *offset)
+        .collect::<HashSet<usize>>();
+    let slots = function.locals.max(function.arity);
+
+    for &(offset, op, operands) in &instructions {
+        let u16_at = |i: usize| u16::from_le_bytes([operands[i], operands[i + 1]]) as usize;
+        let next = offset + 1 + operands.len();
+
+        let reason = match op {
+            OpCode::Constant if u16_at(0) >= module.constants.len() => {
+                Some(format!("constant #{} doesn't exist", u16_at(0)))
+            }
+            OpCode::GetField | OpCode::SetField
+                if !matches!(module.constants.get(u16_at(0)), Some(Constant::String(_))) =>
+            {
+                Some(format!("constant #{} is not a field name", u16_at(0)))
+            }
+            OpCode::GetLocal | OpCode::SetLocal if u16_at(0) >= slots => {
+                Some(format!("local slot {} doesn't exist", u16_at(0)))
+            }
+            OpCode::GetGlobal | OpCode::SetGlobal if u16_at(0) >= module.globals.len() => {
+                Some(format!("global slot {} doesn't exist", u16_at(0)))
+            }
+            OpCode::Struct if u16_at(0) >= module.structs.len() => {
+                Some(format!("struct layout #{} doesn't exist", u16_at(0)))
+            }
+            OpCode::EnumVariant
+                if module
+                    .enums
+                    .get(u16_at(0))
+                    .is_none_or(|layout| u16_at(2) >= layout.variants.len()) =>
+            {
+                Some(format!(
+                    "variant {} of enum layout #{} doesn't exist",
+                    u16_at(2),
+                    u16_at(0)
+                ))
+            }
+            OpCode::Jump | OpCode::JumpIfFalse | OpCode::ForIter
+                if !starts.contains(&(next + u16_at(0))) =>
+            {
+                Some(format!(
+                    "jump target {} is not an instruction",
+                    next + u16_at(0)
+                ))
+            }
+            OpCode::Loop
+                if !next
+                    .checked_sub(u16_at(0))
+                    .is_some_and(|t| starts.contains(&t)) =>
+            {
+                Some(format!(
+                    "loop target {} bytes back is not an instruction",
+                    u16_at(0)
+                ))
+            }
+            _ => None,
+        };
+
+        if let Some(reason) = reason {
+            return Err(invalid(offset, reason));
+        }
+    }
+
+    // every path has to reach an instruction with the same number of values on the stack
+    let by_offset = instructions
+        .iter()
+        .map(|(offset, op, operands)| (*offset, (*op, *operands)))
+        .collect::<HashMap<usize, (OpCode, &[u8])>>();
+    let mut depths = HashMap::from([(0, 0)]);
+    let mut pending = vec![0];
+
+    while let Some(offset) = pending.pop() {
+        let (op, operands) = by_offset[&offset];
+        let u16_at = |i: usize| u16::from_le_bytes([operands[i], operands[i + 1]]) as usize;
+        let next = offset + 1 + operands.len();
+        let depth = depths[&offset];
+        let (pops, pushes) = stack_effect(module, op, operands);
+
+        if depth < pops {
+            return Err(invalid(
+                offset,
+                format!(
+                    "{:?} needs {} value(s) on the stack, found {}",
+                    op, pops, depth
+                ),
+            ));
+        }
+
+        let depth = depth - pops + pushes;
+        let successors = match op {
+            OpCode::Return => vec![],
+            OpCode::Jump => vec![(next + u16_at(0), depth)],
+            OpCode::Loop => vec![(next - u16_at(0), depth)],
+            OpCode::JumpIfFalse => vec![(next, depth), (next + u16_at(0), depth)],
+            // the item is pushed when falling through, the iterator is popped when jumping
+            OpCode::ForIter => vec![(next, depth + 1), (next + u16_at(0), depth - 1)],
+            _ => vec![(next, depth)],
+        };
+
+        for (target, depth) in successors {
+            match depths.get(&target) {
+                Some(known) if *known != depth => {
+                    return Err(invalid(
+                        target,
+                        format!("reached with {} and {} value(s) on the stack", known, depth),
+                    ))
+                }
+                Some(_) => (),
+                None => {
+                    depths.insert(target, depth);
+                    pending.push(target);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Number of values an instruction pops (or peeks at) and pushes. Expects its operands to be
+/// verified already
+fn stack_effect(module: &CompiledModule, op: OpCode, operands: &[u8]) -> (usize, usize) {
+    let u16_at = |i: usize| u16::from_le_bytes([operands[i], operands[i + 1]]) as usize;
+
+    match op {
+        OpCode::Constant
+        | OpCode::Null
+        | OpCode::True
+        | OpCode::False
+        | OpCode::GetLocal
+        | OpCode::GetGlobal => (0, 1),
+        OpCode::Pop | OpCode::Print => (1, 0),
+        OpCode::SetLocal
+        | OpCode::SetGlobal
+        | OpCode::GetField
+        | OpCode::IsVariant
+        | OpCode::GetVariantField
+        | OpCode::Not
+        | OpCode::Negate
+        | OpCode::JumpIfFalse
+        | OpCode::GetIter
+        | OpCode::ForIter => (1, 1),
+        OpCode::SetField
+        | OpCode::Add
+        | OpCode::Subtract
+        | OpCode::Multiply
+        | OpCode::Divide
+        | OpCode::Modulus
+        | OpCode::BitAnd
+        | OpCode::BitOr
+        | OpCode::BitXor
+        | OpCode::ShiftLeft
+        | OpCode::ShiftRight
+        | OpCode::Equal
+        | OpCode::NotEqual
+        | OpCode::Greater
+        | OpCode::GreaterEqual
+        | OpCode::Less
+        | OpCode::LessEqual => (2, 1),
+        OpCode::Struct => (module.structs[u16_at(0)].fields.len(), 1),
+        OpCode::EnumVariant => (module.enums[u16_at(0)].variants[u16_at(2)].fields.len(), 1),
+        // the called function and its arguments are replaced by the returned value
+        OpCode::Call => (operands[0] as usize + 1, 1),
+        OpCode::Return => (1, 0),
+        OpCode::Jump | OpCode::Loop => (0, 0),
+    }
+}
+
+struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    fn usize(&mut self, value: usize) {
+        self.bytes.extend_from_slice(&(value as u64).to_le_bytes());
+    }
+
+    fn string(&mut self, value: &str) {
+        self.usize(value.len());
+        self.bytes.extend_from_slice(value.as_bytes());
+    }
+
+    fn strings(&mut self, values: &[String]) {
+        self.usize(values.len());
+        for value in values {
+            self.string(value);
+        }
+    }
+
+    fn struct_layout(&mut self, layout: &StructLayout) {
+        self.string(&layout.name);
+        self.strings(&layout.fields);
+    }
+}
+
+struct Reader<'b> {
+    bytes: &'b [u8],
+    pos: usize,
+}
+
+impl<'b> Reader<'b> {
+    fn take(&mut self, count: usize) -> Result<&'b [u8], RigcError> {
+        let end = self
+            .pos
+            .checked_add(count)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(RigcError::UnexpectedEof)?;
+        let bytes = &self.bytes[self.pos..end];
+        self.pos = end;
+
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], RigcError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+
+        Ok(array)
+    }
+
+    fn byte(&mut self) -> Result<u8, RigcError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u64(&mut self) -> Result<u64, RigcError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    fn usize(&mut self) -> Result<usize, RigcError> {
+        // a value that doesn't fit can't be a valid length or index either
+        usize::try_from(self.u64()?).map_err(|_| RigcError::UnexpectedEof)
+    }
+
+    fn string(&mut self) -> Result<String, RigcError> {
+        let len = self.usize()?;
+
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|_| RigcError::InvalidString)
+    }
+
+    fn strings(&mut self) -> Result<Vec<String>, RigcError> {
+        let mut strings = vec![];
+        for _ in 0..self.u64()? {
+            strings.push(self.string()?);
+        }
+
+        Ok(strings)
+    }
+
+    fn struct_layout(&mut self) -> Result<StructLayout, RigcError> {
+        Ok(StructLayout {
+            name: self.string()?,
+            fields: self.strings()?,
+        })
+    }
+}
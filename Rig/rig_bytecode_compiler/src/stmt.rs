@@ -0,0 +1,325 @@
+use crate::bytecode::OpCode;
+use crate::{Compiler, FunctionState, LoopState};
+use rig_error::ErrorCode;
+use rig_span::Span;
+use rig_types::checked_expr::CheckedExpr;
+use rig_types::checked_stmt::{
+    CheckedBlockStmt, CheckedForStmt, CheckedIfStmt, CheckedLetStmt, CheckedMatchArms,
+    CheckedMatchStmt, CheckedStmt, CheckedWhileStmt,
+};
+use rig_types::TypeId;
+use rig_utils::bug;
+
+/// Name of the hidden local holding the value being matched. It can't clash with user
+/// variables because it isn't a valid identifier
+const MATCHED_VALUE_LOCAL: &str = "<matched>";
+
+impl<'p> Compiler<'p> {
+    pub(crate) fn compile_stmt(&mut self, state: &mut FunctionState, stmt: &CheckedStmt) {
+        match stmt {
+            // functions are compiled from `Module::functions`, modules have nothing to run
+            CheckedStmt::Fn(_) | CheckedStmt::Mod => (),
+            CheckedStmt::Let(let_stmt) => self.compile_let(state, let_stmt),
+            CheckedStmt::If(if_stmt) => self.compile_if(state, if_stmt),
+            CheckedStmt::While(while_stmt) => self.compile_while(state, while_stmt),
+            CheckedStmt::For(for_stmt) => self.compile_for(state, for_stmt),
+            CheckedStmt::Print(print_stmt) => {
+                self.compile_expr(state, &print_stmt.expr);
+                state.emit_op(OpCode::Print, &print_stmt.span);
+            }
+            CheckedStmt::Return(return_stmt) => {
+                self.compile_expr(state, &return_stmt.expr);
+                state.emit_op(OpCode::Return, &return_stmt.span);
+            }
+            CheckedStmt::Block(block) => self.compile_block(state, block),
+            CheckedStmt::Expr(expr_stmt) => {
+                self.compile_expr(state, &expr_stmt.expr);
+                state.emit_op(OpCode::Pop, &expr_stmt.span);
+            }
+            CheckedStmt::Break(break_stmt) => self.compile_break(state, &break_stmt.span),
+            CheckedStmt::Continue(continue_stmt) => {
+                match state.loops.last().map(|loop_| loop_.start) {
+                    Some(start) => self.emit_loop(state, start, &continue_stmt.span),
+                    None => self.error(
+                        state,
+                        ErrorCode::E0013,
+                        "`continue` outside of a loop",
+                        &continue_stmt.span,
+                    ),
+                }
+            }
+            CheckedStmt::Match(match_stmt) => self.compile_match(state, match_stmt),
+        }
+    }
+
+    pub(crate) fn compile_block(&mut self, state: &mut FunctionState, block: &CheckedBlockStmt) {
+        state.begin_scope();
+        for stmt in &block.stmts {
+            self.compile_stmt(state, stmt);
+        }
+        state.end_scope();
+    }
+
+    fn compile_let(&mut self, state: &mut FunctionState, let_stmt: &CheckedLetStmt) {
+        // compile the value first so that `let x = x;` refers to the outer `x`
+        self.compile_expr(state, &let_stmt.expr.expr);
+
+        if state.is_module_init && state.scopes.is_empty() {
+            let slot = match self.globals.get(&(state.module_id, let_stmt.name.clone())) {
+                Some(slot) => *slot,
+                None => bug!(let_stmt, "global variable wasn't declared"),
+            };
+
+            self.emit_u16_operand(
+                state,
+                OpCode::SetGlobal,
+                slot,
+                "Too many global variables",
+                &let_stmt.span,
+            );
+        } else {
+            let slot = state.declare_local(&let_stmt.name);
+            self.emit_u16_operand(
+                state,
+                OpCode::SetLocal,
+                slot,
+                "Too many local variables in one function",
+                &let_stmt.span,
+            );
+        }
+
+        state.emit_op(OpCode::Pop, &let_stmt.span);
+    }
+
+    fn compile_if(&mut self, state: &mut FunctionState, if_stmt: &CheckedIfStmt) {
+        self.compile_expr(state, &if_stmt.condition);
+        let else_jump = self.emit_jump(state, OpCode::JumpIfFalse, &if_stmt.span);
+        state.emit_op(OpCode::Pop, &if_stmt.span);
+
+        self.compile_block(state, &if_stmt.body);
+        let end_jump = self.emit_jump(state, OpCode::Jump, &if_stmt.span);
+
+        self.patch_jump(state, else_jump, &if_stmt.span);
+        state.emit_op(OpCode::Pop, &if_stmt.span);
+
+        if let Some(else_branch) = &if_stmt.then_branch {
+            self.compile_if(state, else_branch);
+        }
+
+        self.patch_jump(state, end_jump, &if_stmt.span);
+    }
+
+    fn compile_while(&mut self, state: &mut FunctionState, while_stmt: &CheckedWhileStmt) {
+        let start = state.code.code.len();
+        self.compile_expr(state, &while_stmt.condition);
+        let exit_jump = self.emit_jump(state, OpCode::JumpIfFalse, &while_stmt.span);
+        state.emit_op(OpCode::Pop, &while_stmt.span);
+
+        state.loops.push(LoopState {
+            start,
+            breaks: vec![],
+            has_iterator: false,
+        });
+        self.compile_block(state, &while_stmt.body);
+        self.emit_loop(state, start, &while_stmt.span);
+
+        self.patch_jump(state, exit_jump, &while_stmt.span);
+        state.emit_op(OpCode::Pop, &while_stmt.span);
+
+        // `break` doesn't leave the condition on the stack, so it skips the `Pop` above
+        self.end_loop(state, &while_stmt.span);
+    }
+
+    fn compile_for(&mut self, state: &mut FunctionState, for_stmt: &CheckedForStmt) {
+        self.compile_expr(state, &for_stmt.iterable);
+        state.emit_op(OpCode::GetIter, &for_stmt.span);
+
+        let start = state.code.code.len();
+        let exit_jump = self.emit_jump(state, OpCode::ForIter, &for_stmt.span);
+
+        state.begin_scope();
+        let slot = state.declare_local(&for_stmt.var_name);
+        self.emit_u16_operand(
+            state,
+            OpCode::SetLocal,
+            slot,
+            "Too many local variables in one function",
+            &for_stmt.span,
+        );
+        state.emit_op(OpCode::Pop, &for_stmt.span);
+
+        state.loops.push(LoopState {
+            start,
+            breaks: vec![],
+            has_iterator: true,
+        });
+        self.compile_block(state, &for_stmt.body);
+        self.emit_loop(state, start, &for_stmt.span);
+        state.end_scope();
+
+        self.patch_jump(state, exit_jump, &for_stmt.span);
+        self.end_loop(state, &for_stmt.span);
+    }
+
+    fn end_loop(&mut self, state: &mut FunctionState, span: &Span) {
+        let loop_ = match state.loops.pop() {
+            Some(loop_) => loop_,
+            None => bug!(span, "attempt to end a loop that never started"),
+        };
+
+        for break_jump in loop_.breaks {
+            self.patch_jump(state, break_jump, span);
+        }
+    }
+
+    fn compile_break(&mut self, state: &mut FunctionState, span: &Span) {
+        let has_iterator = match state.loops.last() {
+            Some(loop_) => loop_.has_iterator,
+            None => {
+                self.error(state, ErrorCode::E0013, "`break` outside of a loop", span);
+                return;
+            }
+        };
+
+        if has_iterator {
+            state.emit_op(OpCode::Pop, span);
+        }
+
+        let jump = self.emit_jump(state, OpCode::Jump, span);
+        if let Some(loop_) = state.loops.last_mut() {
+            loop_.breaks.push(jump);
+        }
+    }
+
+    fn compile_match(&mut self, state: &mut FunctionState, match_stmt: &CheckedMatchStmt) {
+        self.compile_expr(state, &match_stmt.matched_expr);
+
+        state.begin_scope();
+        let matched_slot = state.declare_local(MATCHED_VALUE_LOCAL);
+        self.emit_u16_operand(
+            state,
+            OpCode::SetLocal,
+            matched_slot,
+            "Too many local variables in one function",
+            &match_stmt.span,
+        );
+        state.emit_op(OpCode::Pop, &match_stmt.span);
+
+        let matched_ty = match_stmt.matched_expr.ty();
+        let mut end_jumps = vec![];
+
+        for arm in &match_stmt.arms {
+            state.begin_scope();
+
+            if is_catch_all(&arm.expr) {
+                self.compile_block(state, &arm.body);
+                end_jumps.push(self.emit_jump(state, OpCode::Jump, &arm.span));
+                state.end_scope();
+                continue;
+            }
+
+            self.emit_u16_operand(
+                state,
+                OpCode::GetLocal,
+                matched_slot,
+                "Too many local variables in one function",
+                &arm.span,
+            );
+
+            let variant = self.matched_variant(matched_ty, arm);
+            match variant {
+                Some(variant) => self.emit_u16_operand(
+                    state,
+                    OpCode::IsVariant,
+                    variant,
+                    "Too many enum variants",
+                    &arm.span,
+                ),
+                None => {
+                    self.compile_expr(state, &arm.expr);
+                    state.emit_op(OpCode::Equal, &arm.span);
+                }
+            }
+
+            let next_arm = self.emit_jump(state, OpCode::JumpIfFalse, &arm.span);
+            state.emit_op(OpCode::Pop, &arm.span);
+
+            if let Some(variant) = variant {
+                self.bind_variant_fields(state, matched_ty, variant, matched_slot, &arm.span);
+            }
+
+            self.compile_block(state, &arm.body);
+            end_jumps.push(self.emit_jump(state, OpCode::Jump, &arm.span));
+
+            self.patch_jump(state, next_arm, &arm.span);
+            state.emit_op(OpCode::Pop, &arm.span);
+            state.end_scope();
+        }
+
+        for end_jump in end_jumps {
+            self.patch_jump(state, end_jump, &match_stmt.span);
+        }
+
+        state.end_scope();
+    }
+
+    /// Returns the index of the variant the arm matches if the matched value is an enum
+    fn matched_variant(&self, matched_ty: TypeId, arm: &CheckedMatchArms) -> Option<usize> {
+        let enum_ = self.project.get_enum(matched_ty)?;
+        let name = match &arm.expr {
+            CheckedExpr::Variable(variable) => &variable.name,
+            CheckedExpr::Path(path) => path.path.last()?,
+            _ => return None,
+        };
+
+        enum_.variants.iter().position(|v| &v.name == name)
+    }
+
+    /// Puts the fields of the matched variant into scope as local variables
+    fn bind_variant_fields(
+        &mut self,
+        state: &mut FunctionState,
+        matched_ty: TypeId,
+        variant: usize,
+        matched_slot: usize,
+        span: &Span,
+    ) {
+        let project = self.project;
+        let enum_ = match project.get_enum(matched_ty) {
+            Some(enum_) => enum_,
+            None => bug!(matched_ty, "matched value isn't an enum"),
+        };
+
+        for (idx, field) in enum_.variants[variant].value.iter().enumerate() {
+            self.emit_u16_operand(
+                state,
+                OpCode::GetLocal,
+                matched_slot,
+                "Too many local variables in one function",
+                span,
+            );
+            self.emit_u16_operand(
+                state,
+                OpCode::GetVariantField,
+                idx,
+                "Too many fields in one enum variant",
+                span,
+            );
+
+            let slot = state.declare_local(&field.name);
+            self.emit_u16_operand(
+                state,
+                OpCode::SetLocal,
+                slot,
+                "Too many local variables in one function",
+                span,
+            );
+            state.emit_op(OpCode::Pop, span);
+        }
+    }
+}
+
+/// `_` matches anything
+fn is_catch_all(expr: &CheckedExpr) -> bool {
+    matches!(expr, CheckedExpr::Variable(variable) if variable.name == "_")
+}
@@ -0,0 +1,579 @@
+use rig_ast::function_prototype::FnType;
+use rig_ast::op::{BinaryOperator, LogicalOperator};
+use rig_ast::visibility::Visibility;
+use rig_bytecode_compiler::bytecode::{Constant, OpCode};
+use rig_bytecode_compiler::compile;
+use rig_error::ErrorCode;
+use rig_project::Project;
+use rig_span::Span;
+use rig_types::checked_expr::*;
+use rig_types::checked_stmt::*;
+use rig_types::{
+    EnumType, EnumVariantType, EnumVariantValueType, FunctionArgument, FunctionType, Module,
+    ModuleId, ScopeId, StructFieldType, StructType, Type, TypeId,
+};
+use rig_vm::Vm;
+
+fn span() -> Span {
+    Span::for_single_char("test.rig", 1, 0)
+}
+
+fn user_type(idx: usize) -> TypeId {
+    TypeId(ScopeId(ModuleId(0), 0), idx, false)
+}
+
+fn block(stmts: Vec<CheckedStmt>) -> CheckedBlockStmt {
+    CheckedBlockStmt {
+        scope_id: ScopeId(ModuleId(0), 0),
+        returns: false,
+        stmts,
+        span: span(),
+    }
+}
+
+fn function(name: &str, args: &[&str], fn_type: FnType, body: Vec<CheckedStmt>) -> FunctionType {
+    FunctionType {
+        location: vec![String::from("test"), name.to_string()],
+        args: args
+            .iter()
+            .map(|arg| FunctionArgument {
+                name: arg.to_string(),
+                ty: TypeId::INTEGER,
+                span: span(),
+            })
+            .collect(),
+        return_ty: None,
+        body: block(body),
+        visibility: Visibility::NotPub,
+        fn_type,
+        is_extern: false,
+        span: span(),
+    }
+}
+
+/// Builds a project with a single module. `main` must be the first function
+fn project(functions: Vec<FunctionType>) -> Project {
+    let mut module = Module::new(
+        String::from("test.rig"),
+        vec![String::from("test")],
+        String::new(),
+        vec![],
+    );
+    for idx in 0..functions.len() {
+        module.types.push(Type::Function(idx));
+    }
+    module.functions = functions;
+
+    let mut project = Project::new();
+    project.insert_module(module);
+    project
+}
+
+fn run(project: &Project) -> String {
+    let (compiled, errors) = compile(project);
+    assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+
+    let mut out = Vec::new();
+    if let Err(e) = Vm::new(&compiled).run(&mut out) {
+        panic!("unexpected runtime error: {}", e);
+    }
+
+    String::from_utf8(out).unwrap()
+}
+
+fn int(value: i64) -> CheckedExpr {
+    CheckedExpr::Int(CheckedInteger {
+        value,
+        ty: TypeId::INTEGER,
+        span: span(),
+    })
+}
+
+fn string(value: &str) -> CheckedExpr {
+    CheckedExpr::String(CheckedString {
+        value: value.to_string(),
+        ty: TypeId::STRING,
+        span: span(),
+    })
+}
+
+fn boolean(value: bool) -> CheckedExpr {
+    CheckedExpr::Boolean(CheckedBoolean {
+        value,
+        ty: TypeId::BOOLEAN,
+        span: span(),
+    })
+}
+
+fn var(name: &str, ty: TypeId) -> CheckedExpr {
+    CheckedExpr::Variable(CheckedVariable {
+        name: name.to_string(),
+        ty,
+        span: span(),
+    })
+}
+
+fn binary(lhs: CheckedExpr, op: BinaryOperator, rhs: CheckedExpr) -> CheckedExpr {
+    CheckedExpr::Binary(CheckedBinary {
+        lhs: Box::new(lhs),
+        op,
+        rhs: Box::new(rhs),
+        ty: TypeId::INTEGER,
+        span: span(),
+    })
+}
+
+fn logical(lhs: CheckedExpr, op: LogicalOperator, rhs: CheckedExpr) -> CheckedExpr {
+    CheckedExpr::Logical(CheckedLogical {
+        lhs: Box::new(lhs),
+        op,
+        rhs: Box::new(rhs),
+        ty: TypeId::BOOLEAN,
+        span: span(),
+    })
+}
+
+fn call(callee: CheckedExpr, args: Vec<CheckedExpr>) -> CheckedExpr {
+    CheckedExpr::Call(CheckedCall {
+        name: Box::new(callee),
+        args: args.into_iter().map(Box::new).collect(),
+        ty: TypeId::INTEGER,
+        span: span(),
+    })
+}
+
+fn let_(name: &str, expr: CheckedExpr) -> CheckedStmt {
+    CheckedStmt::Let(CheckedLetStmt {
+        name: name.to_string(),
+        var_ty: expr.ty(),
+        expr: CheckedExprStmt { expr, span: span() },
+        span: span(),
+    })
+}
+
+fn assign(name: &str, value: CheckedExpr) -> CheckedStmt {
+    CheckedStmt::Expr(CheckedExprStmt {
+        expr: CheckedExpr::Assignment(CheckedAssignment {
+            name: name.to_string(),
+            ty: value.ty(),
+            value: Box::new(value),
+            span: span(),
+        }),
+        span: span(),
+    })
+}
+
+fn print(expr: CheckedExpr) -> CheckedStmt {
+    CheckedStmt::Print(CheckedPrintStmt { expr, span: span() })
+}
+
+fn if_(
+    condition: CheckedExpr,
+    body: Vec<CheckedStmt>,
+    else_: Option<CheckedIfStmt>,
+) -> CheckedIfStmt {
+    CheckedIfStmt {
+        condition,
+        body: block(body),
+        then_branch: else_.map(Box::new),
+        span: span(),
+    }
+}
+
+fn break_() -> CheckedStmt {
+    CheckedStmt::Break(CheckedBreakStmt { span: span() })
+}
+
+fn continue_() -> CheckedStmt {
+    CheckedStmt::Continue(CheckedContinueStmt { span: span() })
+}
+
+#[test]
+fn test_lower_print_of_binary_expression() {
+    let main = function(
+        "main",
+        &[],
+        FnType::Fn,
+        vec![print(binary(int(40), BinaryOperator::Plus, int(2)))],
+    );
+    let (module, errors) = compile(&project(vec![main]));
+    assert!(errors.is_empty());
+
+    assert_eq!(
+        module.constants,
+        vec![
+            Constant::Integer(40),
+            Constant::Integer(2),
+            Constant::Function(1)
+        ]
+    );
+    assert_eq!(module.entry_point, 0);
+
+    let main = &module.functions[1];
+    assert_eq!(main.name, "test::main");
+    assert_eq!(
+        main.code,
+        vec![
+            OpCode::Constant as u8,
+            0,
+            0,
+            OpCode::Constant as u8,
+            1,
+            0,
+            OpCode::Add as u8,
+            OpCode::Print as u8,
+            OpCode::Null as u8,
+            OpCode::Return as u8,
+        ]
+    );
+}
+
+#[test]
+fn test_while_with_break_and_continue() {
+    let i = || var("i", TypeId::INTEGER);
+    let sum = || var("sum", TypeId::INTEGER);
+
+    // sum of odd numbers up to 10
+    let main = function(
+        "main",
+        &[],
+        FnType::Fn,
+        vec![
+            let_("i", int(0)),
+            let_("sum", int(0)),
+            CheckedStmt::While(CheckedWhileStmt {
+                condition: boolean(true),
+                body: block(vec![
+                    assign("i", binary(i(), BinaryOperator::Plus, int(1))),
+                    CheckedStmt::If(if_(
+                        logical(i(), LogicalOperator::Greater, int(10)),
+                        vec![break_()],
+                        None,
+                    )),
+                    CheckedStmt::If(if_(
+                        logical(
+                            binary(i(), BinaryOperator::Modulus, int(2)),
+                            LogicalOperator::Equal,
+                            int(0),
+                        ),
+                        vec![continue_()],
+                        None,
+                    )),
+                    assign("sum", binary(sum(), BinaryOperator::Plus, i())),
+                ]),
+                span: span(),
+            }),
+            print(sum()),
+        ],
+    );
+
+    assert_eq!(run(&project(vec![main])), "25\n");
+}
+
+#[test]
+fn test_for_with_break() {
+    let main = function(
+        "main",
+        &[],
+        FnType::Fn,
+        vec![
+            CheckedStmt::For(CheckedForStmt {
+                iterable: string("abcd"),
+                var_name: String::from("ch"),
+                var_type: TypeId::STRING,
+                body: block(vec![
+                    CheckedStmt::If(if_(
+                        logical(
+                            var("ch", TypeId::STRING),
+                            LogicalOperator::Equal,
+                            string("c"),
+                        ),
+                        vec![break_()],
+                        None,
+                    )),
+                    print(var("ch", TypeId::STRING)),
+                ]),
+                span: span(),
+            }),
+            print(string("done")),
+        ],
+    );
+
+    assert_eq!(run(&project(vec![main])), "a\nb\ndone\n");
+}
+
+#[test]
+fn test_if_else_chain_and_short_circuit() {
+    let grade = |score: i64| {
+        CheckedStmt::If(if_(
+            logical(int(score), LogicalOperator::GreaterEq, int(90)),
+            vec![print(string("A"))],
+            Some(if_(
+                logical(
+                    logical(int(score), LogicalOperator::GreaterEq, int(50)),
+                    LogicalOperator::And,
+                    logical(int(score), LogicalOperator::Less, int(90)),
+                ),
+                vec![print(string("B"))],
+                Some(if_(boolean(true), vec![print(string("F"))], None)),
+            )),
+        ))
+    };
+
+    // the right hand side would fail with a division by zero if it were evaluated
+    let short_circuit = print(logical(
+        boolean(true),
+        LogicalOperator::Or,
+        logical(
+            binary(int(1), BinaryOperator::Divide, int(0)),
+            LogicalOperator::Equal,
+            int(0),
+        ),
+    ));
+
+    let main = function(
+        "main",
+        &[],
+        FnType::Fn,
+        vec![grade(95), grade(70), grade(10), short_circuit],
+    );
+
+    assert_eq!(run(&project(vec![main])), "A\nB\nF\ntrue\n");
+}
+
+#[test]
+fn test_function_calls_and_globals() {
+    let add = function(
+        "add",
+        &["a", "b"],
+        FnType::Fn,
+        vec![CheckedStmt::Return(CheckedReturnStmt {
+            expr: binary(
+                binary(
+                    var("a", TypeId::INTEGER),
+                    BinaryOperator::Plus,
+                    var("b", TypeId::INTEGER),
+                ),
+                BinaryOperator::Plus,
+                var("offset", TypeId::INTEGER),
+            ),
+            span: span(),
+        })],
+    );
+    let main = function(
+        "main",
+        &[],
+        FnType::Fn,
+        vec![print(call(
+            var("add", user_type(1)),
+            vec![int(1), call(var("add", user_type(1)), vec![int(2), int(3)])],
+        ))],
+    );
+
+    let mut project = project(vec![main, add]);
+    project.get_entry_point_mut().checked_ast = vec![let_("offset", int(100))];
+
+    assert_eq!(run(&project), "206\n");
+}
+
+#[test]
+fn test_struct_and_method_call() {
+    let point_ty = user_type(2);
+    let field = |name: &str| {
+        CheckedExpr::Get(CheckedGet {
+            object: Box::new(CheckedExpr::SelfLit(CheckedSelf {
+                ty: point_ty,
+                span: span(),
+            })),
+            name: name.to_string(),
+            ty: TypeId::INTEGER,
+            span: span(),
+        })
+    };
+
+    let sum = function(
+        "sum",
+        &["scale"],
+        FnType::Method,
+        vec![CheckedStmt::Return(CheckedReturnStmt {
+            expr: binary(
+                binary(field("x"), BinaryOperator::Plus, field("y")),
+                BinaryOperator::Multiply,
+                var("scale", TypeId::INTEGER),
+            ),
+            span: span(),
+        })],
+    );
+    let point = |name: &str| var(name, point_ty);
+    let main = function(
+        "main",
+        &[],
+        FnType::Fn,
+        vec![
+            // fields are written out of declaration order on purpose
+            let_(
+                "p",
+                CheckedExpr::Struct(CheckedStruct {
+                    name: Box::new(point("Point")),
+                    fields: ["y", "x"]
+                        .iter()
+                        .zip([5, 2])
+                        .map(|(name, value)| CheckedStructField {
+                            name: name.to_string(),
+                            value: Box::new(int(value)),
+                            ty: TypeId::INTEGER,
+                            span: span(),
+                        })
+                        .collect(),
+                    ty: point_ty,
+                    span: span(),
+                }),
+            ),
+            print(point("p")),
+            print(call(
+                CheckedExpr::Get(CheckedGet {
+                    object: Box::new(point("p")),
+                    name: String::from("sum"),
+                    ty: user_type(1),
+                    span: span(),
+                }),
+                vec![int(10)],
+            )),
+        ],
+    );
+
+    let mut project = project(vec![main, sum]);
+    let module = project.get_entry_point_mut();
+    module.types.push(Type::Struct(0));
+    module.structs.push(StructType {
+        location: vec![String::from("test"), String::from("Point")],
+        fields: ["x", "y"]
+            .iter()
+            .map(|name| StructFieldType {
+                name: name.to_string(),
+                ty: TypeId::INTEGER,
+                visibility: Visibility::NotPub,
+                span: span(),
+            })
+            .collect(),
+        methods: vec![user_type(1)],
+        helpers: vec![],
+        visibility: Visibility::NotPub,
+        span: span(),
+    });
+
+    assert_eq!(run(&project), "Point { x: 2, y: 5 }\n70\n");
+}
+
+#[test]
+fn test_match_on_enum() {
+    let shape_ty = user_type(1);
+    let path = |variant: &str| {
+        CheckedExpr::Path(CheckedPath {
+            path: vec![String::from("Shape"), variant.to_string()],
+            ty: shape_ty,
+            span: span(),
+        })
+    };
+    let arm = |pattern: CheckedExpr, body: Vec<CheckedStmt>| CheckedMatchArms {
+        expr: pattern,
+        body: block(body),
+        span: span(),
+    };
+    let match_ = |value: CheckedExpr| {
+        CheckedStmt::Match(CheckedMatchStmt {
+            matched_expr: value,
+            arms: vec![
+                arm(
+                    var("Circle", shape_ty),
+                    vec![print(var("radius", TypeId::INTEGER))],
+                ),
+                arm(
+                    var("Square", shape_ty),
+                    vec![print(binary(
+                        var("side", TypeId::INTEGER),
+                        BinaryOperator::Multiply,
+                        var("side", TypeId::INTEGER),
+                    ))],
+                ),
+                arm(var("_", shape_ty), vec![print(string("nothing"))]),
+            ],
+            span: span(),
+        })
+    };
+
+    let main = function(
+        "main",
+        &[],
+        FnType::Fn,
+        vec![
+            match_(CheckedExpr::Struct(CheckedStruct {
+                name: Box::new(path("Square")),
+                fields: vec![CheckedStructField {
+                    name: String::from("side"),
+                    value: Box::new(int(4)),
+                    ty: TypeId::INTEGER,
+                    span: span(),
+                }],
+                ty: shape_ty,
+                span: span(),
+            })),
+            match_(path("Empty")),
+        ],
+    );
+
+    let mut project = project(vec![main]);
+    let module = project.get_entry_point_mut();
+    module.types.push(Type::Enum(0));
+    let variant = |name: &str, fields: &[&str]| EnumVariantType {
+        name: name.to_string(),
+        value: fields
+            .iter()
+            .map(|field| EnumVariantValueType {
+                name: field.to_string(),
+                value_ty: TypeId::INTEGER,
+                span: span(),
+            })
+            .collect(),
+        span: span(),
+    };
+    module.enums.push(EnumType {
+        location: vec![String::from("test"), String::from("Shape")],
+        variants: vec![
+            variant("Circle", &["radius"]),
+            variant("Square", &["side"]),
+            variant("Empty", &[]),
+        ],
+        visibility: Visibility::NotPub,
+        span: span(),
+    });
+
+    assert_eq!(run(&project), "16\nnothing\n");
+}
+
+#[test]
+fn test_break_outside_of_loop() {
+    let main = function("main", &[], FnType::Fn, vec![break_(), continue_()]);
+    let (_, errors) = compile(&project(vec![main]));
+
+    assert_eq!(errors.len(), 2);
+    assert!(errors
+        .iter()
+        .all(|(module_id, e)| *module_id == ModuleId(0) && e.error_code == ErrorCode::E0013));
+}
+
+#[test]
+fn test_call_of_extern_function() {
+    let mut puts = function("puts", &["s"], FnType::Fn, vec![]);
+    puts.is_extern = true;
+    let main = function(
+        "main",
+        &[],
+        FnType::Fn,
+        vec![print(call(var("puts", user_type(1)), vec![string("hi")]))],
+    );
+    let (_, errors) = compile(&project(vec![main, puts]));
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].1.error_code, ErrorCode::E0032);
+}
@@ -0,0 +1,109 @@
+use rig_bytecode_compiler::bytecode::{
+    CodeObject, CompiledModule, Constant, EnumLayout, OpCode, StructLayout,
+};
+use rig_bytecode_compiler::disassembler::{disassemble, disassemble_function};
+
+#[test]
+fn test_disassemble_module() {
+    let mut main = CodeObject::new("test::main", 0);
+    main.locals = 1;
+    main.write_op(OpCode::Constant, 2);
+    main.write_u16(0, 2);
+    main.write_op(OpCode::SetGlobal, 2);
+    main.write_u16(0, 2);
+    main.write_op(OpCode::JumpIfFalse, 3);
+    main.write_u16(4, 3);
+    main.write_op(OpCode::Constant, 4);
+    main.write_u16(1, 4);
+    main.write_op(OpCode::Call, 4);
+    main.write_byte(0, 4);
+    main.write_op(OpCode::Loop, 5);
+    main.write_u16(14, 5);
+    main.write_op(OpCode::Null, 6);
+    main.write_op(OpCode::Return, 6);
+
+    let module = CompiledModule {
+        name: String::from("test"),
+        imports: vec![String::from("std::io")],
+        constants: vec![
+            Constant::String(String::from("hi\n")),
+            Constant::Function(0),
+        ],
+        functions: vec![main],
+        globals: vec![String::from("test::greeting")],
+        ..Default::default()
+    };
+
+    assert_eq!(
+        disassemble(&module),
+        "module test
+entry point: #0
+
+imports:
+  std::io
+
+constants:
+  #0     \"hi\\n\"
+  #1     fn #0 test::main
+
+globals:
+  0      test::greeting
+
+fn #0 test::main (arity: 0, locals: 1)
+  offset  line  instruction
+       0     2  Constant         #0      // \"hi\\n\"
+       3     2  SetGlobal        0       // test::greeting
+       6     3  JumpIfFalse      4       // -> 13
+       9     4  Constant         #1      // fn #0 test::main
+      12     4  Call             0
+      14     5  Loop             14      // -> 3
+      17     6  Null
+      18     6  Return
+"
+    );
+}
+
+#[test]
+fn test_disassemble_layouts_and_invalid_code() {
+    let mut function = CodeObject::new("test::build", 0);
+    function.write_op(OpCode::Struct, 1);
+    function.write_u16(0, 1);
+    function.write_op(OpCode::EnumVariant, 1);
+    function.write_u16(0, 1);
+    function.write_u16(1, 1);
+    function.write_byte(0xff, 2);
+    function.write_op(OpCode::GetLocal, 2);
+
+    let module = CompiledModule {
+        functions: vec![function.clone()],
+        structs: vec![StructLayout {
+            name: String::from("Point"),
+            fields: vec![String::from("x")],
+        }],
+        enums: vec![EnumLayout {
+            name: String::from("Shape"),
+            variants: vec![
+                StructLayout {
+                    name: String::from("Empty"),
+                    fields: vec![],
+                },
+                StructLayout {
+                    name: String::from("Circle"),
+                    fields: vec![String::from("radius")],
+                },
+            ],
+        }],
+        ..Default::default()
+    };
+
+    assert_eq!(
+        disassemble_function(&module, 0, &function),
+        "fn #0 test::build (arity: 0, locals: 0)
+  offset  line  instruction
+       0     1  Struct           0       // Point
+       3     1  EnumVariant      0, 1    // Shape::Circle
+       8     2  <invalid opcode 0xff>
+       9     2  GetLocal         <truncated>
+"
+    );
+}
@@ -0,0 +1,67 @@
+use rig_bytecode_compiler::compile;
+use rig_error::explain::EXPLANATIONS;
+use rig_error::ErrorCode;
+use rig_project::parsed_module::ParsedModule;
+use rig_session::{DebugInfo, Session};
+use rig_typeck::TypeChecker;
+use std::path::PathBuf;
+
+/// Codes of all errors and warnings reported for `source`, in the order they're reported
+fn error_codes(source: &str) -> Vec<ErrorCode> {
+    let parsed_module = ParsedModule::new(PathBuf::from("example.rig"), source.to_string());
+    let mut codes = parsed_module
+        .lexer_errors
+        .iter()
+        .chain(&parsed_module.parser_errors)
+        .map(|error| error.error_code)
+        .collect::<Vec<_>>();
+    if !codes.is_empty() {
+        return codes;
+    }
+
+    let session = Session {
+        search_paths: vec![],
+        debug: DebugInfo::None,
+    };
+    let mut typechecker = TypeChecker::new(parsed_module, &session);
+    typechecker.do_typechecking();
+    codes.extend(
+        typechecker
+            .typechecking_errors
+            .values()
+            .flatten()
+            .map(|error| error.error_code),
+    );
+    if typechecker.has_errors() {
+        return codes;
+    }
+
+    let (_, compiler_errors) = compile(&typechecker.project);
+    codes.extend(compiler_errors.iter().map(|(_, error)| error.error_code));
+    codes
+}
+
+#[test]
+fn test_explanation_examples() {
+    for explanation in EXPLANATIONS {
+        for block in explanation.code_blocks() {
+            let codes = error_codes(&block.code);
+
+            match block.info.split_once(',') {
+                Some(("compile_fail", code)) => assert_eq!(
+                    codes.first().map(|code| format!("{:?}", code)).as_deref(),
+                    Some(code),
+                    "erroneous example of {:?}",
+                    explanation.code
+                ),
+                _ if block.info == "ignore" => {}
+                _ => assert!(
+                    codes.is_empty(),
+                    "corrected example of {:?} reports {:?}",
+                    explanation.code,
+                    codes
+                ),
+            }
+        }
+    }
+}
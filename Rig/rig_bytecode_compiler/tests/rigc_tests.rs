@@ -0,0 +1,235 @@
+use rig_bytecode_compiler::bytecode::{
+    CodeObject, CompiledModule, Constant, EnumLayout, OpCode, StructLayout,
+};
+use rig_bytecode_compiler::compile;
+use rig_bytecode_compiler::rigc::{read_rigc, write_rigc, RigcError, FORMAT_VERSION, MAGIC};
+use rig_project::parsed_module::ParsedModule;
+use rig_session::{DebugInfo, Session};
+use rig_typeck::TypeChecker;
+use rig_vm::Vm;
+use std::path::PathBuf;
+
+/// `print Point { x: 1.5, y: "two" }; print add(40, 2); print Shape::Empty;`
+fn sample_module() -> CompiledModule {
+    let mut main = CodeObject::new("test::main", 0);
+    main.write_op(OpCode::Constant, 1);
+    main.write_u16(0, 1);
+    main.write_op(OpCode::Constant, 1);
+    main.write_u16(1, 1);
+    main.write_op(OpCode::Struct, 1);
+    main.write_u16(0, 1);
+    main.write_op(OpCode::Print, 1);
+    main.write_op(OpCode::Constant, 2);
+    main.write_u16(2, 2);
+    main.write_op(OpCode::Constant, 2);
+    main.write_u16(3, 2);
+    main.write_op(OpCode::Constant, 2);
+    main.write_u16(4, 2);
+    main.write_op(OpCode::Call, 2);
+    main.write_byte(2, 2);
+    main.write_op(OpCode::Print, 2);
+    main.write_op(OpCode::EnumVariant, 3);
+    main.write_u16(0, 3);
+    main.write_u16(0, 3);
+    main.write_op(OpCode::Print, 3);
+    main.write_op(OpCode::Null, 4);
+    main.write_op(OpCode::Return, 4);
+
+    let mut add = CodeObject::new("test::add", 2);
+    add.write_op(OpCode::GetLocal, 7);
+    add.write_u16(0, 7);
+    add.write_op(OpCode::GetLocal, 7);
+    add.write_u16(1, 7);
+    add.write_op(OpCode::Add, 7);
+    add.write_op(OpCode::Return, 7);
+
+    CompiledModule {
+        name: String::from("test"),
+        imports: vec![String::from("test::math")],
+        constants: vec![
+            Constant::Float(1.5),
+            Constant::String(String::from("two")),
+            Constant::Function(1),
+            Constant::Integer(40),
+            Constant::Integer(2),
+        ],
+        functions: vec![main, add],
+        globals: vec![String::from("test::unused")],
+        structs: vec![StructLayout {
+            name: String::from("Point"),
+            fields: vec![String::from("x"), String::from("y")],
+        }],
+        enums: vec![EnumLayout {
+            name: String::from("Shape"),
+            variants: vec![StructLayout {
+                name: String::from("Empty"),
+                fields: vec![],
+            }],
+        }],
+        entry_point: 0,
+    }
+}
+
+fn run(module: &CompiledModule) -> String {
+    let mut out = Vec::new();
+    if let Err(e) = Vm::new(module).run(&mut out) {
+        panic!("unexpected runtime error: {}", e);
+    }
+
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn test_round_trip_through_file() {
+    let module = sample_module();
+    let path = std::env::temp_dir().join(format!("rigc_round_trip_{}.rigc", std::process::id()));
+
+    std::fs::write(&path, write_rigc(&module)).unwrap();
+    let loaded = read_rigc(&std::fs::read(&path).unwrap());
+    std::fs::remove_file(&path).unwrap();
+
+    let loaded = loaded.unwrap();
+    assert_eq!(loaded, module);
+    assert_eq!(run(&loaded), run(&module));
+    assert_eq!(
+        run(&loaded),
+        "Point { x: 1.5, y: \"two\" }\n42\nShape::Empty\n"
+    );
+}
+
+#[test]
+fn test_round_trip_from_source() {
+    let source = "struct Point { x: int, y: int }
+        impl Point {
+            fn new(x: int, y: int) -> Self { return Point { x: x, y: y }; }
+            fn sum(self) -> int { return self.x + self.y; }
+        }
+        enum Shape { Circle { r: int }, Empty }
+        fn fact(n: int) -> int {
+            if n < 2 { return 1; }
+            return n * fact(n - 1);
+        }
+        fn main() {
+            print Point::new(40, 2).sum();
+            print fact(5);
+            let shape = Shape::Circle { r: 3 };
+            match shape {
+                Circle => { print r; }
+                _ => { print \"other\"; }
+            }
+            for c in \"ab\" {
+                if c == \"b\" { break; }
+                print c;
+            }
+            let i = 0;
+            while i < 3 && true {
+                i = i + 1;
+                if i == 2 || false { continue; }
+                print i;
+            }
+        }";
+    let session = Session {
+        search_paths: vec![],
+        debug: DebugInfo::None,
+    };
+    let mut typechecker = TypeChecker::new(
+        ParsedModule::new(PathBuf::from("test.rig"), source.to_string()),
+        &session,
+    );
+    typechecker.do_typechecking();
+    assert!(!typechecker.has_errors());
+
+    let (module, errors) = compile(&typechecker.project);
+    assert!(errors.is_empty());
+
+    let loaded = read_rigc(&write_rigc(&module)).unwrap();
+    assert_eq!(loaded, module);
+    assert_eq!(run(&loaded), "42\n120\n3\na\n1\n3\n");
+}
+
+#[test]
+fn test_reject_other_versions() {
+    let mut bytes = write_rigc(&sample_module());
+    bytes[MAGIC.len()..MAGIC.len() + 2].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+
+    let error = read_rigc(&bytes).unwrap_err();
+    assert_eq!(
+        error,
+        RigcError::VersionMismatch {
+            found: FORMAT_VERSION + 1
+        }
+    );
+    assert!(error.to_string().contains("Recompile it from source"));
+}
+
+#[test]
+fn test_reject_malformed_files() {
+    let bytes = write_rigc(&sample_module());
+
+    assert_eq!(read_rigc(b"print 1;").unwrap_err(), RigcError::NotARigcFile);
+    assert_eq!(
+        read_rigc(&bytes[..bytes.len() - 1]).unwrap_err(),
+        RigcError::UnexpectedEof
+    );
+
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert_eq!(read_rigc(&trailing).unwrap_err(), RigcError::TrailingBytes);
+}
+
+#[test]
+fn test_reject_corrupted_code() {
+    let load = |corrupt: fn(&mut CompiledModule)| {
+        let mut module = sample_module();
+        corrupt(&mut module);
+        read_rigc(&write_rigc(&module)).unwrap_err()
+    };
+    let invalid_code = |function: &str, offset: usize, reason: &str| RigcError::InvalidCode {
+        function: function.to_string(),
+        offset,
+        reason: reason.to_string(),
+    };
+
+    assert_eq!(
+        load(|m| m.functions[0].code[1..3].copy_from_slice(&0xFFFFu16.to_le_bytes())),
+        invalid_code("test::main", 0, "constant #65535 doesn't exist")
+    );
+    assert_eq!(
+        load(|m| m.functions[1].code[0] = 0xFF),
+        invalid_code("test::add", 0, "unknown opcode 0xff")
+    );
+    assert_eq!(
+        load(|m| m.functions[1].code[1] = 9),
+        invalid_code("test::add", 0, "local slot 9 doesn't exist")
+    );
+    assert_eq!(
+        load(|m| m.functions[1].code = vec![OpCode::Jump as u8, 0xFF, 0]),
+        invalid_code("test::add", 0, "jump target 258 is not an instruction")
+    );
+    assert_eq!(
+        load(|m| {
+            m.functions[1].code.pop();
+        }),
+        invalid_code("test::add", 7, "execution can run past the end of the code")
+    );
+    assert_eq!(
+        load(|m| m.functions[1].code =
+            vec![OpCode::Pop as u8, OpCode::Pop as u8, OpCode::Return as u8]),
+        invalid_code("test::add", 0, "Pop needs 1 value(s) on the stack, found 0")
+    );
+    assert_eq!(
+        load(|m| m.functions[1].code = vec![
+            OpCode::True as u8,
+            OpCode::JumpIfFalse as u8,
+            1,
+            0,
+            OpCode::Null as u8,
+            OpCode::Return as u8
+        ]),
+        invalid_code("test::add", 5, "reached with 1 and 2 value(s) on the stack")
+    );
+    assert_eq!(
+        load(|m| m.constants[2] = Constant::Function(7)),
+        RigcError::InvalidFunctionConstant(7)
+    );
+}
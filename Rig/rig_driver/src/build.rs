@@ -0,0 +1,37 @@
+use crate::diagnostics::ErrorFormat;
+use crate::run::compile_file;
+use colored::Colorize;
+use rig_bytecode_compiler::rigc::write_rigc;
+use rig_error::context::DiagnosticsContext;
+use rig_session::{DebugInfo, Session};
+use std::env::current_dir;
+use std::path::PathBuf;
+
+pub fn build(file_name: String, output: Option<String>, error_format: ErrorFormat) {
+    let session = Session {
+        search_paths: vec![current_dir().unwrap()],
+        debug: DebugInfo::None,
+    };
+
+    let mut diagnostics = DiagnosticsContext::new();
+    let compiled_module = compile_file(&file_name, &session, &mut diagnostics);
+    diagnostics.emit(&mut *error_format.emitter());
+
+    let compiled_module = match compiled_module {
+        Some(compiled_module) => compiled_module,
+        None => std::process::exit(1),
+    };
+
+    let output = match output {
+        Some(output) => PathBuf::from(output),
+        None => PathBuf::from(&file_name).with_extension("rigc"),
+    };
+
+    if let Err(e) = std::fs::write(&output, write_rigc(&compiled_module)) {
+        eprintln!(
+            "{}",
+            format!("Failed to write \"{}\": {}", output.display(), e).red()
+        );
+        std::process::exit(1);
+    }
+}
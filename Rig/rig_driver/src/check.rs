@@ -0,0 +1,72 @@
+use crate::diagnostics::ErrorFormat;
+use crate::run::parse_file;
+use colored::Colorize;
+use rig_error::context::DiagnosticsContext;
+use rig_session::{DebugInfo, Session};
+use rig_typeck::TypeChecker;
+use std::env::current_dir;
+
+/// Lexes, parses and typechecks the program starting at `file_name` without compiling it, and
+/// prints how many errors and warnings were emitted in the human readable format. Exits with 1
+/// if there are errors, or warnings with `deny_warnings`
+pub fn check(file_name: String, deny_warnings: bool, error_format: ErrorFormat) {
+    let diagnostics = match collect_diagnostics(&file_name) {
+        Some(diagnostics) => diagnostics,
+        None => std::process::exit(1),
+    };
+
+    diagnostics.emit(&mut *error_format.emitter());
+    let (errors, warnings) = (diagnostics.error_count(), diagnostics.warning_count());
+    if error_format == ErrorFormat::Human {
+        eprintln!("{}", summary(errors, warnings));
+    }
+    if errors > 0 || (deny_warnings && warnings > 0) {
+        std::process::exit(1);
+    }
+}
+
+/// Lexes, parses and typechecks the program starting at `file_name` and collects the
+/// diagnostics of all its files. Returns `None` if the file can't be read
+pub fn collect_diagnostics(file_name: &str) -> Option<DiagnosticsContext> {
+    let session = Session {
+        search_paths: vec![current_dir().unwrap()],
+        debug: DebugInfo::None,
+    };
+
+    let parsed_module = parse_file(file_name)?;
+
+    let mut diagnostics = DiagnosticsContext::new();
+    diagnostics.extend(&parsed_module.lexer_errors, &parsed_module.file_content);
+
+    // the tokens of a file with lexer errors aren't parsed further
+    if !parsed_module.has_lexer_errors() {
+        diagnostics.extend(&parsed_module.parser_errors, &parsed_module.file_content);
+
+        let mut type_checker = TypeChecker::new(parsed_module, &session);
+        type_checker.do_typechecking();
+        type_checker.report_errors(&mut diagnostics);
+    }
+
+    Some(diagnostics)
+}
+
+/// `2 errors, 1 warning emitted`
+fn summary(errors: usize, warnings: usize) -> String {
+    let plural = |count: usize, name: &str| {
+        format!("{} {}{}", count, name, if count == 1 { "" } else { "s" })
+    };
+
+    format!(
+        "{}, {} emitted",
+        if errors > 0 {
+            plural(errors, "error").bright_red().bold()
+        } else {
+            plural(errors, "error").bold()
+        },
+        if warnings > 0 {
+            plural(warnings, "warning").bright_yellow().bold()
+        } else {
+            plural(warnings, "warning").bold()
+        }
+    )
+}
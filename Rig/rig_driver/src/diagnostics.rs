@@ -0,0 +1,37 @@
+use rig_error::emitter::{Emitter, JsonEmitter, SarifEmitter, TerminalEmitter};
+use std::str::FromStr;
+
+/// How diagnostics are printed
+#[derive(Clone, Copy, PartialEq)]
+pub enum ErrorFormat {
+    Human,
+    Json,
+    Sarif,
+}
+
+impl FromStr for ErrorFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(Self::Human),
+            "json" => Ok(Self::Json),
+            "sarif" => Ok(Self::Sarif),
+            _ => Err(format!(
+                "Unknown error format '{}'. Expected one of 'human', 'json', 'sarif'.",
+                s
+            )),
+        }
+    }
+}
+
+impl ErrorFormat {
+    /// Emitter printing diagnostics to stderr in this format
+    pub fn emitter(self) -> Box<dyn Emitter> {
+        match self {
+            ErrorFormat::Human => Box::new(TerminalEmitter),
+            ErrorFormat::Json => Box::new(JsonEmitter::new(std::io::stderr())),
+            ErrorFormat::Sarif => Box::new(SarifEmitter::new(std::io::stderr())),
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use colored::Colorize;
+use rig_error::explain::{Explanation, EXPLANATIONS};
+use rig_error::ErrorCode;
+
+pub fn explain(error_code: Option<String>, list: bool) {
+    if list {
+        for explanation in EXPLANATIONS {
+            println!(
+                "{}  {}",
+                format!("{:?}", explanation.code).bold(),
+                explanation.summary
+            );
+        }
+        return;
+    }
+
+    let error_code = error_code.unwrap();
+    match error_code.parse::<ErrorCode>() {
+        Ok(error_code) => print_explanation(error_code.explanation()),
+        Err(message) => {
+            eprintln!("{}: {}", "error".bright_red().bold(), message.bold());
+            eprintln!(
+                "{}",
+                format!(
+                    "{} run `rig explain --list` to see all error codes",
+                    "help:".green()
+                )
+                .bold()
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Prints the text of an explanation, with the lines of its examples in a gutter
+fn print_explanation(explanation: &Explanation) {
+    println!(
+        "{}\n",
+        format!("{:?}: {}", explanation.code, explanation.summary).bold()
+    );
+
+    let mut in_code_block = false;
+    for line in explanation.text.lines() {
+        if line.starts_with("```") || (in_code_block && line.is_empty()) {
+            in_code_block ^= line.starts_with("```");
+            println!("{}", "  |".bright_blue().bold());
+        } else if in_code_block {
+            println!("{} {}", "  |".bright_blue().bold(), line);
+        } else {
+            println!("{}", line);
+        }
+    }
+}
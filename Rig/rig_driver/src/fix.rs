@@ -0,0 +1,69 @@
+use crate::check::collect_diagnostics;
+use colored::Colorize;
+use rig_error::suggestion::{apply_suggestions, unified_diff, Applicability, Suggestion};
+
+/// Applies the machine-applicable suggestions of the diagnostics of the program starting at
+/// `file_name` to the files they are made for, or prints the changes as a diff with `dry_run`.
+/// Fixed code can have new diagnostics, which are fixed by running it again
+pub fn fix(file_name: String, dry_run: bool) {
+    let diagnostics = match collect_diagnostics(&file_name) {
+        Some(diagnostics) => diagnostics,
+        None => std::process::exit(1),
+    };
+
+    let mut files: Vec<(&str, Vec<&Suggestion>)> = vec![];
+    for error in diagnostics.sorted() {
+        for suggestion in &error.suggestions {
+            if suggestion.applicability != Applicability::MachineApplicable {
+                continue;
+            }
+
+            let file_name = suggestion.span.file_name.as_str();
+            match files.iter_mut().find(|(name, _)| *name == file_name) {
+                Some((_, suggestions)) => suggestions.push(suggestion),
+                None => files.push((file_name, vec![suggestion])),
+            }
+        }
+    }
+
+    if files.is_empty() {
+        eprintln!("No fixes to apply");
+        return;
+    }
+
+    for (file_name, suggestions) in files {
+        let source = match diagnostics.source(file_name) {
+            Some(source) => source.to_string(),
+            None => match std::fs::read_to_string(file_name) {
+                Ok(source) => source,
+                Err(e) => {
+                    eprintln!(
+                        "{}",
+                        format!("Failed to open file \"{}\": {}", file_name, e).red()
+                    );
+                    std::process::exit(1);
+                }
+            },
+        };
+
+        let (fixed, applied) = apply_suggestions(&source, &suggestions);
+        if dry_run {
+            print!("{}", unified_diff(file_name, &source, &fixed));
+            continue;
+        }
+
+        if let Err(e) = std::fs::write(file_name, fixed) {
+            eprintln!(
+                "{}",
+                format!("Failed to write \"{}\": {}", file_name, e).red()
+            );
+            std::process::exit(1);
+        }
+        eprintln!(
+            "Applied {} {} to {}",
+            applied,
+            if applied == 1 { "fix" } else { "fixes" },
+            file_name
+        );
+    }
+}
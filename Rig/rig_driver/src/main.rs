@@ -1,6 +1,16 @@
-#![feature(panic_info_message)]
+mod build;
+mod check;
+mod diagnostics;
+mod explain;
+mod fix;
 mod run;
+mod unpretty;
 
+use crate::build::build;
+use crate::check::check;
+use crate::diagnostics::ErrorFormat;
+use crate::explain::explain;
+use crate::fix::fix;
 use crate::run::run;
 use clap::{Parser, Subcommand};
 use std::panic;
@@ -16,20 +26,66 @@ struct RigCLI {
 #[derive(Subcommand)]
 enum Subcommands {
     Run {
-        /// File to execute
+        /// File to execute. Compiled modules(`.rigc`) are run without recompiling them
         file: String,
 
-        /// Output generated unpretty stuff
+        /// Print the lexical tokens, the AST or the bytecode instead of running the program
         #[clap(short, long, name = "type")]
         unpretty: Option<OutputType>,
 
-        /// Reconstruct source code from ast(debugging purpose)
+        /// Print source code reconstructed from the AST and check that it parses to the same
+        /// AST(debugging purpose)
         #[clap(short, long)]
         reconstruct_from_ast: bool,
+
+        /// How to print diagnostics: 'human', 'json'(one object per line) or 'sarif'
+        #[clap(long, default_value = "human")]
+        error_format: ErrorFormat,
     },
+    /// Compile a program into a `.rigc` file
+    Build {
+        /// Entry point of the program
+        file: String,
+
+        /// Where to write the compiled module. Defaults to the file name with `.rigc` extension
+        #[clap(short, long)]
+        output: Option<String>,
+
+        /// How to print diagnostics: 'human', 'json'(one object per line) or 'sarif'
+        #[clap(long, default_value = "human")]
+        error_format: ErrorFormat,
+    },
+    /// Check a program for errors without compiling it
+    Check {
+        /// Entry point of the program
+        file: String,
+
+        /// Fail if there are warnings
+        #[clap(long)]
+        deny_warnings: bool,
+
+        /// How to print diagnostics: 'human', 'json'(one object per line) or 'sarif'
+        #[clap(long, default_value = "human")]
+        error_format: ErrorFormat,
+    },
+    /// Apply the fixes suggested by diagnostics of a program to its files
+    Fix {
+        /// Entry point of the program
+        file: String,
+
+        /// Print the fixes as a diff instead of applying them
+        #[clap(long)]
+        dry_run: bool,
+    },
+    /// Show a detailed explanation of an error code, with examples
     Explain {
-        /// Error code to explain
-        error_code: String,
+        /// Error code to explain, like `E0005`
+        #[clap(required_unless_present = "list")]
+        error_code: Option<String>,
+
+        /// List all error codes with a short summary
+        #[clap(short, long)]
+        list: bool,
     },
 }
 
@@ -62,7 +118,7 @@ fn main() {
         eprintln!("Internal compiler error: Compiler panicked\n");
         eprintln!("Backtrace:\n{:?}", backtrace::Backtrace::new());
 
-        if let Some(message) = pi.message() {
+        if let Some(message) = pi.payload_as_str() {
             eprintln!("Panic message:\n\n{}\n", message);
         }
         if let Some(location) = pi.location() {
@@ -77,7 +133,19 @@ fn main() {
             file,
             unpretty,
             reconstruct_from_ast,
-        } => run(file, unpretty, reconstruct_from_ast),
-        Subcommands::Explain { error_code: _ } => {}
+            error_format,
+        } => run(file, unpretty, reconstruct_from_ast, error_format),
+        Subcommands::Build {
+            file,
+            output,
+            error_format,
+        } => build(file, output, error_format),
+        Subcommands::Check {
+            file,
+            deny_warnings,
+            error_format,
+        } => check(file, deny_warnings, error_format),
+        Subcommands::Fix { file, dry_run } => fix(file, dry_run),
+        Subcommands::Explain { error_code, list } => explain(error_code, list),
     }
 }
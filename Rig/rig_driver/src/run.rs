@@ -1,41 +1,161 @@
+use crate::diagnostics::ErrorFormat;
+use crate::unpretty::print_unpretty;
 use crate::OutputType;
 use colored::Colorize;
 use std::env::current_dir;
 
+use rig_bytecode_compiler::bytecode::CompiledModule;
+use rig_bytecode_compiler::compile;
+use rig_bytecode_compiler::disassembler::disassemble;
+use rig_bytecode_compiler::rigc::read_rigc;
+use rig_error::context::DiagnosticsContext;
 use rig_project::parsed_module::ParsedModule;
 use rig_session::{DebugInfo, Session};
 use rig_typeck::TypeChecker;
+use rig_vm::Vm;
 use std::path::PathBuf;
 
-pub fn run(file_name: String, _unpretty: Option<OutputType>, _reconstruct_from_ast: bool) {
-    let file_content = match std::fs::read_to_string(&file_name) {
+pub fn run(
+    file_name: String,
+    unpretty: Option<OutputType>,
+    reconstruct_from_ast: bool,
+    error_format: ErrorFormat,
+) {
+    let session = Session {
+        search_paths: vec![current_dir().unwrap()],
+        debug: match unpretty {
+            Some(OutputType::LexicalTokens) => DebugInfo::LexicalTokens,
+            Some(OutputType::Ast) => DebugInfo::AST,
+            Some(OutputType::Bytecode) => DebugInfo::Bytecode,
+            None => DebugInfo::None,
+        },
+    };
+
+    if reconstruct_from_ast || matches!(session.debug, DebugInfo::LexicalTokens | DebugInfo::AST) {
+        if file_name.ends_with(".rigc") {
+            eprintln!(
+                "{}",
+                format!(
+                    "\"{}\" is a compiled module, it has no source code",
+                    file_name
+                )
+                .red()
+            );
+            std::process::exit(1);
+        }
+
+        if !print_unpretty(&file_name, &session, reconstruct_from_ast) {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let compiled_module = if file_name.ends_with(".rigc") {
+        load_compiled_module(&file_name)
+    } else {
+        let mut diagnostics = DiagnosticsContext::new();
+        let compiled_module = compile_file(&file_name, &session, &mut diagnostics);
+        diagnostics.emit(&mut *error_format.emitter());
+
+        compiled_module
+    };
+    let compiled_module = match compiled_module {
+        Some(compiled_module) => compiled_module,
+        None => std::process::exit(1),
+    };
+
+    if let DebugInfo::Bytecode = session.debug {
+        print!("{}", disassemble(&compiled_module));
+        return;
+    }
+
+    let mut vm = Vm::new(&compiled_module);
+    if let Err(e) = vm.run(&mut std::io::stdout()) {
+        eprintln!("{}", e.to_string().red());
+        std::process::exit(1);
+    }
+}
+
+/// Lexes, parses, typechecks and compiles the program starting at `file_name`. Errors are
+/// added to `diagnostics`, `None` is returned if the program can't be compiled
+pub fn compile_file(
+    file_name: &str,
+    session: &Session,
+    diagnostics: &mut DiagnosticsContext,
+) -> Option<CompiledModule> {
+    let parsed_module = parse_file(file_name)?;
+
+    if parsed_module.has_lexer_errors() {
+        diagnostics.extend(&parsed_module.lexer_errors, &parsed_module.file_content);
+        return None;
+    }
+
+    diagnostics.extend(&parsed_module.parser_errors, &parsed_module.file_content);
+    let has_parser_errors = parsed_module.has_parser_errors();
+
+    let mut type_checker = TypeChecker::new(parsed_module, session);
+    type_checker.do_typechecking();
+    type_checker.report_errors(diagnostics);
+
+    if has_parser_errors || type_checker.has_errors() {
+        return None;
+    }
+
+    let (compiled_module, compiler_errors) = compile(&type_checker.project);
+    if !compiler_errors.is_empty() {
+        for (module_id, error) in compiler_errors {
+            diagnostics.add(
+                error,
+                &type_checker.project.get_module(module_id).source_code,
+            );
+        }
+        return None;
+    }
+
+    Some(compiled_module)
+}
+
+/// Lexes and parses the entry point of a program. Errors are left to the caller, `None` is
+/// returned if the file can't be read
+pub fn parse_file(file_name: &str) -> Option<ParsedModule> {
+    let file_content = match std::fs::read_to_string(file_name) {
         Ok(f) => f,
         Err(e) => {
             eprintln!(
                 "{}",
                 format!("Failed to open file \"{}\": {}", file_name, e).red()
             );
-            return;
+            return None;
         }
     };
 
-    let parsed_module = ParsedModule::new(
+    Some(ParsedModule::new(
         PathBuf::from(file_name).canonicalize().unwrap(),
         file_content,
-    );
-
-    if parsed_module.has_lexer_errors() {
-        parsed_module.print_lexer_errors();
-        std::process::exit(1);
-    }
-
-    parsed_module.print_parser_errors();
+    ))
+}
 
-    let session = Session {
-        search_paths: vec![current_dir().unwrap()],
-        debug: DebugInfo::None,
+/// Reads a `.rigc` file. Errors are printed, `None` is returned if the file can't be loaded
+fn load_compiled_module(file_name: &str) -> Option<CompiledModule> {
+    let bytes = match std::fs::read(file_name) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            eprintln!(
+                "{}",
+                format!("Failed to open file \"{}\": {}", file_name, e).red()
+            );
+            return None;
+        }
     };
-    let mut type_checker = TypeChecker::new(parsed_module, &session);
-    type_checker.do_typechecking();
-    type_checker.print_errors();
+
+    match read_rigc(&bytes) {
+        Ok(compiled_module) => Some(compiled_module),
+        Err(e) => {
+            eprintln!(
+                "{}",
+                format!("Failed to load \"{}\": {}", file_name, e).red()
+            );
+            None
+        }
+    }
 }
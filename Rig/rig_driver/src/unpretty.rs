@@ -0,0 +1,123 @@
+use colored::Colorize;
+use rig_ast::stmt::Stmt;
+use rig_ast::tree::render_ast;
+use rig_project::parsed_module::ParsedModule;
+use rig_session::{DebugInfo, Session};
+use std::path::PathBuf;
+
+/// Prints the tokens or the AST of `file_name` as requested by `session.debug`, and the source
+/// reconstructed from the AST with `reconstruct_from_ast`. Returns `false` if the file can't be
+/// read or parsed, or if the reconstructed source doesn't parse to the same AST
+pub fn print_unpretty(file_name: &str, session: &Session, reconstruct_from_ast: bool) -> bool {
+    let file_content = match std::fs::read_to_string(file_name) {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!(
+                "{}",
+                format!("Failed to open file \"{}\": {}", file_name, e).red()
+            );
+            return false;
+        }
+    };
+    let parsed_module = ParsedModule::new(PathBuf::from(file_name), file_content);
+
+    if let DebugInfo::LexicalTokens = session.debug {
+        for token in &parsed_module.lexical_tokens {
+            let span = &token.span;
+            println!(
+                "{:<16} {:<20} {:?}",
+                format!(
+                    "{}:{}-{}:{}",
+                    span.starting_line,
+                    span.starting_line_offset + 1,
+                    span.ending_line,
+                    span.ending_line_end_offset + 1
+                ),
+                format!("{:?}", token.token_type),
+                token.lexeme
+            );
+        }
+    }
+
+    parsed_module.print_lexer_errors();
+    if parsed_module.has_lexer_errors() {
+        return false;
+    }
+    if !reconstruct_from_ast && !matches!(session.debug, DebugInfo::AST) {
+        return true;
+    }
+
+    parsed_module.print_parser_errors();
+    if parsed_module.has_parser_errors() {
+        return false;
+    }
+
+    if let DebugInfo::AST = session.debug {
+        print!("{}", render_ast(&parsed_module.ast, true));
+    }
+
+    if reconstruct_from_ast {
+        let reconstructed = reconstruct(&parsed_module.ast);
+        print!("{}", reconstructed);
+
+        return check_reconstruction(&parsed_module, reconstructed);
+    }
+
+    true
+}
+
+/// Source code of the statements, as printed by [Stmt::to_string]
+fn reconstruct(ast: &[Stmt]) -> String {
+    ast.iter().map(|stmt| stmt.to_string(0) + "\n").collect()
+}
+
+/// Parses the reconstructed source and reports if it doesn't parse to the AST it was
+/// reconstructed from
+fn check_reconstruction(parsed_module: &ParsedModule, reconstructed: String) -> bool {
+    let reparsed = ParsedModule::new(
+        PathBuf::from(format!("{} (reconstructed)", parsed_module.absolute_path)),
+        reconstructed,
+    );
+
+    if reparsed.has_lexer_errors() || reparsed.has_parser_errors() {
+        eprintln!(
+            "{}: {}",
+            "error".bright_red().bold(),
+            "Reconstructed source doesn't parse".bold()
+        );
+        reparsed.print_lexer_errors();
+        reparsed.print_parser_errors();
+        return false;
+    }
+
+    let original_tree = render_ast(&parsed_module.ast, false);
+    let reparsed_tree = render_ast(&reparsed.ast, false);
+    if original_tree == reparsed_tree {
+        return true;
+    }
+
+    eprintln!(
+        "{}: {}",
+        "error".bright_red().bold(),
+        "Reconstructed source parses to a different AST".bold()
+    );
+    let (original, reparsed) = original_tree
+        .lines()
+        .zip(reparsed_tree.lines())
+        .find(|(original, reparsed)| original != reparsed)
+        .unwrap_or_else(|| {
+            // one of the trees is a prefix of the other
+            let len = original_tree
+                .lines()
+                .count()
+                .min(reparsed_tree.lines().count());
+            (
+                original_tree.lines().nth(len).unwrap_or(""),
+                reparsed_tree.lines().nth(len).unwrap_or(""),
+            )
+        });
+    eprintln!("{} {}", "    original:".bright_blue().bold(), original);
+    eprintln!("{} {}", "    reparsed:".bright_blue().bold(), reparsed);
+
+    false
+}
@@ -0,0 +1,96 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+fn test_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("rig_driver_{}_{}", name, std::process::id()))
+}
+
+/// Writes `files` into `dir` and runs the driver with `args` in it
+fn rig(dir: &Path, files: &[(&str, &str)], args: &[&str]) -> Output {
+    std::fs::create_dir_all(dir).unwrap();
+    for (path, source) in files {
+        std::fs::write(dir.join(path), source).unwrap();
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rig_driver"))
+        .args(args)
+        .current_dir(dir)
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap();
+    std::fs::remove_dir_all(dir).unwrap();
+
+    output
+}
+
+fn summary(output: &Output) -> String {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    stderr.lines().last().unwrap_or_default().to_string()
+}
+
+#[test]
+fn test_check_without_errors() {
+    let output = rig(
+        &test_dir("check_ok"),
+        &[("main.rig", "fn main() { print 1; }")],
+        &["check", "main.rig"],
+    );
+
+    assert!(output.status.success());
+    assert_eq!(summary(&output), "0 errors, 0 warnings emitted");
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn test_check_warnings() {
+    let files = [(
+        "main.rig",
+        "fn f() -> int { return 1; print 2; }\nfn main() { print f(); }",
+    )];
+
+    let output = rig(&test_dir("check_warnings"), &files, &["check", "main.rig"]);
+    assert!(output.status.success());
+    assert_eq!(summary(&output), "0 errors, 1 warning emitted");
+
+    let output = rig(
+        &test_dir("check_deny_warnings"),
+        &files,
+        &["check", "--deny-warnings", "main.rig"],
+    );
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_check_errors_in_imported_files() {
+    let output = rig(
+        &test_dir("check_errors"),
+        &[
+            ("main.rig", "use util;\nfn main() { let x: int = true; }"),
+            ("util.rig", "pub fn f() {}\nfn g( {}"),
+        ],
+        &["check", "main.rig"],
+    );
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(summary(&output), "3 errors, 0 warnings emitted");
+}
+
+#[test]
+fn test_check_json_diagnostics() {
+    let output = rig(
+        &test_dir("check_json"),
+        &[(
+            "main.rig",
+            "fn main() {\n    let x: int = true;\n    x = \"a\";\n}",
+        )],
+        &["check", "--error-format=json", "main.rig"],
+    );
+
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let lines = stderr.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2);
+    for line in lines {
+        assert!(line.starts_with(r#"{"code":"E0017","severity":"error","#));
+    }
+}
@@ -0,0 +1,45 @@
+use std::process::Command;
+
+const SOURCE: &str = "fn main() {\n    let x = 1.2.3;\n    print x;\n}\nlet s = \"abc";
+const FIXED: &str = "fn main() {\n    let x = 1.2.3;\n    print x;\n}\nlet s = \"abc\"";
+
+/// Runs `rig fix` with `args` on a copy of [SOURCE], returns stdout and the file afterwards
+fn fix(name: &str, args: &[&str]) -> (String, String) {
+    let dir = std::env::temp_dir().join(format!("rig_driver_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("main.rig"), SOURCE).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rig_driver"))
+        .arg("fix")
+        .args(args)
+        .arg("main.rig")
+        .current_dir(&dir)
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let fixed = std::fs::read_to_string(dir.join("main.rig")).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    (String::from_utf8_lossy(&output.stdout).to_string(), fixed)
+}
+
+#[test]
+fn test_fix() {
+    let (stdout, fixed) = fix("fix", &[]);
+    assert!(stdout.is_empty());
+    assert_eq!(fixed, FIXED);
+}
+
+#[test]
+fn test_fix_dry_run() {
+    let (stdout, fixed) = fix("fix_dry_run", &["--dry-run"]);
+    assert_eq!(fixed, SOURCE);
+
+    let diff = stdout.lines().skip(2).collect::<Vec<_>>();
+    assert_eq!(
+        diff,
+        ["@@ -5,1 +5,1 @@", "-let s = \"abc", "+let s = \"abc\"",]
+    );
+}
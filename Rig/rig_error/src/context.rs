@@ -0,0 +1,82 @@
+use crate::emitter::Emitter;
+use crate::{ErrorType, RigError};
+use std::collections::HashMap;
+
+/// Collects the diagnostics of a compilation, so they can be counted and emitted sorted by file
+/// and position. A diagnostic that is reported twice is only kept once
+#[derive(Default)]
+pub struct DiagnosticsContext {
+    diagnostics: Vec<RigError>,
+
+    /// Content of the files diagnostics were reported in, by file name
+    sources: HashMap<String, String>,
+}
+
+impl DiagnosticsContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a diagnostic reported in `source`, the content of the file of its primary span
+    pub fn add(&mut self, error: RigError, source: &str) {
+        if self.diagnostics.contains(&error) {
+            return;
+        }
+
+        self.sources
+            .entry(error.span.file_name.clone())
+            .or_insert_with(|| source.to_string());
+        self.diagnostics.push(error);
+    }
+
+    pub fn extend<'e>(&mut self, errors: impl IntoIterator<Item = &'e RigError>, source: &str) {
+        for error in errors {
+            self.add(error.clone(), source);
+        }
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|error| error.error_type == ErrorType::Hard)
+            .count()
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|error| error.error_type == ErrorType::Soft)
+            .count()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.error_count() > 0
+    }
+
+    /// Content of `file_name` if a diagnostic was reported in it
+    pub fn source(&self, file_name: &str) -> Option<&str> {
+        self.sources.get(file_name).map(String::as_str)
+    }
+
+    /// Diagnostics sorted by file name, then by the start of their primary span
+    pub fn sorted(&self) -> Vec<&RigError> {
+        let mut diagnostics = self.diagnostics.iter().collect::<Vec<_>>();
+        diagnostics.sort_by_key(|error| {
+            (
+                &error.span.file_name,
+                error.span.starting_line,
+                error.span.starting_line_offset,
+            )
+        });
+
+        diagnostics
+    }
+
+    /// Emits the diagnostics in sorted order and finishes `emitter`
+    pub fn emit(&self, emitter: &mut dyn Emitter) {
+        for error in self.sorted() {
+            emitter.emit(error, &self.sources[&error.span.file_name]);
+        }
+        emitter.finish();
+    }
+}
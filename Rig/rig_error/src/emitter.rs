@@ -0,0 +1,96 @@
+use crate::sarif::sarif_log;
+use crate::RigError;
+use std::io::Write;
+
+/// Receives the diagnostics of a compilation and outputs them
+pub trait Emitter {
+    /// Emits a diagnostic, `source` is the content of the file of its primary span
+    fn emit(&mut self, error: &RigError, source: &str);
+
+    /// Called after the last diagnostic was emitted
+    fn finish(&mut self) {}
+}
+
+/// Prints diagnostics to stderr, colored unless `NO_COLOR` is set
+pub struct TerminalEmitter;
+
+impl Emitter for TerminalEmitter {
+    fn emit(&mut self, error: &RigError, source: &str) {
+        eprint!("{}", error.render(source, true));
+    }
+}
+
+/// Writes diagnostics as plain text, like [TerminalEmitter] without colors
+pub struct TextEmitter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> TextEmitter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl TextEmitter<Vec<u8>> {
+    /// Emitter collecting the diagnostics into a string, see [TextEmitter::output]
+    pub fn buffered() -> Self {
+        Self::new(vec![])
+    }
+
+    pub fn output(&self) -> String {
+        String::from_utf8_lossy(&self.writer).to_string()
+    }
+}
+
+impl<W: Write> Emitter for TextEmitter<W> {
+    fn emit(&mut self, error: &RigError, source: &str) {
+        write!(self.writer, "{}", error.render(source, false)).expect("Failed to emit diagnostic");
+    }
+}
+
+/// Writes every diagnostic as a JSON object on its own line, see [RigError::to_json]
+pub struct JsonEmitter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> JsonEmitter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+}
+
+impl<W: Write> Emitter for JsonEmitter<W> {
+    fn emit(&mut self, error: &RigError, _source: &str) {
+        writeln!(self.writer, "{}", error.to_json()).expect("Failed to emit diagnostic");
+    }
+}
+
+/// Collects the diagnostics and writes them as a SARIF log when finished
+pub struct SarifEmitter<W: Write> {
+    writer: W,
+    results: Vec<RigError>,
+}
+
+impl<W: Write> SarifEmitter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            results: vec![],
+        }
+    }
+}
+
+impl<W: Write> Emitter for SarifEmitter<W> {
+    fn emit(&mut self, error: &RigError, _source: &str) {
+        self.results.push(error.clone());
+    }
+
+    fn finish(&mut self) {
+        writeln!(self.writer, "{}", sarif_log(&self.results)).expect("Failed to emit diagnostic");
+        self.results.clear();
+    }
+}
@@ -0,0 +1,118 @@
+use crate::ErrorCode;
+use std::str::FromStr;
+
+/// Long-form explanation of an error code, printed by `rig explain`
+pub struct Explanation {
+    pub code: ErrorCode,
+
+    /// One line summary, printed by `rig explain --list`
+    pub summary: &'static str,
+
+    /// Markdown text with an erroneous and a corrected example
+    pub text: &'static str,
+}
+
+/// Code block of an explanation
+pub struct CodeBlock {
+    /// Text after the opening fence. `compile_fail,E0005` marks a program causing `E0005`,
+    /// `ignore` an example that can't be compiled on its own, correct programs have none
+    pub info: &'static str,
+
+    pub code: String,
+}
+
+macro_rules! explanations {
+    ($($code:ident: $summary:literal,)*) => {
+        /// Explanations of all error codes, in order
+        pub const EXPLANATIONS: &[Explanation] = &[$(
+            Explanation {
+                code: ErrorCode::$code,
+                summary: $summary,
+                text: include_str!(concat!("explanations/", stringify!($code), ".md")),
+            },
+        )*];
+
+        impl ErrorCode {
+            pub fn explanation(self) -> &'static Explanation {
+                // listing the codes makes a code without an explanation a compile error
+                match self {
+                    $(ErrorCode::$code)|* => EXPLANATIONS
+                        .iter()
+                        .find(|explanation| explanation.code == self)
+                        .unwrap(),
+                }
+            }
+        }
+    };
+}
+
+explanations! {
+    E0001: "Unknown character",
+    E0002: "Unterminated string literal",
+    E0003: "Invalid number literal",
+    E0004: "Invalid escape character",
+    E0005: "Unexpected token",
+    E0006: "Invalid assignment",
+    E0007: "Unreachable code",
+    E0008: "Item defined more than once",
+    E0009: "Cannot find or load module",
+    E0010: "Import of a private item",
+    E0011: "Ambiguous import",
+    E0012: "Import from a type",
+    E0013: "`break` or `continue` outside of a loop",
+    E0014: "Program exceeds a limit of the bytecode format",
+    E0015: "Unknown type",
+    E0016: "Unknown name",
+    E0017: "Mismatched types",
+    E0018: "Wrong number of arguments",
+    E0019: "Unsupported operand types",
+    E0020: "Call of a value that isn't a function",
+    E0021: "Access to a private field",
+    E0022: "Unknown field",
+    E0023: "Field specified more than once",
+    E0024: "Missing fields in struct literal",
+    E0025: "Method called without a receiver or associated function called on a value",
+    E0026: "Non-exhaustive `match`",
+    E0027: "Unreachable `match` arm",
+    E0028: "`match` arm names a variant that doesn't exist",
+    E0029: "Function doesn't return a value on every path",
+    E0030: "File of a module found at more than one path",
+    E0031: "Import cycle",
+    E0032: "Extern function without a native implementation",
+}
+
+impl Explanation {
+    pub fn code_blocks(&self) -> Vec<CodeBlock> {
+        let mut blocks = vec![];
+        let mut lines = self.text.lines();
+
+        while let Some(line) = lines.next() {
+            if let Some(info) = line.strip_prefix("```") {
+                let code = lines
+                    .by_ref()
+                    .take_while(|line| !line.starts_with("```"))
+                    .map(|line| format!("{}\n", line))
+                    .collect();
+
+                blocks.push(CodeBlock { info, code });
+            }
+        }
+
+        blocks
+    }
+}
+
+impl FromStr for ErrorCode {
+    type Err = String;
+
+    /// Parses codes like `E0005`, `e0005` and `0005`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.strip_prefix(['E', 'e']).unwrap_or(s);
+
+        EXPLANATIONS
+            .iter()
+            .map(|explanation| explanation.code)
+            .find(|code| format!("{:?}", code)[1..] == *digits)
+            .ok_or_else(|| format!("`{}` isn't a valid error code", s))
+    }
+}
@@ -0,0 +1,183 @@
+use crate::{ErrorType, RigError};
+use rig_span::Span;
+use std::fmt::{Display, Formatter, Write};
+
+/// JSON value, printed without whitespace
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(usize),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(&'static str, Json)>),
+}
+
+impl From<&str> for Json {
+    fn from(value: &str) -> Self {
+        Json::String(value.to_string())
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Self {
+        value.map(Into::into).unwrap_or(Json::Null)
+    }
+}
+
+impl Json {
+    /// Value of `key` if this is an object
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields
+                .iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Json {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(value) => write!(f, "{}", value),
+            Json::Number(value) => write!(f, "{}", value),
+            Json::String(value) => write_string(f, value),
+            Json::Array(values) => {
+                f.write_char('[')?;
+                for (idx, value) in values.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                f.write_char(']')
+            }
+            Json::Object(fields) => {
+                f.write_char('{')?;
+                for (idx, (name, value)) in fields.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_string(f, name)?;
+                    write!(f, ":{}", value)?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut Formatter<'_>, value: &str) -> std::fmt::Result {
+    f.write_char('"')?;
+    for ch in value.chars() {
+        match ch {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            ch if ch.is_control() => write!(f, "\\u{:04x}", ch as u32)?,
+            ch => f.write_char(ch)?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// `error` or `warning`
+pub fn severity(error_type: &ErrorType) -> &'static str {
+    match error_type {
+        ErrorType::Hard => "error",
+        ErrorType::Soft => "warning",
+    }
+}
+
+/// Span with 1-based lines and columns, the end column is the column of the last character
+pub fn span_to_json(span: &Span) -> Json {
+    let position = |line: usize, offset: usize| {
+        Json::Object(vec![
+            ("line", Json::Number(line)),
+            ("column", Json::Number(offset + 1)),
+        ])
+    };
+
+    Json::Object(vec![
+        ("file", Json::String(span.file_name.clone())),
+        (
+            "start",
+            position(span.starting_line, span.starting_line_offset),
+        ),
+        (
+            "end",
+            position(span.ending_line, span.ending_line_end_offset),
+        ),
+    ])
+}
+
+impl RigError {
+    /// Diagnostic as printed by `--error-format=json`
+    pub fn to_json(&self) -> Json {
+        Json::Object(vec![
+            ("code", Json::String(format!("{:?}", self.error_code))),
+            ("severity", severity(&self.error_type).into()),
+            ("message", self.message.as_str().into()),
+            ("span", span_to_json(&self.span)),
+            ("label", self.primary_label.as_deref().into()),
+            (
+                "labels",
+                Json::Array(
+                    self.labels
+                        .iter()
+                        .map(|label| {
+                            Json::Object(vec![
+                                ("message", label.message.as_str().into()),
+                                ("span", span_to_json(&label.span)),
+                            ])
+                        })
+                        .collect(),
+                ),
+            ),
+            ("hint", self.hint.as_deref().into()),
+            (
+                "hint_span",
+                self.hint_span
+                    .as_ref()
+                    .map(span_to_json)
+                    .unwrap_or(Json::Null),
+            ),
+            (
+                "suggestions",
+                Json::Array(
+                    self.suggestions
+                        .iter()
+                        .map(|suggestion| {
+                            Json::Object(vec![
+                                ("message", suggestion.message.as_str().into()),
+                                ("span", span_to_json(&suggestion.span)),
+                                ("replacement", suggestion.replacement.as_str().into()),
+                                ("insert", Json::Bool(suggestion.insert)),
+                                ("applicability", suggestion.applicability.name().into()),
+                            ])
+                        })
+                        .collect(),
+                ),
+            ),
+            (
+                "notes",
+                Json::Array(
+                    self.notes
+                        .iter()
+                        .map(|note| {
+                            Json::Object(vec![
+                                ("message", note.message.as_str().into()),
+                                ("span", span_to_json(&note.span)),
+                            ])
+                        })
+                        .collect(),
+                ),
+            ),
+        ])
+    }
+}
@@ -1,9 +1,18 @@
-use colored::Colorize;
+pub mod context;
+pub mod emitter;
+pub mod explain;
+pub mod json;
+pub mod sarif;
+pub mod suggestion;
+
+use crate::emitter::{Emitter, TerminalEmitter};
+use crate::suggestion::{apply_suggestions, changed_lines, Suggestion};
+use colored::{ColoredString, Colorize};
 use rig_span::Span;
 use std::cmp::max;
 use std::fmt::{Display, Formatter};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct RigError {
     /// Describes the type of error
     pub error_type: ErrorType,
@@ -25,16 +34,32 @@ pub struct RigError {
 
     /// Notes
     pub notes: Vec<Note>,
+
+    /// Message written next to the markers of the primary span
+    pub primary_label: Option<String>,
+
+    /// Secondary spans, shown in the snippet of the primary span if they are in the same file
+    pub labels: Vec<Label>,
+
+    /// Changes of the code that fix the diagnostic
+    pub suggestions: Vec<Suggestion>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Note {
     pub span: Span,
     pub message: String,
 }
 
+/// Secondary span of a diagnostic, like the first definition of a redefined item
+#[derive(Clone, Debug, PartialEq)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
 /// Error code
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ErrorCode {
     /// Unknown character
     E0001,
@@ -71,6 +96,66 @@ pub enum ErrorCode {
 
     /// Invalid import
     E0012,
+
+    /// `break` or `continue` outside of a loop
+    E0013,
+
+    /// Program exceeds a limit of the bytecode format
+    E0014,
+
+    /// Unknown type
+    E0015,
+
+    /// Unknown name
+    E0016,
+
+    /// Mismatched types
+    E0017,
+
+    /// Wrong number of arguments
+    E0018,
+
+    /// Unsupported operand types
+    E0019,
+
+    /// Call of a value that isn't a function
+    E0020,
+
+    /// Access to a private field
+    E0021,
+
+    /// Unknown field
+    E0022,
+
+    /// Field specified more than once
+    E0023,
+
+    /// Missing fields in struct literal
+    E0024,
+
+    /// Method called without a receiver or associated function called on a value
+    E0025,
+
+    /// Non-exhaustive `match`
+    E0026,
+
+    /// Unreachable `match` arm
+    E0027,
+
+    /// `match` arm names a variant that doesn't exist
+    E0028,
+
+    /// Function with a return type doesn't return on every path
+    E0029,
+
+    /// File of a module found at more than one path
+    E0030,
+
+    /// Import cycle
+    E0031,
+
+    /// Use of an extern function without a native implementation
+    E0032,
 }
 
 /// Describes the type of error
@@ -117,6 +202,9 @@ impl RigError {
             notes: vec![],
             hint: None,
             hint_span: None,
+            primary_label: None,
+            labels: vec![],
+            suggestions: vec![],
         }
     }
 
@@ -136,9 +224,60 @@ impl RigError {
             notes: vec![],
             hint: Some(hint.to_string()),
             hint_span: Some(hint_span),
+            primary_label: None,
+            labels: vec![],
+            suggestions: vec![],
         }
     }
 
+    /// Error at `span`, to be completed with the `with_*` methods:
+    ///
+    /// ```ignore
+    /// RigError::error(ErrorCode::E0008, "`f` is defined multiple times", span)
+    ///     .with_primary_label("redefined here")
+    ///     .with_label(first_span, "first defined here")
+    /// ```
+    pub fn error(error_code: ErrorCode, message: &str, span: Span) -> Self {
+        Self::with_no_hint_and_notes(ErrorType::Hard, error_code, message, span)
+    }
+
+    /// Warning at `span`, see [RigError::error]
+    pub fn warning(error_code: ErrorCode, message: &str, span: Span) -> Self {
+        Self::with_no_hint_and_notes(ErrorType::Soft, error_code, message, span)
+    }
+
+    pub fn with_primary_label(mut self, message: &str) -> Self {
+        self.primary_label = Some(message.to_string());
+        self
+    }
+
+    pub fn with_label(mut self, span: Span, message: &str) -> Self {
+        self.labels.push(Label {
+            span,
+            message: message.to_string(),
+        });
+        self
+    }
+
+    pub fn with_help(mut self, hint: &str, hint_span: Span) -> Self {
+        self.hint = Some(hint.to_string());
+        self.hint_span = Some(hint_span);
+        self
+    }
+
+    pub fn with_suggestion(mut self, suggestion: Suggestion) -> Self {
+        self.suggestions.push(suggestion);
+        self
+    }
+
+    pub fn with_note(mut self, span: Span, message: &str) -> Self {
+        self.notes.push(Note {
+            span,
+            message: message.to_string(),
+        });
+        self
+    }
+
     fn line_number_max_size(&self) -> usize {
         let mut size = max(
             self.span.starting_line.literal_size(),
@@ -155,12 +294,18 @@ impl RigError {
             );
         }
 
-        for note in &self.notes {
+        for span in self
+            .notes
+            .iter()
+            .map(|note| &note.span)
+            .chain(self.labels.iter().map(|label| &label.span))
+            .chain(self.suggestions.iter().map(|suggestion| &suggestion.span))
+        {
             size = max(
                 size,
                 max(
-                    note.span.starting_line.literal_size(),
-                    note.span.ending_line.literal_size(),
+                    span.starting_line.literal_size(),
+                    span.ending_line.literal_size(),
                 ),
             );
         }
@@ -168,168 +313,385 @@ impl RigError {
         size
     }
 
-    fn print_line(line_number: usize, line: &str, max_line_number_size: usize) {
-        eprintln!(
-            "{} {}",
-            format!(
-                "{}{}|",
-                line_number,
-                " ".repeat(max_line_number_size - line_number.literal_size() + 1)
-            )
-            .bright_blue()
-            .bold(),
-            line
-        );
-    }
-
-    fn write_marker(blank_line: &str, padding: usize, count: usize) {
-        if count == 0 {
-            return;
-        }
-        eprintln!(
-            "{}{}{}",
-            blank_line.bright_blue().bold(),
-            " ".repeat(padding),
-            "^".repeat(count).bright_yellow().bold()
-        );
-    }
-
-    pub fn print_span(
-        &self,
+    /// Renders the lines of the labeled spans, every span marked below its line. Lines without
+    /// labels between them are left out
+    fn render_snippet(
+        style: &Style,
         lines: &[&str],
         blank_line: &str,
-        span: &Span,
+        labels: &[SnippetLabel],
         max_line_num_size: usize,
-        print_trailing_empty_line: bool,
-    ) {
-        for line_num in span.starting_line..span.ending_line + 1 {
+        render_trailing_empty_line: bool,
+    ) -> String {
+        let mut line_nums = labels
+            .iter()
+            .flat_map(|label| label.span.starting_line..label.span.ending_line + 1)
+            .collect::<Vec<_>>();
+        line_nums.sort_unstable();
+        line_nums.dedup();
+
+        let mut res = String::new();
+        let mut previous = None;
+        for line_num in line_nums {
             let line = lines[line_num - 1].replace('\t', "    ");
 
-            if !print_trailing_empty_line {
-                if line_num == span.ending_line && line == "" {
-                    break;
-                }
+            if !render_trailing_empty_line
+                && line.is_empty()
+                && labels
+                    .iter()
+                    .any(|label| label.span.ending_line == line_num)
+            {
+                continue;
             }
 
-            let tab_count = count_tab_until(lines[line_num - 1], span.starting_line_offset);
-            let padding = if line_num == self.span.starting_line {
-                (span.starting_line_offset + tab_count * 4)
-                    .checked_sub(tab_count)
-                    .unwrap_or_default()
-            } else {
-                0
-            };
-            let count;
-
-            if span.starting_line == span.ending_line {
-                if span.starting_line_offset == span.ending_line_end_offset {
-                    count = 1;
-                } else {
-                    let tab_count_until_end = tab_count
-                        - count_tab_until(lines[line_num - 1], span.ending_line_end_offset);
-
-                    count = (span.ending_line_end_offset - span.starting_line_offset)
-                        + 1
-                        + 4 * tab_count_until_end
-                        - tab_count_until_end;
+            if matches!(previous, Some(previous) if line_num > previous + 1) {
+                res += &format!("{}\n", style.gutter("..."));
+            }
+            previous = Some(line_num);
+
+            res += &format!(
+                "{} {}\n",
+                style.gutter(&format!(
+                    "{}{}|",
+                    line_num,
+                    " ".repeat(max_line_num_size - line_num.literal_size() + 1)
+                )),
+                line
+            );
+
+            let mut markers = labels
+                .iter()
+                .filter(|label| {
+                    (label.span.starting_line..label.span.ending_line + 1).contains(&line_num)
+                })
+                .map(|label| (marker_position(lines, line_num, label.span), label))
+                .collect::<Vec<_>>();
+            markers.sort_by_key(|((padding, _), _)| *padding);
+
+            for ((padding, count), label) in markers {
+                if count == 0 {
+                    continue;
                 }
-            } else if line_num == span.starting_line {
-                count = line.len() - padding;
-            } else {
-                if line_num == span.ending_line {
-                    let tab_count_until_end =
-                        count_tab_until(lines[line_num - 1], span.ending_line_end_offset);
-
-                    count = span.ending_line_end_offset + 1 + tab_count_until_end * 4
-                        - tab_count_until_end;
-                } else {
-                    count = line.len() - padding;
+
+                let mut marker = if label.primary { "^" } else { "-" }.repeat(count);
+                if let (Some(message), true) = (label.message, line_num == label.span.ending_line) {
+                    marker = format!("{} {}", marker, message);
                 }
+
+                res += &format!(
+                    "{}{}{}\n",
+                    style.gutter(blank_line),
+                    " ".repeat(padding + 1),
+                    if label.primary {
+                        style.paint(&marker, |s| s.bright_yellow().bold())
+                    } else {
+                        style.paint(&marker, |s| s.bright_blue().bold())
+                    }
+                );
             }
+        }
+
+        res
+    }
 
-            Self::print_line(line_num, &line, max_line_num_size);
-            Self::write_marker(&blank_line, padding + 1, count);
+    /// Renders the lines changed by `suggestion` as a diff. A suggestion for another file than
+    /// the one of the primary span reads that file
+    fn render_suggestion(
+        &self,
+        style: &Style,
+        file_content: &str,
+        suggestion: &Suggestion,
+        max_line_num_size: usize,
+    ) -> String {
+        let other_file;
+        let file_content = if suggestion.span.file_name == self.span.file_name {
+            file_content
+        } else {
+            other_file = std::fs::read_to_string(&suggestion.span.file_name).unwrap_or_default();
+            &other_file
+        };
+
+        let (fixed, _) = apply_suggestions(file_content, &[suggestion]);
+        let (first, removed, added) = changed_lines(file_content, &fixed);
+        let mut res = String::new();
+
+        let line_num = |line_num: usize| {
+            format!(
+                "{}{}",
+                line_num,
+                " ".repeat(max_line_num_size - line_num.literal_size() + 1)
+            )
+        };
+        for (idx, line) in removed.iter().enumerate() {
+            res += &format!(
+                "{}{}\n",
+                style.gutter(&line_num(first + idx)),
+                style.paint(&format!("- {}", line.replace('\t', "    ")), |s| s.red())
+            );
+        }
+        for (idx, line) in added.iter().enumerate() {
+            res += &format!(
+                "{}{}\n",
+                style.gutter(&line_num(first + idx)),
+                style.paint(&format!("+ {}", line.replace('\t', "    ")), |s| s.green())
+            );
         }
+
+        res
     }
 
-    pub fn print(&self, file_content: &str) {
+    /// Renders the diagnostic like rustc does, `file_content` is the content of the file of its
+    /// primary span. Colors are left out without `colored`
+    pub fn render(&self, file_content: &str, colored: bool) -> String {
+        let style = Style { colored };
         let lines = file_content.split('\n').collect::<Vec<&str>>();
         let max_line_num_size = self.line_number_max_size();
         let blank_line = format!("{} |", " ".repeat(max_line_num_size));
+        let mut res = String::new();
 
-        eprintln!(
-            "{}: {}",
+        let header = format!(
+            "{}[{:?}]",
+            json::severity(&self.error_type),
+            self.error_code
+        );
+        res += &format!(
+            "{}: {}\n",
             if self.error_type == ErrorType::Hard {
-                format!("{}[{:?}]", self.error_type, self.error_code)
-                    .bright_red()
-                    .bold()
+                style.paint(&header, |s| s.bright_red().bold())
             } else {
-                format!("{}[{:?}]", self.error_type, self.error_code)
-                    .bright_yellow()
-                    .bold()
+                style.paint(&header, |s| s.bright_yellow().bold())
             },
             self.message
         );
-        eprintln!(
-            "{}{} {}",
+        res += &format!(
+            "{}{} {}\n",
             " ".repeat(max_line_num_size),
-            "-->".bright_blue().bold(),
-            self.span.to_string()
+            style.gutter("-->"),
+            self.span
         );
 
-        eprintln!("{}", blank_line.bright_blue().bold());
-
-        self.print_span(&lines, &blank_line, &self.span, max_line_num_size, false);
+        let (same_file, other_files): (Vec<_>, Vec<_>) = self
+            .labels
+            .iter()
+            .partition(|label| label.span.file_name == self.span.file_name);
+        let mut labels = vec![SnippetLabel {
+            span: &self.span,
+            message: self.primary_label.as_deref(),
+            primary: true,
+        }];
+        labels.extend(same_file.into_iter().map(SnippetLabel::secondary));
+
+        res += &format!("{}\n", style.gutter(&blank_line));
+        res += &Self::render_snippet(
+            &style,
+            &lines,
+            &blank_line,
+            &labels,
+            max_line_num_size,
+            false,
+        );
+        res += &format!("{}\n", style.gutter(&blank_line));
 
-        eprintln!("{}", blank_line.bright_blue().bold());
+        // labels in other files get a snippet per file, the files are read only to render it
+        let mut file_names: Vec<&String> = vec![];
+        for label in &other_files {
+            if !file_names.contains(&&label.span.file_name) {
+                file_names.push(&label.span.file_name);
+            }
+        }
+        for file_name in file_names {
+            let labels = other_files
+                .iter()
+                .filter(|label| &label.span.file_name == file_name)
+                .map(|label| SnippetLabel::secondary(label))
+                .collect::<Vec<_>>();
+
+            res += &format!(
+                "{}{} {}\n",
+                " ".repeat(max_line_num_size),
+                style.gutter("-->"),
+                labels[0].span
+            );
+            let other_file = match std::fs::read_to_string(file_name) {
+                Ok(content) => content,
+                Err(_) => continue,
+            };
+            res += &format!("{}\n", style.gutter(&blank_line));
+            res += &Self::render_snippet(
+                &style,
+                &other_file.split('\n').collect::<Vec<_>>(),
+                &blank_line,
+                &labels,
+                max_line_num_size,
+                true,
+            );
+            res += &format!("{}\n", style.gutter(&blank_line));
+        }
 
         if let Some(hint_span) = &self.hint_span {
-            eprintln!(
-                "{}",
-                format!("{} {}", "help:".green(), self.hint.as_ref().unwrap()).bold()
+            res += &format!(
+                "{} {}\n",
+                style.paint("help:", |s| s.green().bold()),
+                style.paint(self.hint.as_ref().unwrap(), |s| s.bold())
             );
-            eprintln!("{}", blank_line.bright_blue().bold());
-
-            self.print_span(&lines, &blank_line, &hint_span, max_line_num_size, true);
+            res += &format!("{}\n", style.gutter(&blank_line));
+            res += &Self::render_snippet(
+                &style,
+                &lines,
+                &blank_line,
+                &[SnippetLabel::marker(hint_span)],
+                max_line_num_size,
+                true,
+            );
+            res += &format!("{}\n", style.gutter(&blank_line));
+        }
 
-            eprintln!("{}", blank_line.bright_blue().bold());
+        for suggestion in &self.suggestions {
+            res += &format!(
+                "{} {}\n",
+                style.paint("help:", |s| s.green().bold()),
+                style.paint(&suggestion.message, |s| s.bold())
+            );
+            res += &format!("{}\n", style.gutter(&blank_line));
+            res += &self.render_suggestion(&style, file_content, suggestion, max_line_num_size);
+            res += &format!("{}\n", style.gutter(&blank_line));
         }
 
         for note in &self.notes {
-            eprintln!(
-                "{}",
-                format!("{} {}", "note:".bright_blue(), note.message).bold()
+            res += &format!(
+                "{} {}\n",
+                style.paint("note:", |s| s.bright_blue().bold()),
+                style.paint(&note.message, |s| s.bold())
             );
-            eprintln!("{}", blank_line.bright_blue().bold());
 
-            self.print_span(&lines, &blank_line, &note.span, max_line_num_size, true);
-
-            eprintln!("{}", blank_line.bright_blue().bold());
+            // notes can point into other files, which are read only to render the note
+            let other_file;
+            let note_lines = if note.span.file_name == self.span.file_name {
+                lines.clone()
+            } else {
+                res += &format!(
+                    "{}{} {}\n",
+                    " ".repeat(max_line_num_size),
+                    style.gutter("-->"),
+                    note.span
+                );
+                other_file = match std::fs::read_to_string(&note.span.file_name) {
+                    Ok(content) => content,
+                    Err(_) => continue,
+                };
+                other_file.split('\n').collect()
+            };
+            res += &format!("{}\n", style.gutter(&blank_line));
+            res += &Self::render_snippet(
+                &style,
+                &note_lines,
+                &blank_line,
+                &[SnippetLabel::marker(&note.span)],
+                max_line_num_size,
+                true,
+            );
+            res += &format!("{}\n", style.gutter(&blank_line));
         }
 
-        eprintln!(
-            "{}",
-            format!(
-                "For more information about this error, try `rig explain {:?}`\n",
-                self.error_code
+        res += &format!(
+            "{}\n\n",
+            style.paint(
+                &format!(
+                    "For more information about this error, try `rig explain {:?}`",
+                    self.error_code
+                ),
+                |s| s.bold()
             )
-            .bold()
         );
+
+        res
+    }
+
+    /// Prints the diagnostic to stderr with [TerminalEmitter]
+    pub fn print(&self, file_content: &str) {
+        TerminalEmitter.emit(self, file_content);
     }
 }
 
-fn count_tab_until(line: &str, offset: usize) -> usize {
-    let mut count = 0;
+/// Span rendered in a snippet. Primary spans are marked with `^`, secondary ones with `-`
+struct SnippetLabel<'a> {
+    span: &'a Span,
+    message: Option<&'a str>,
+    primary: bool,
+}
 
-    for (idx, ch) in line.chars().enumerate() {
-        if ch == '\t' {
-            count += 1;
+impl<'a> SnippetLabel<'a> {
+    /// Unlabeled span of a hint or note
+    fn marker(span: &'a Span) -> Self {
+        Self {
+            span,
+            message: None,
+            primary: true,
         }
+    }
+
+    fn secondary(label: &'a Label) -> Self {
+        Self {
+            span: &label.span,
+            message: Some(&label.message),
+            primary: false,
+        }
+    }
+}
 
-        if idx == offset {
-            break;
+/// Colors of a rendered diagnostic, if it is colored
+struct Style {
+    colored: bool,
+}
+
+impl Style {
+    fn paint(&self, text: &str, color: impl Fn(ColoredString) -> ColoredString) -> String {
+        if self.colored {
+            color(text.normal()).to_string()
+        } else {
+            text.to_string()
         }
     }
 
-    count
+    fn gutter(&self, text: &str) -> String {
+        self.paint(text, |s| s.bright_blue().bold())
+    }
+}
+
+/// Padding and length of the markers below line `line_num` of `span`, with tabs expanded to
+/// 4 spaces
+fn marker_position(lines: &[&str], line_num: usize, span: &Span) -> (usize, usize) {
+    let line = lines[line_num - 1];
+    let start = if line_num == span.starting_line {
+        span.starting_line_offset
+    } else {
+        0
+    };
+    let end = if line_num == span.ending_line {
+        span.ending_line_end_offset + 1
+    } else {
+        line.chars().count()
+    };
+
+    let padding = expanded_width(line, 0, start);
+    let count = if span.starting_line == span.ending_line {
+        expanded_width(line, start, end).max(1)
+    } else {
+        expanded_width(line, start, end)
+    };
+
+    (padding, count)
+}
+
+/// Width of the characters `from..to` of the line with tabs expanded to 4 spaces. Offsets past
+/// the end of the line count as one column each
+fn expanded_width(line: &str, from: usize, to: usize) -> usize {
+    let tabs = line
+        .chars()
+        .take(to)
+        .skip(from)
+        .filter(|ch| *ch == '\t')
+        .count();
+
+    to.saturating_sub(from) + tabs * 3
 }
@@ -0,0 +1,186 @@
+use crate::json::{severity, Json};
+use crate::suggestion::Suggestion;
+use crate::RigError;
+use rig_span::Span;
+
+pub const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// SARIF 2.1.0 log with a single run of the compiler, reporting `errors` as its results
+pub fn sarif_log(errors: &[RigError]) -> Json {
+    let mut rules: Vec<_> = errors.iter().map(|error| error.error_code).collect();
+    rules.sort_by_key(|code| format!("{:?}", code));
+    rules.dedup();
+
+    let rules = rules
+        .into_iter()
+        .map(|code| {
+            Json::Object(vec![
+                ("id", Json::String(format!("{:?}", code))),
+                (
+                    "shortDescription",
+                    Json::Object(vec![("text", code.explanation().summary.into())]),
+                ),
+                (
+                    "fullDescription",
+                    Json::Object(vec![("text", code.explanation().text.into())]),
+                ),
+            ])
+        })
+        .collect();
+
+    Json::Object(vec![
+        ("$schema", SARIF_SCHEMA.into()),
+        ("version", "2.1.0".into()),
+        (
+            "runs",
+            Json::Array(vec![Json::Object(vec![
+                (
+                    "tool",
+                    Json::Object(vec![(
+                        "driver",
+                        Json::Object(vec![
+                            ("name", "rig".into()),
+                            ("version", env!("CARGO_PKG_VERSION").into()),
+                            ("rules", Json::Array(rules)),
+                        ]),
+                    )]),
+                ),
+                ("results", Json::Array(errors.iter().map(result).collect())),
+            ])]),
+        ),
+    ])
+}
+
+/// Result of a diagnostic. Its primary label is the message of its location, its labels, hint
+/// and notes are related locations
+fn result(error: &RigError) -> Json {
+    let mut related = vec![];
+    for label in &error.labels {
+        related.push((label.message.clone(), &label.span));
+    }
+    if let (Some(hint), Some(hint_span)) = (&error.hint, &error.hint_span) {
+        related.push((format!("help: {}", hint), hint_span));
+    }
+    for note in &error.notes {
+        related.push((format!("note: {}", note.message), &note.span));
+    }
+
+    let mut message = error.message.clone();
+    if let (Some(hint), None) = (&error.hint, &error.hint_span) {
+        message += &format!("\nhelp: {}", hint);
+    }
+
+    let mut fields = vec![
+        ("ruleId", Json::String(format!("{:?}", error.error_code))),
+        ("level", severity(&error.error_type).into()),
+        (
+            "message",
+            Json::Object(vec![("text", message.as_str().into())]),
+        ),
+        (
+            "locations",
+            Json::Array(vec![location(
+                &error.span,
+                None,
+                error.primary_label.as_deref(),
+            )]),
+        ),
+        (
+            "relatedLocations",
+            Json::Array(
+                related
+                    .into_iter()
+                    .enumerate()
+                    .map(|(id, (message, span))| location(span, Some(id), Some(&message)))
+                    .collect(),
+            ),
+        ),
+    ];
+    if !error.suggestions.is_empty() {
+        fields.push((
+            "fixes",
+            Json::Array(error.suggestions.iter().map(fix).collect()),
+        ));
+    }
+
+    Json::Object(fields)
+}
+
+/// Fix of a suggestion, replacing its region with the replacement
+fn fix(suggestion: &Suggestion) -> Json {
+    let span = &suggestion.span;
+    let (end_line, end_column) = if suggestion.insert {
+        (span.starting_line, span.starting_line_offset + 1)
+    } else {
+        (span.ending_line, span.ending_line_end_offset + 2)
+    };
+
+    Json::Object(vec![
+        (
+            "description",
+            Json::Object(vec![("text", suggestion.message.as_str().into())]),
+        ),
+        (
+            "artifactChanges",
+            Json::Array(vec![Json::Object(vec![
+                ("artifactLocation", artifact_location(span)),
+                (
+                    "replacements",
+                    Json::Array(vec![Json::Object(vec![
+                        (
+                            "deletedRegion",
+                            Json::Object(vec![
+                                ("startLine", Json::Number(span.starting_line)),
+                                ("startColumn", Json::Number(span.starting_line_offset + 1)),
+                                ("endLine", Json::Number(end_line)),
+                                ("endColumn", Json::Number(end_column)),
+                            ]),
+                        ),
+                        (
+                            "insertedContent",
+                            Json::Object(vec![("text", suggestion.replacement.as_str().into())]),
+                        ),
+                    ])]),
+                ),
+            ])]),
+        ),
+    ])
+}
+
+fn artifact_location(span: &Span) -> Json {
+    let uri = if span.file_name.starts_with('/') {
+        format!("file://{}", span.file_name)
+    } else {
+        span.file_name.clone()
+    };
+
+    Json::Object(vec![("uri", Json::String(uri))])
+}
+
+/// Location of a span. SARIF regions end one column after the last character
+fn location(span: &Span, id: Option<usize>, message: Option<&str>) -> Json {
+    let mut fields = vec![];
+    if let Some(id) = id {
+        fields.push(("id", Json::Number(id)));
+    }
+    fields.push((
+        "physicalLocation",
+        Json::Object(vec![
+            ("artifactLocation", artifact_location(span)),
+            (
+                "region",
+                Json::Object(vec![
+                    ("startLine", Json::Number(span.starting_line)),
+                    ("startColumn", Json::Number(span.starting_line_offset + 1)),
+                    ("endLine", Json::Number(span.ending_line)),
+                    ("endColumn", Json::Number(span.ending_line_end_offset + 2)),
+                ]),
+            ),
+        ]),
+    ));
+    if let Some(message) = message {
+        fields.push(("message", Json::Object(vec![("text", message.into())])));
+    }
+
+    Json::Object(fields)
+}
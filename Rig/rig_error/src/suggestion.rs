@@ -0,0 +1,187 @@
+use rig_span::Span;
+use std::ops::Range;
+
+/// How likely a suggestion is to be what the user meant
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Applicability {
+    /// The suggestion fixes the diagnostic, `rig fix` applies it
+    MachineApplicable,
+
+    /// The suggestion may not be what the user meant, it is only shown
+    MaybeIncorrect,
+}
+
+impl Applicability {
+    pub fn name(self) -> &'static str {
+        match self {
+            Applicability::MachineApplicable => "machine-applicable",
+            Applicability::MaybeIncorrect => "maybe-incorrect",
+        }
+    }
+}
+
+/// Change of the code that fixes a diagnostic
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    pub message: String,
+
+    /// Code replaced by `replacement`
+    pub span: Span,
+
+    pub replacement: String,
+
+    /// Whether `replacement` is inserted before `span` instead of replacing it
+    pub insert: bool,
+
+    pub applicability: Applicability,
+}
+
+impl Suggestion {
+    /// Suggestion replacing the code of `span` with `replacement`
+    pub fn replace(
+        message: &str,
+        span: Span,
+        replacement: &str,
+        applicability: Applicability,
+    ) -> Self {
+        Self {
+            message: message.to_string(),
+            span,
+            replacement: replacement.to_string(),
+            insert: false,
+            applicability,
+        }
+    }
+
+    /// Suggestion inserting `text` before the start of `span`, which may be right after the end
+    /// of its line
+    pub fn insert(message: &str, span: Span, text: &str, applicability: Applicability) -> Self {
+        Self {
+            message: message.to_string(),
+            span,
+            replacement: text.to_string(),
+            insert: true,
+            applicability,
+        }
+    }
+
+    /// Byte range of `source` replaced by the suggestion, `None` if the span isn't in `source`
+    fn range(&self, source: &str) -> Option<Range<usize>> {
+        let start = byte_offset(
+            source,
+            self.span.starting_line,
+            self.span.starting_line_offset,
+        )?;
+        if self.insert {
+            return Some(start..start);
+        }
+
+        let end = byte_offset(
+            source,
+            self.span.ending_line,
+            self.span.ending_line_end_offset,
+        )?;
+        let end = end + source[end..].chars().next().map_or(0, char::len_utf8);
+
+        Some(start..end.max(start))
+    }
+}
+
+/// Byte offset of the character at `offset` on the 1-based `line`, or of the end of the line
+fn byte_offset(source: &str, line: usize, offset: usize) -> Option<usize> {
+    let line_start = source
+        .split_inclusive('\n')
+        .take(line - 1)
+        .map(str::len)
+        .sum::<usize>();
+    let line_content = source.get(line_start..)?.split('\n').next()?;
+
+    match line_content.char_indices().nth(offset) {
+        Some((idx, _)) => Some(line_start + idx),
+        None if offset == line_content.chars().count() => Some(line_start + line_content.len()),
+        None => None,
+    }
+}
+
+/// Applies `suggestions` to `source`, the content of the file they were made for. A suggestion
+/// overlapping one before it in `suggestions` is left out. Returns the fixed source and how
+/// many suggestions were applied
+pub fn apply_suggestions(source: &str, suggestions: &[&Suggestion]) -> (String, usize) {
+    let mut edits: Vec<(Range<usize>, &str)> = vec![];
+    for suggestion in suggestions {
+        let range = match suggestion.range(source) {
+            Some(range) => range,
+            None => continue,
+        };
+        let overlaps = edits.iter().any(|(applied, _)| {
+            range.start < applied.end && applied.start < range.end || range.start == applied.start
+        });
+
+        if !overlaps {
+            edits.push((range, &suggestion.replacement));
+        }
+    }
+    edits.sort_by_key(|(range, _)| range.start);
+
+    let mut res = String::new();
+    let mut end = 0;
+    for (range, replacement) in &edits {
+        res += &source[end..range.start];
+        res += replacement;
+        end = range.end;
+    }
+    res += &source[end..];
+
+    (res, edits.len())
+}
+
+/// Lines that differ between `old` and `new`: the 1-based number of the first one, the old
+/// lines and the new lines
+pub fn changed_lines<'a>(old: &'a str, new: &'a str) -> (usize, Vec<&'a str>, Vec<&'a str>) {
+    let old = old.split('\n').collect::<Vec<_>>();
+    let new = new.split('\n').collect::<Vec<_>>();
+
+    let prefix = old
+        .iter()
+        .zip(&new)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+
+    (
+        prefix + 1,
+        old[prefix..old.len() - suffix].to_vec(),
+        new[prefix..new.len() - suffix].to_vec(),
+    )
+}
+
+/// Unified diff of the changes between `old` and `new`, the old and the fixed content of
+/// `file_name`, as a single hunk
+pub fn unified_diff(file_name: &str, old: &str, new: &str) -> String {
+    let (first, removed, added) = changed_lines(old, new);
+    if removed.is_empty() && added.is_empty() {
+        return String::new();
+    }
+
+    let mut res = format!("--- {}\n+++ {}\n", file_name, file_name);
+    res += &format!(
+        "@@ -{},{} +{},{} @@\n",
+        first,
+        removed.len(),
+        first,
+        added.len()
+    );
+    for line in removed {
+        res += &format!("-{}\n", line);
+    }
+    for line in added {
+        res += &format!("+{}\n", line);
+    }
+
+    res
+}
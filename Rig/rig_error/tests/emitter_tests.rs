@@ -0,0 +1,189 @@
+use rig_error::context::DiagnosticsContext;
+use rig_error::emitter::{JsonEmitter, TextEmitter};
+use rig_error::{ErrorCode, ErrorType, RigError};
+use rig_span::Span;
+
+const SOURCE: &str = "fn main() {\n    let x: int = \"a\";\n    print y;\n}";
+
+fn span(file_name: &str, line: usize, start: usize, end: usize) -> Span {
+    Span {
+        file_name: file_name.to_string(),
+        starting_line: line,
+        starting_line_offset: start,
+        ending_line: line,
+        ending_line_end_offset: end,
+    }
+}
+
+fn mismatched_types(file_name: &str) -> RigError {
+    RigError::with_hint(
+        ErrorType::Hard,
+        ErrorCode::E0017,
+        "Mismatched types: expected `int`, found `String`",
+        span(file_name, 2, 17, 19),
+        "expected `int` because of this",
+        span(file_name, 2, 11, 13),
+    )
+}
+
+fn unknown_variable(file_name: &str) -> RigError {
+    RigError::with_no_hint_and_notes(
+        ErrorType::Hard,
+        ErrorCode::E0016,
+        "Unknown variable `y`",
+        span(file_name, 3, 10, 10),
+    )
+}
+
+#[test]
+fn test_text_emitter() {
+    let mut context = DiagnosticsContext::new();
+    context.add(mismatched_types("main.rig"), SOURCE);
+
+    let mut emitter = TextEmitter::buffered();
+    context.emit(&mut emitter);
+
+    assert_eq!(
+        emitter.output(),
+        "error[E0017]: Mismatched types: expected `int`, found `String`
+ --> main.rig:2:18
+  |
+2 |     let x: int = \"a\";
+  |                  ^^^
+  |
+help: expected `int` because of this
+  |
+2 |     let x: int = \"a\";
+  |            ^^^
+  |
+For more information about this error, try `rig explain E0017`
+
+"
+    );
+}
+
+#[test]
+fn test_tabs_in_snippets() {
+    let source = "fn main() {\n\tlet x =\t\"a\";\n}";
+    let error = RigError::with_no_hint_and_notes(
+        ErrorType::Hard,
+        ErrorCode::E0017,
+        "Mismatched types: expected `int`, found `String`",
+        span("main.rig", 2, 7, 11),
+    );
+    // tabs are expanded to 4 spaces, in the line and in the markers below it
+    let snippet = "2 |     let x =    \"a\";\n  |           ^^^^^^^^\n";
+
+    assert!(error.render(source, false).contains(snippet));
+
+    let mut context = DiagnosticsContext::new();
+    context.add(error, source);
+    let mut emitter = TextEmitter::buffered();
+    context.emit(&mut emitter);
+
+    assert!(emitter.output().contains(snippet));
+}
+
+#[test]
+fn test_context_dedups_counts_and_sorts() {
+    let mut context = DiagnosticsContext::new();
+    context.add(unknown_variable("main.rig"), SOURCE);
+    context.add(unknown_variable("lib.rig"), SOURCE);
+    context.add(mismatched_types("main.rig"), SOURCE);
+    context.add(unknown_variable("main.rig"), SOURCE);
+    context.add(
+        RigError::with_no_hint_and_notes(
+            ErrorType::Soft,
+            ErrorCode::E0007,
+            "Unreachable code",
+            span("main.rig", 4, 0, 0),
+        ),
+        SOURCE,
+    );
+
+    assert_eq!(context.error_count(), 3);
+    assert_eq!(context.warning_count(), 1);
+    assert!(context.has_errors());
+
+    let order = context
+        .sorted()
+        .iter()
+        .map(|error| (error.span.file_name.as_str(), error.error_code))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        order,
+        [
+            ("lib.rig", ErrorCode::E0016),
+            ("main.rig", ErrorCode::E0017),
+            ("main.rig", ErrorCode::E0016),
+            ("main.rig", ErrorCode::E0007),
+        ]
+    );
+}
+
+#[test]
+fn test_json_emitter() {
+    let mut context = DiagnosticsContext::new();
+    context.add(unknown_variable("main.rig"), SOURCE);
+    context.add(mismatched_types("main.rig"), SOURCE);
+
+    let mut output = vec![];
+    context.emit(&mut JsonEmitter::new(&mut output));
+    let output = String::from_utf8(output).unwrap();
+
+    let lines = output.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with(r#"{"code":"E0017","#));
+    assert!(lines[1].starts_with(r#"{"code":"E0016","#));
+}
+
+#[test]
+fn test_multi_label_snippets() {
+    let other_file = std::env::temp_dir().join(format!("rig_labels_{}.rig", std::process::id()));
+    std::fs::write(&other_file, "pub fn f() {}\n").unwrap();
+    let other_file_name = other_file.to_str().unwrap();
+
+    let source = "fn f() {}\n\nuse other::f;\n\n\nfn f() {}";
+    let error = RigError::error(
+        ErrorCode::E0008,
+        "`f` is defined multiple times in this scope",
+        span("main.rig", 6, 3, 5),
+    )
+    .with_primary_label("`f` redefined here")
+    .with_label(span("main.rig", 1, 3, 5), "first definition of `f` is here")
+    .with_label(span("main.rig", 3, 11, 11), "imported here")
+    .with_label(span(other_file_name, 1, 7, 9), "`other::f` is defined here");
+
+    let mut emitter = TextEmitter::buffered();
+    let mut context = DiagnosticsContext::new();
+    context.add(error, source);
+    context.emit(&mut emitter);
+    std::fs::remove_file(&other_file).unwrap();
+
+    assert_eq!(
+        emitter.output(),
+        format!(
+            "error[E0008]: `f` is defined multiple times in this scope
+ --> main.rig:6:4
+  |
+1 | fn f() {{}}
+  |    --- first definition of `f` is here
+...
+3 | use other::f;
+  |            - imported here
+...
+6 | fn f() {{}}
+  |    ^^^ `f` redefined here
+  |
+ --> {}:1:8
+  |
+1 | pub fn f() {{}}
+  |        --- `other::f` is defined here
+  |
+For more information about this error, try `rig explain E0008`
+
+",
+            other_file_name
+        )
+    );
+}
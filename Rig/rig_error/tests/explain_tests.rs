@@ -0,0 +1,46 @@
+use rig_error::explain::EXPLANATIONS;
+use rig_error::ErrorCode;
+
+#[test]
+fn test_explanations_are_in_order() {
+    for (idx, explanation) in EXPLANATIONS.iter().enumerate() {
+        assert_eq!(
+            format!("{:?}", explanation.code),
+            format!("E{:04}", idx + 1)
+        );
+        assert!(!explanation.summary.is_empty());
+    }
+}
+
+#[test]
+fn test_explanations_have_examples() {
+    for explanation in EXPLANATIONS {
+        let infos = explanation
+            .code_blocks()
+            .iter()
+            .map(|block| block.info)
+            .collect::<Vec<_>>();
+
+        // an erroneous example, followed by the corrected one
+        let erroneous = format!("compile_fail,{:?}", explanation.code);
+        assert!(
+            infos == [erroneous.as_str(), ""] || infos == ["ignore", "ignore"],
+            "unexpected examples for {:?}: {:?}",
+            explanation.code,
+            infos
+        );
+    }
+}
+
+#[test]
+fn test_parse_error_codes() {
+    assert_eq!("E0005".parse::<ErrorCode>(), Ok(ErrorCode::E0005));
+    assert_eq!("e0017".parse::<ErrorCode>(), Ok(ErrorCode::E0017));
+    assert_eq!("0031".parse::<ErrorCode>(), Ok(ErrorCode::E0031));
+    assert_eq!(
+        "E9999".parse::<ErrorCode>(),
+        Err(String::from("`E9999` isn't a valid error code"))
+    );
+    assert!("E".parse::<ErrorCode>().is_err());
+    assert_eq!(ErrorCode::E0026.explanation().code, ErrorCode::E0026);
+}
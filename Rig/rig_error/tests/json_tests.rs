@@ -0,0 +1,139 @@
+use rig_error::json::Json;
+use rig_error::sarif::sarif_log;
+use rig_error::{ErrorCode, ErrorType, Note, RigError};
+use rig_span::Span;
+
+fn span(line: usize, start: usize, end: usize) -> Span {
+    Span {
+        file_name: String::from("/src/main.rig"),
+        starting_line: line,
+        starting_line_offset: start,
+        ending_line: line,
+        ending_line_end_offset: end,
+    }
+}
+
+fn mismatched_types() -> RigError {
+    let mut error = RigError::with_hint(
+        ErrorType::Hard,
+        ErrorCode::E0017,
+        "Mismatched types: expected `int`, found `String`",
+        span(2, 17, 19),
+        "expected `int` because of this",
+        span(2, 11, 13),
+    );
+    error.notes.push(Note {
+        span: span(1, 3, 6),
+        message: String::from("in \"main\""),
+    });
+    error
+}
+
+#[test]
+fn test_json_escaping() {
+    let json = Json::Object(vec![
+        ("text", "a \"quoted\"\n\\path\u{1}".into()),
+        ("list", Json::Array(vec![Json::Number(1), Json::Null])),
+    ]);
+
+    assert_eq!(
+        json.to_string(),
+        r#"{"text":"a \"quoted\"\n\\path\u0001","list":[1,null]}"#
+    );
+}
+
+#[test]
+fn test_diagnostic_to_json() {
+    assert_eq!(
+        mismatched_types().to_json().to_string(),
+        concat!(
+            r#"{"code":"E0017","severity":"error","#,
+            r#""message":"Mismatched types: expected `int`, found `String`","#,
+            r#""span":{"file":"/src/main.rig","start":{"line":2,"column":18},"end":{"line":2,"column":20}},"#,
+            r#""label":null,"labels":[],"#,
+            r#""hint":"expected `int` because of this","#,
+            r#""hint_span":{"file":"/src/main.rig","start":{"line":2,"column":12},"end":{"line":2,"column":14}},"#,
+            r#""suggestions":[],"#,
+            r#""notes":[{"message":"in \"main\"","span":{"file":"/src/main.rig","start":{"line":1,"column":4},"end":{"line":1,"column":7}}}]}"#
+        )
+    );
+
+    let warning = RigError::with_no_hint_and_notes(
+        ErrorType::Soft,
+        ErrorCode::E0007,
+        "Unreachable code",
+        span(1, 0, 0),
+    )
+    .to_json();
+    assert_eq!(warning.get("severity"), Some(&"warning".into()));
+    assert_eq!(warning.get("hint"), Some(&Json::Null));
+    assert_eq!(warning.get("notes"), Some(&Json::Array(vec![])));
+}
+
+#[test]
+fn test_sarif_log() {
+    let log = sarif_log(&[mismatched_types(), mismatched_types()]);
+    assert_eq!(log.get("version"), Some(&"2.1.0".into()));
+
+    let run = match log.get("runs") {
+        Some(Json::Array(runs)) => &runs[0],
+        runs => panic!("unexpected runs: {:?}", runs),
+    };
+    let driver = run.get("tool").and_then(|tool| tool.get("driver")).unwrap();
+    match driver.get("rules") {
+        Some(Json::Array(rules)) => {
+            assert_eq!(rules.len(), 1);
+            assert_eq!(rules[0].get("id"), Some(&"E0017".into()));
+        }
+        rules => panic!("unexpected rules: {:?}", rules),
+    }
+
+    let result = match run.get("results") {
+        Some(Json::Array(results)) => {
+            assert_eq!(results.len(), 2);
+            &results[0]
+        }
+        results => panic!("unexpected results: {:?}", results),
+    };
+    assert_eq!(result.get("ruleId"), Some(&"E0017".into()));
+    assert_eq!(result.get("level"), Some(&"error".into()));
+    assert_eq!(
+        result
+            .to_string()
+            .matches(r#""uri":"file:///src/main.rig""#)
+            .count(),
+        3
+    );
+    assert!(result
+        .to_string()
+        .contains(r#""region":{"startLine":2,"startColumn":18,"endLine":2,"endColumn":21}"#));
+    assert!(result
+        .to_string()
+        .contains(r#""message":{"text":"help: expected `int` because of this"}"#));
+
+    // the primary label is the message of the primary location
+    let primary_location = |error: RigError| {
+        let log = sarif_log(&[error]);
+        let run = match log.get("runs") {
+            Some(Json::Array(runs)) => runs[0].clone(),
+            runs => panic!("unexpected runs: {:?}", runs),
+        };
+        let result = match run.get("results") {
+            Some(Json::Array(results)) => results[0].clone(),
+            results => panic!("unexpected results: {:?}", results),
+        };
+        match result.get("locations") {
+            Some(Json::Array(locations)) => locations[0].clone(),
+            locations => panic!("unexpected locations: {:?}", locations),
+        }
+    };
+    assert_eq!(primary_location(mismatched_types()).get("message"), None);
+    assert_eq!(
+        primary_location(mismatched_types().with_primary_label("expected `int`, found `String`"))
+            .get("message"),
+        Some(&Json::Object(vec![(
+            "text",
+            "expected `int`, found `String`".into()
+        )]))
+    );
+}
@@ -0,0 +1,86 @@
+use rig_error::emitter::Emitter;
+use rig_error::emitter::TextEmitter;
+use rig_error::suggestion::{apply_suggestions, unified_diff, Applicability, Suggestion};
+use rig_error::{ErrorCode, RigError};
+use rig_span::Span;
+
+fn replace(line: usize, start: usize, end: usize, replacement: &str) -> Suggestion {
+    Suggestion::replace(
+        "replace",
+        Span::for_single_line("main.rig", line, start, end),
+        replacement,
+        Applicability::MachineApplicable,
+    )
+}
+
+fn insert(line: usize, offset: usize, text: &str) -> Suggestion {
+    Suggestion::insert(
+        "insert",
+        Span::for_single_char("main.rig", line, offset),
+        text,
+        Applicability::MachineApplicable,
+    )
+}
+
+#[test]
+fn test_apply_suggestions() {
+    let source = "let ä = 1.2.3;\nlet s = \"abc";
+    let suggestions = [
+        insert(2, 12, "\""),
+        replace(1, 11, 11, ""),
+        replace(1, 4, 4, "a"),
+        // overlaps the replacement of `ä`
+        replace(1, 0, 5, "let b"),
+    ];
+
+    assert_eq!(
+        apply_suggestions(source, &suggestions.iter().collect::<Vec<_>>()),
+        (String::from("let a = 1.23;\nlet s = \"abc\""), 3)
+    );
+}
+
+#[test]
+fn test_unified_diff() {
+    let old = "fn main() {\n    let x = 1.2.3;\n    print x;\n}\n";
+    let new = "fn main() {\n    let x = 1.23;\n    print x;\n}\n";
+
+    assert_eq!(
+        unified_diff("main.rig", old, new),
+        "--- main.rig\n+++ main.rig\n@@ -2,1 +2,1 @@\n-    let x = 1.2.3;\n+    let x = 1.23;\n"
+    );
+    assert_eq!(unified_diff("main.rig", old, old), "");
+}
+
+#[test]
+fn test_render_suggestion() {
+    let error = RigError::error(
+        ErrorCode::E0003,
+        "Invalid integer literal",
+        Span::for_single_line("main.rig", 1, 8, 12),
+    )
+    .with_suggestion(Suggestion {
+        message: String::from("Remove this `.`"),
+        ..replace(1, 11, 11, "")
+    });
+
+    let mut emitter = TextEmitter::buffered();
+    emitter.emit(&error, "let x = 1.2.3;");
+
+    assert_eq!(
+        emitter.output(),
+        "error[E0003]: Invalid integer literal
+ --> main.rig:1:9
+  |
+1 | let x = 1.2.3;
+  |         ^^^^^
+  |
+help: Remove this `.`
+  |
+1 - let x = 1.2.3;
+1 + let x = 1.23;
+  |
+For more information about this error, try `rig explain E0003`
+
+"
+    );
+}
@@ -4,6 +4,7 @@ mod escape;
 
 use crate::escape::escape;
 use rig_ast::token::{Token, TokenType, KEYWORDS};
+use rig_error::suggestion::{Applicability, Suggestion};
 use rig_error::{ErrorCode, ErrorType, RigError};
 use rig_span::Span;
 
@@ -90,10 +91,10 @@ impl<'l> Lexer<'l> {
                         self,
                         tokens,
                         '=',
-                        "+=",
+                        "-=",
                         '>',
                         "->",
-                        '+',
+                        '-',
                         TokenType::MinusEquals,
                         TokenType::Arrow,
                         TokenType::Minus
@@ -296,14 +297,22 @@ impl<'l> Lexer<'l> {
                         if self.eof() && sp.starting_line == sp.ending_line {
                             sp.ending_line_end_offset -= 1;
                         }
-                        errors.push(RigError::with_hint(
-                            ErrorType::Hard,
-                            ErrorCode::E0002,
-                            "Unterminated string literal",
-                            sp,
-                            "Insert `\"` here",
-                            Span::for_single_char(self.file_path, self.line, self.offset),
-                        ));
+                        // a string running over several lines was likely meant to end on the
+                        // first one
+                        let applicability = if sp.starting_line == sp.ending_line {
+                            Applicability::MachineApplicable
+                        } else {
+                            Applicability::MaybeIncorrect
+                        };
+                        errors.push(
+                            RigError::error(ErrorCode::E0002, "Unterminated string literal", sp)
+                                .with_suggestion(Suggestion::insert(
+                                    "Insert `\"` here",
+                                    Span::for_single_char(self.file_path, self.line, self.offset),
+                                    "\"",
+                                    applicability,
+                                )),
+                        );
                         continue;
                     } else {
                         lexeme.push('"');
@@ -389,19 +398,25 @@ impl<'l> Lexer<'l> {
                     }
 
                     if dot_count > 1 {
-                        errors.push(RigError::with_hint(
-                            ErrorType::Hard,
-                            ErrorCode::E0003,
-                            "Invalid integer literal",
-                            Span::for_single_line(
-                                self.file_path,
-                                line,
-                                starting_position,
-                                ending_position,
-                            ),
-                            "Remove this",
-                            last_dot_position.unwrap(), // there's no other way to construct an invalid integer other than more than one dots
-                        ));
+                        errors.push(
+                            RigError::error(
+                                ErrorCode::E0003,
+                                "Invalid integer literal",
+                                Span::for_single_line(
+                                    self.file_path,
+                                    line,
+                                    starting_position,
+                                    ending_position,
+                                ),
+                            )
+                            .with_suggestion(Suggestion::replace(
+                                "Remove this `.`",
+                                last_dot_position.unwrap(), // there's no other way to construct an invalid integer other than more than one dots
+                                "",
+                                // removing a `.` changes the value of the literal
+                                Applicability::MaybeIncorrect,
+                            )),
+                        );
                         self.advance();
                         continue 'mainloop;
                     }
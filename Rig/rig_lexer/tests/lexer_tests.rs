@@ -1,4 +1,6 @@
 use rig_ast::token::{Token, TokenType};
+use rig_error::suggestion::{apply_suggestions, Applicability};
+use rig_error::ErrorCode;
 use rig_lexer::Lexer;
 use rig_span::Span;
 
@@ -98,6 +100,38 @@ fn test_plus_equals_double_char() {
     );
 }
 
+#[test]
+fn test_minus_single_char() {
+    let mut lexer = Lexer::new("-", "<test>");
+    let lexer_output = lexer.lex();
+    let tokens = lexer_output.0;
+
+    if !lexer_output.1.is_empty() {
+        for err in lexer_output.1 {
+            err.print("-");
+        }
+        panic!("unexpected error occurred in the lexer");
+    }
+
+    assert_eq!(
+        tokens,
+        [
+            Token {
+                token_type: TokenType::Minus,
+                lexeme: "-".to_string(),
+                literal: "-".to_string(),
+                span: Span::for_single_char("<test>", 1, 0)
+            },
+            Token {
+                token_type: TokenType::EOF,
+                lexeme: String::new(),
+                literal: String::new(),
+                span: Span::for_single_char("<test>", 1, 1)
+            }
+        ]
+    );
+}
+
 #[test]
 fn test_less_than_double_char() {
     let mut lexer = Lexer::new("<", "<test>");
@@ -582,3 +616,35 @@ fn test_unknown_char() {
         }
     }
 }
+
+#[test]
+fn test_suggestions() {
+    let file_content = "let x = 1.2.3;\nlet s = \"abc";
+    let mut lexer = Lexer::new(file_content, "<test>");
+    let (_, errors) = lexer.lex();
+
+    let fixes = errors
+        .iter()
+        .flat_map(|error| &error.suggestions)
+        .filter(|suggestion| suggestion.applicability == Applicability::MachineApplicable)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        apply_suggestions(file_content, &fixes),
+        (String::from("let x = 1.2.3;\nlet s = \"abc\""), 1)
+    );
+
+    // removing a `.` changes the value of the literal
+    assert_eq!(errors[0].error_code, ErrorCode::E0003);
+    assert_eq!(
+        errors[0].suggestions[0].applicability,
+        Applicability::MaybeIncorrect
+    );
+
+    // a string running to the end of the file may be missing its `"` on any line
+    let file_content = "let s = \"abc;\nprint s;";
+    let (_, errors) = Lexer::new(file_content, "<test>").lex();
+    assert_eq!(
+        errors[0].suggestions[0].applicability,
+        Applicability::MaybeIncorrect
+    );
+}
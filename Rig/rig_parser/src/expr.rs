@@ -2,7 +2,7 @@ use crate::Parser;
 use rig_ast::expr::Expr;
 use rig_ast::op::{BinaryOperator, LogicalOperator, UnaryOperator};
 use rig_ast::struct_field::StructExprField;
-use rig_ast::token::TokenType;
+use rig_ast::token::{TokenType, PATH_PREFIXES};
 use rig_error::{ErrorCode, ErrorType, RigError};
 use rig_span::Span;
 use std::str::FromStr;
@@ -511,6 +511,13 @@ pub fn primary(parser: &mut Parser) -> Result<Expr, RigError> {
                 "null" => Ok(Expr::NullLiteralExpr {
                     span: parser.peek().span.clone(),
                 }),
+                // `self::f`, `super::f` and `crate::f`
+                prefix
+                    if PATH_PREFIXES.contains(&prefix)
+                        && (prefix != "self" || parser.check_next(TokenType::Scope)) =>
+                {
+                    return struct_(parser);
+                }
                 "self" => Ok(Expr::SelfExpr {
                     span: parser.peek().span.clone(),
                 }),
@@ -606,6 +613,7 @@ pub fn struct_(parser: &mut Parser) -> Result<Expr, RigError> {
 }
 
 fn field_with_val(parser: &mut Parser) -> Result<StructExprField, RigError> {
+    let sp_start = parser.peek().span.clone();
     let field = parser
         .consume(TokenType::Identifier, "Expected field name")?
         .lexeme
@@ -613,44 +621,60 @@ fn field_with_val(parser: &mut Parser) -> Result<StructExprField, RigError> {
     parser.consume(TokenType::Colon, "Expected `:` after field name")?;
     let val = assignment(parser)?;
 
-    Ok(StructExprField { name: field, val })
+    Ok(StructExprField {
+        name: field,
+        val,
+        span: Span::merge(sp_start, parser.previous().span.clone()),
+    })
 }
 
 pub fn path(parser: &mut Parser) -> Result<Expr, RigError> {
-    let mut path = Vec::new();
     let start_span = parser.peek().span.clone();
-    path.push(
-        parser
-            .consume(TokenType::Identifier, "Expected identifier")?
-            .lexeme
-            .clone(),
-    );
-
-    if parser.check(TokenType::Scope) {
-        parser.advance();
-        path.extend(parse_path(parser)?);
-        let end_span = parser.previous().span.clone();
+    let mut path = vec![path_segment(parser, &PATH_PREFIXES)?];
 
-        Ok(Expr::PathExpr {
-            path,
-            span: Span::merge(start_span, end_span),
-        })
-    } else {
-        Ok(Expr::VariableExpr {
+    if !parser.check(TokenType::Scope) {
+        return Ok(Expr::VariableExpr {
             name: path[0].clone(),
             span: parser.previous().span.clone(),
-        })
+        });
+    }
+
+    while parser.check(TokenType::Scope) {
+        parser.advance();
+        path.push(path_segment(parser, next_path_prefixes(&path))?);
     }
-}
 
-fn parse_path(parser: &mut Parser) -> Result<Vec<String>, RigError> {
-    let name = parser.consume(TokenType::Identifier, "Expected identifier")?;
-    let mut path = vec![name.lexeme.clone()];
+    Ok(Expr::PathExpr {
+        path,
+        span: Span::merge(start_span, parser.previous().span.clone()),
+    })
+}
 
-    if parser.check(TokenType::Scope) {
+/// Identifier, or one of the keywords in `prefixes`
+pub fn path_segment(parser: &mut Parser, prefixes: &[&str]) -> Result<String, RigError> {
+    let token = parser.peek();
+    if token.token_type == TokenType::Keyword && prefixes.contains(&token.lexeme.as_str()) {
+        let segment = token.lexeme.clone();
         parser.advance();
-        path.extend(parse_path(parser)?);
+
+        return Ok(segment);
     }
 
-    Ok(path)
+    Ok(parser
+        .consume(TokenType::Identifier, "Expected identifier")?
+        .lexeme
+        .clone())
+}
+
+/// Keywords allowed after the segments of a path. `super` can follow the prefixes of a path, as
+/// in `super::super::f` and `self::super::f`
+pub fn next_path_prefixes(path: &[String]) -> &'static [&'static str] {
+    if path
+        .iter()
+        .all(|segment| PATH_PREFIXES.contains(&segment.as_str()))
+    {
+        &["super"]
+    } else {
+        &[]
+    }
 }
@@ -1,3 +1,4 @@
+#![allow(clippy::result_large_err)]
 mod expr;
 mod stmt;
 
@@ -52,6 +53,14 @@ impl<'p> Parser<'p> {
         self.peek().token_type == token_type
     }
 
+    /// Checks the token after the current one
+    fn check_next(&self, token_type: TokenType) -> bool {
+        self.lexical_tokens
+            .get(self.pos + 1)
+            .map(|token| token.token_type == token_type)
+            .unwrap_or(false)
+    }
+
     /// # Panics
     /// Will panic if position is not set correctly
     fn set_position(&mut self, pos: usize) {
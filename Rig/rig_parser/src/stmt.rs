@@ -1,4 +1,4 @@
-use crate::expr::{expr, path, primary};
+use crate::expr::{expr, next_path_prefixes, path, path_segment, primary};
 use crate::{name_with_type, Parser};
 use rig_ast::enum_variant::{EnumVariant, EnumVariantField};
 use rig_ast::expr::Expr;
@@ -6,7 +6,8 @@ use rig_ast::function_prototype::{Argument, FnType, Prototype};
 use rig_ast::match_arms::MatchArm;
 use rig_ast::stmt::Stmt;
 use rig_ast::struct_field::StructField;
-use rig_ast::token::TokenType;
+use rig_ast::token::{TokenType, PATH_PREFIXES};
+use rig_ast::use_tree::UseTree;
 use rig_ast::visibility::Visibility;
 use rig_error::{ErrorCode, ErrorType, RigError};
 use rig_span::Span;
@@ -91,6 +92,7 @@ fn enum_(parser: &mut Parser, visibility: bool) -> Result<Stmt, RigError> {
 
 fn parse_enum_variant(parser: &mut Parser) -> Result<EnumVariant, RigError> {
     let name = parser.peek().lexeme.clone();
+    let span = parser.peek().span.clone();
     parser.advance();
 
     if parser.check(TokenType::LeftBrace) {
@@ -99,6 +101,7 @@ fn parse_enum_variant(parser: &mut Parser) -> Result<EnumVariant, RigError> {
         let field = name_with_type(parser)?;
 
         fields.push(EnumVariantField {
+            span: Span::merge(field.0.span, field.1.get_span()),
             name: field.0.lexeme,
             ty: field.1,
         });
@@ -108,6 +111,7 @@ fn parse_enum_variant(parser: &mut Parser) -> Result<EnumVariant, RigError> {
             let field = name_with_type(parser)?;
 
             fields.push(EnumVariantField {
+                span: Span::merge(field.0.span, field.1.get_span()),
                 name: field.0.lexeme,
                 ty: field.1,
             })
@@ -117,9 +121,14 @@ fn parse_enum_variant(parser: &mut Parser) -> Result<EnumVariant, RigError> {
         Ok(EnumVariant {
             name,
             fields: Some(fields),
+            span,
         })
     } else {
-        Ok(EnumVariant { name, fields: None })
+        Ok(EnumVariant {
+            name,
+            fields: None,
+            span,
+        })
     }
 }
 
@@ -146,6 +155,7 @@ fn struct_(parser: &mut Parser, visibility: bool) -> Result<Stmt, RigError> {
 
         fields.push(StructField {
             visibility: vis,
+            span: Span::merge(name_w_ty.0.span, name_w_ty.1.get_span()),
             name: name_w_ty.0.lexeme,
             ty: name_w_ty.1,
         });
@@ -230,6 +240,7 @@ fn struct_fn(parser: &mut Parser) -> Result<Stmt, RigError> {
         visibility = Visibility::NotPub;
     }
 
+    let name_span = parser.peek().span.clone();
     let method_name = parser
         .consume(TokenType::Identifier, "Expected name after `fn`")?
         .lexeme
@@ -280,10 +291,11 @@ fn struct_fn(parser: &mut Parser) -> Result<Stmt, RigError> {
 
     let prototype = Prototype {
         name: method_name,
-        visibility: visibility.clone(),
+        visibility,
         return_ty,
         args,
         fn_type,
+        span: Span::merge(name_span, parser.previous().span.clone()),
     };
 
     parser.consume(TokenType::LeftBrace, "Expected `{` before block statement")?;
@@ -348,23 +360,85 @@ fn use_(parser: &mut Parser, visibility: bool) -> Result<Stmt, RigError> {
     let sp_start = parser.peek().span.clone();
 
     parser.advance();
-    let mut import_path = path(parser)?;
-    if let Expr::VariableExpr { name, span } = import_path {
-        import_path = Expr::PathExpr {
-            path: vec![name],
-            span,
-        }
-    }
+    let tree = use_tree(parser)?;
 
     parser.consume(TokenType::Semicolon, "Expected semicolon after path")?;
 
     Ok(Stmt::UseStmt {
-        path: import_path,
+        tree,
         visibility: Visibility::from(visibility),
         span: Span::merge(sp_start, parser.previous().span.clone()),
     })
 }
 
+/// `a::b`, `a::b as c`, `a::*` or `a::{b, c::*}`
+fn use_tree(parser: &mut Parser) -> Result<UseTree, RigError> {
+    let sp_start = parser.peek().span.clone();
+    let mut prefix = Vec::new();
+
+    loop {
+        if parser.check(TokenType::LeftBrace) {
+            parser.advance();
+
+            let mut children = Vec::new();
+            while !parser.check(TokenType::RightBrace) && !parser.is_eof() {
+                children.push(use_tree(parser)?);
+
+                if !parser.check(TokenType::RightBrace) {
+                    parser.consume(TokenType::Comma, "Expected `,` or `}` after path")?;
+                }
+            }
+            parser.consume(TokenType::RightBrace, "Expected `}`")?;
+
+            return Ok(UseTree::Group {
+                prefix,
+                children,
+                span: Span::merge(sp_start, parser.previous().span.clone()),
+            });
+        }
+
+        if !prefix.is_empty() && parser.check(TokenType::Multiply) {
+            parser.advance();
+
+            return Ok(UseTree::Glob {
+                prefix,
+                span: Span::merge(sp_start, parser.previous().span.clone()),
+            });
+        }
+
+        let prefixes = if prefix.is_empty() {
+            &PATH_PREFIXES
+        } else {
+            next_path_prefixes(&prefix)
+        };
+        prefix.push(path_segment(parser, prefixes)?);
+
+        if !parser.check(TokenType::Scope) {
+            break;
+        }
+        parser.advance();
+    }
+
+    let alias = if parser.peek().token_type == TokenType::Keyword && parser.peek().lexeme == "as" {
+        parser.advance();
+
+        Some(
+            parser
+                .consume(TokenType::Identifier, "Expected name after `as`")?
+                .lexeme
+                .clone(),
+        )
+    } else {
+        None
+    };
+
+    Ok(UseTree::Path {
+        path: prefix,
+        alias,
+        span: Span::merge(sp_start, parser.previous().span.clone()),
+    })
+}
+
 fn fn_(parser: &mut Parser, visibility: bool) -> Result<Stmt, RigError> {
     let sp_start = parser.peek().span.clone();
     parser.advance();
@@ -465,8 +539,8 @@ fn stmt(parser: &mut Parser) -> Result<Stmt, RigError> {
             _ => Err(RigError::with_no_hint_and_notes(
                 ErrorType::Hard,
                 ErrorCode::E0005,
-                &format!("Expected `let`, `use`, `mod`, `struct`, `extern`, `impl`, \
-                                 `while`, `if`, `for`, `loop`, `print`, `return`, `enum`, `break, `continue` and `match`"),
+                "Expected `let`, `use`, `mod`, `struct`, `extern`, `impl`, \
+                                 `while`, `if`, `for`, `loop`, `print`, `return`, `enum`, `break, `continue` and `match`",
                 parser.peek().span.clone(),
             )),
         },
@@ -538,7 +612,7 @@ fn mod_(parser: &mut Parser, visibility: bool) -> Result<Stmt, RigError> {
         name: mod_name,
         body,
         visibility: Visibility::from(visibility),
-        span: Span::merge(sp_start, parser.peek().span.clone()),
+        span: Span::merge(sp_start, parser.previous().span.clone()),
     })
 }
 
@@ -712,6 +786,7 @@ fn expr_stmt(parser: &mut Parser) -> Result<Stmt, RigError> {
 }
 
 fn prototype(parser: &mut Parser, visibility: bool) -> Result<Prototype, RigError> {
+    let sp_start = parser.peek().span.clone();
     let name = parser
         .consume(
             TokenType::Identifier,
@@ -757,6 +832,7 @@ fn prototype(parser: &mut Parser, visibility: bool) -> Result<Prototype, RigErro
         visibility: Visibility::from(visibility),
         return_ty,
         fn_type: FnType::Fn,
+        span: Span::merge(sp_start, parser.previous().span.clone()),
     })
 }
 
@@ -0,0 +1,78 @@
+use rig_ast::stmt::Stmt;
+use rig_ast::tree::render_ast;
+use rig_lexer::Lexer;
+use rig_parser::{parse, Parser};
+
+fn parse_source(source: &str) -> Vec<Stmt> {
+    let (tokens, lexer_errors) = Lexer::new(source, "<test>").lex();
+    assert!(lexer_errors.is_empty(), "unexpected error in the lexer");
+
+    let mut parser = Parser::new(&tokens);
+    let (ast, parser_errors) = parse(&mut parser);
+    for error in &parser_errors {
+        error.print(source);
+    }
+    assert!(parser_errors.is_empty(), "unexpected error in the parser");
+
+    ast
+}
+
+#[test]
+fn test_ast_tree() {
+    let ast = parse_source("fn main() {\n    print -(1 + x);\n}");
+
+    assert_eq!(
+        render_ast(&ast, true),
+        "Fn fn main() @ 1:1-3:1
+└── Block @ 1:11-3:1
+    └── Print @ 2:5-2:19
+        └── Unary - @ 2:11-2:18
+            └── Grouping @ 2:18-2:18
+                └── Binary + @ 2:13-2:17
+                    ├── Integer 1 @ 2:13-2:13
+                    └── Variable x @ 2:17-2:17
+"
+    );
+}
+
+#[test]
+fn test_reconstructed_source_parses_to_same_ast() {
+    let source = "use std::io;
+        pub use a::{b, c::*, d as e};
+        extern {
+            fn puts(s: String);
+            pub fn abs(x: int) -> int;
+        }
+        pub struct Point { pub x: int, y: float }
+        pub enum Shape { Circle { r: float, c: Point }, Empty }
+        impl Point {
+            pub fn new(x: int) -> Self { return Self { x: x, y: 1.0 }; }
+            fn len(self, by: float) -> float { return self.y * by; }
+        }
+        pub let LIMIT: int = -3;
+        pub mod inner { pub fn f() {} }
+        mod file;
+        fn main() {
+            let p = Point::new(1);
+            p.x += 2;
+            let s = \"tab\\t\\\"quoted\\\"\\n\";
+            if !(p.x > 1) && true || false { print 1; } else { print null; }
+            while p.x < 10 { p.x = p.x * (2 + 1); continue; }
+            for i in 10 { break; }
+            match Shape::Empty {
+                Circle => { print r; }
+                _ => {}
+            }
+            print -(1 - 2) - -3 + 2.0;
+            print super::x::y() + self::inner::f();
+        }";
+    let ast = parse_source(source);
+
+    let reconstructed = ast
+        .iter()
+        .map(|stmt| stmt.to_string(0) + "\n")
+        .collect::<String>();
+    let reparsed = parse_source(&reconstructed);
+
+    assert_eq!(render_ast(&ast, false), render_ast(&reparsed, false));
+}
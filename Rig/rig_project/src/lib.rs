@@ -1,11 +1,28 @@
 pub mod parsed_module;
 
-use rig_types::{Module, ModuleId};
+use rig_span::Span;
+use rig_types::{
+    EnumType, FunctionType, Module, ModuleId, StructType, Type, TypeId, BUILTIN_TYPES,
+};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone)]
 pub struct Project {
     pub modules: Vec<Module>,
     pub entry_point: ModuleId,
+
+    /// Modules loaded from files, by the canonical path of the file
+    pub files: HashMap<PathBuf, ModuleId>,
+
+    /// `use` statements whose module is being loaded, the outermost first
+    pub import_stack: Vec<(ModuleId, Span)>,
+}
+
+impl Default for Project {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Project {
@@ -13,6 +30,8 @@ impl Project {
         Project {
             modules: vec![],
             entry_point: ModuleId(0),
+            files: HashMap::new(),
+            import_stack: vec![],
         }
     }
 
@@ -23,6 +42,22 @@ impl Project {
         ModuleId(self.modules.len() - 1)
     }
 
+    /// Inserts a module loaded from a file, so that the file isn't loaded again
+    pub fn insert_file_module(&mut self, module: Module) -> ModuleId {
+        let file_path = PathBuf::from(&module.absolute_path);
+        let module_id = self.insert_module(module);
+        if let Ok(file_path) = file_path.canonicalize() {
+            self.files.insert(file_path, module_id);
+        }
+
+        module_id
+    }
+
+    /// Module already loaded from the file at `file_path`
+    pub fn find_file_module(&self, file_path: &Path) -> Option<ModuleId> {
+        self.files.get(&file_path.canonicalize().ok()?).copied()
+    }
+
     pub fn get_module(&self, module_id: ModuleId) -> &Module {
         self.modules.get(module_id.0).unwrap()
     }
@@ -32,7 +67,7 @@ impl Project {
     }
 
     pub fn get_entry_point(&self) -> &Module {
-        self.modules.get(0).unwrap()
+        self.modules.first().unwrap()
     }
 
     pub fn get_entry_point_mut(&mut self) -> &mut Module {
@@ -42,4 +77,75 @@ impl Project {
     pub fn has_module(&self, id: ModuleId) -> bool {
         self.modules.len() > id.0
     }
+
+    pub fn get_type(&self, type_id: TypeId) -> &Type {
+        if type_id.is_builtin() {
+            &BUILTIN_TYPES[type_id.get_id()]
+        } else {
+            &self
+                .get_module(type_id.get_scope_id().get_module_id())
+                .types[type_id.get_id()]
+        }
+    }
+
+    pub fn get_function(&self, type_id: TypeId) -> Option<&FunctionType> {
+        match self.get_type(type_id) {
+            Type::Function(idx) => Some(
+                &self
+                    .get_module(type_id.get_scope_id().get_module_id())
+                    .functions[*idx],
+            ),
+            _ => None,
+        }
+    }
+
+    pub fn get_struct(&self, type_id: TypeId) -> Option<&StructType> {
+        match self.get_type(type_id) {
+            Type::Struct(idx) => Some(
+                &self
+                    .get_module(type_id.get_scope_id().get_module_id())
+                    .structs[*idx],
+            ),
+            _ => None,
+        }
+    }
+
+    pub fn get_enum(&self, type_id: TypeId) -> Option<&EnumType> {
+        match self.get_type(type_id) {
+            Type::Enum(idx) => Some(
+                &self
+                    .get_module(type_id.get_scope_id().get_module_id())
+                    .enums[*idx],
+            ),
+            _ => None,
+        }
+    }
+
+    /// Name of the type as it's written in source code, used in diagnostics
+    pub fn type_name(&self, type_id: TypeId) -> String {
+        match self.get_type(type_id) {
+            Type::Integer => String::from("int"),
+            Type::Float => String::from("float"),
+            Type::Null => String::from("null"),
+            Type::String => String::from("String"),
+            Type::Boolean => String::from("bool"),
+            Type::Function(_) => {
+                let function = self.get_function(type_id).unwrap();
+                let args = function
+                    .args
+                    .iter()
+                    .map(|arg| self.type_name(arg.ty))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                match function.return_ty {
+                    Some(return_ty) => format!("fn({}) -> {}", args, self.type_name(return_ty)),
+                    None => format!("fn({})", args),
+                }
+            }
+            Type::Struct(_) => self.get_struct(type_id).unwrap().location.join("::"),
+            Type::Enum(_) => self.get_enum(type_id).unwrap().location.join("::"),
+            Type::Undefined => String::from("{unknown}"),
+        }
+    }
 }
@@ -23,14 +23,12 @@ impl ParsedModule {
             .unwrap()
             .to_str()
             .unwrap()
-            .to_string()
-            .split(".")
-            .collect::<Vec<&str>>()
-            .get(0)
+            .split('.')
+            .next()
             .unwrap()
             .to_string();
 
-        let mut lexer = Lexer::new(&file_content, &absolute_path.to_str().unwrap());
+        let mut lexer = Lexer::new(&file_content, absolute_path.to_str().unwrap());
         let (lexical_tokens, lexer_errors) = lexer.lex();
 
         let mut parser = Parser::new(&lexical_tokens);
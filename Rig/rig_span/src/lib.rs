@@ -1,3 +1,5 @@
+use std::fmt::{Display, Formatter};
+
 /// ## [Span] describes the location of a part of code. It's mainly used for diagnostics
 /// Examples:
 /// ```rig
@@ -74,9 +76,10 @@ impl Span {
     }
 }
 
-impl ToString for Span {
-    fn to_string(&self) -> String {
-        format!(
+impl Display for Span {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
             "{}:{}:{}",
             self.file_name,
             self.starting_line,
@@ -0,0 +1,744 @@
+use crate::import;
+use crate::resolve::{
+    find_associated_function, find_item, lookup_item, resolve_type, scope_chain, similar_name,
+    Namespace,
+};
+
+use rig_ast::enum_variant::EnumVariant;
+use rig_ast::expr::Expr;
+use rig_ast::function_prototype::{FnType, Prototype};
+use rig_ast::stmt::Stmt;
+use rig_ast::struct_field::StructField;
+use rig_ast::visibility::Visibility;
+
+use rig_error::{ErrorCode, ErrorType, RigError};
+
+use rig_project::Project;
+use rig_session::Session;
+
+use rig_span::Span;
+use rig_types::checked_stmt::CheckedBlockStmt;
+use rig_types::{
+    EnumType, EnumVariantType, EnumVariantValueType, FunctionArgument, FunctionType, Import,
+    Module, ModuleId, ScopeId, StructFieldType, StructType, Type, TypeId,
+};
+use rig_utils::bug;
+use std::borrow::Borrow;
+use std::collections::HashMap;
+
+/// Item declared by [collect_declarations] for a statement. Bodies of functions and modules
+/// are checked afterwards with the declaration
+#[derive(Debug)]
+pub enum Declaration {
+    /// Index of the struct in [Module::structs]
+    Struct(usize),
+
+    /// Index of the enum in [Module::enums]
+    Enum(usize),
+
+    Function(TypeId),
+
+    /// Functions of an `extern` block
+    Extern(Vec<TypeId>),
+
+    /// Scope of the `impl` block and its methods in the order they are written
+    Impl(ScopeId, Vec<TypeId>),
+
+    /// Module, its root scope, its statements and their declarations
+    Module(ModuleId, ScopeId, Vec<Stmt>, Vec<Option<Declaration>>),
+}
+
+/// Declares every item of `stmts` and of the modules declared in them, so that items can be
+/// used before they're declared. Names are declared before any signature is resolved. The
+/// result has a declaration for each statement that declares an item
+pub fn collect_declarations(
+    project: &mut Project,
+    session: &Session,
+    typechecker_errors: &mut HashMap<ModuleId, Vec<RigError>>,
+    scope_id: ScopeId,
+    stmts: &[impl Borrow<Stmt>],
+) -> (Vec<Option<Declaration>>, Vec<(ModuleId, RigError)>) {
+    let (mut declarations, mut errs) =
+        declare_names(project, session, typechecker_errors, scope_id, stmts);
+    errs.extend(declare_signatures(
+        project,
+        scope_id,
+        stmts,
+        &mut declarations,
+    ));
+
+    (declarations, errs)
+}
+
+/// Adds structs, enums, functions, modules and imports to the scope without resolving any
+/// type
+fn declare_names(
+    project: &mut Project,
+    session: &Session,
+    typechecker_errors: &mut HashMap<ModuleId, Vec<RigError>>,
+    scope_id: ScopeId,
+    stmts: &[impl Borrow<Stmt>],
+) -> (Vec<Option<Declaration>>, Vec<(ModuleId, RigError)>) {
+    let mut errs = Vec::new();
+    let mut declarations = Vec::new();
+
+    for stmt in stmts {
+        let declaration = match stmt.borrow() {
+            Stmt::StructStmt {
+                name,
+                visibility,
+                span,
+                ..
+            } => {
+                let (struct_idx, error) =
+                    declare_struct(project, scope_id, name, *visibility, span);
+                errs.extend(error);
+
+                Some(Declaration::Struct(struct_idx))
+            }
+            Stmt::EnumStmt {
+                name,
+                visibility,
+                span,
+                ..
+            } => {
+                let (enum_idx, error) = declare_enum(project, scope_id, name, *visibility, span);
+                errs.extend(error);
+
+                Some(Declaration::Enum(enum_idx))
+            }
+            Stmt::ModStmt {
+                name,
+                body,
+                visibility,
+                span,
+            } => {
+                let (declaration, new_errs) = declare_mod(
+                    project,
+                    session,
+                    typechecker_errors,
+                    scope_id,
+                    name,
+                    body,
+                    *visibility,
+                    span,
+                );
+                errs.extend(new_errs);
+
+                declaration
+            }
+            Stmt::FnStmt {
+                prototype,
+                visibility,
+                ..
+            } => {
+                let (type_id, error) =
+                    declare_function(project, scope_id, prototype, *visibility, false);
+                errs.extend(error);
+
+                Some(Declaration::Function(type_id))
+            }
+            Stmt::ExternStmt { prototypes, .. } => {
+                let mut declared = Vec::new();
+                for prototype in prototypes {
+                    let (type_id, error) =
+                        declare_function(project, scope_id, prototype, prototype.visibility, true);
+                    errs.extend(error);
+                    declared.push(type_id);
+                }
+
+                Some(Declaration::Extern(declared))
+            }
+            _ => None,
+        };
+
+        declarations.push(declaration);
+    }
+
+    // imports can refer to modules declared anywhere in the scope
+    for stmt in stmts {
+        if let Stmt::UseStmt {
+            tree,
+            visibility,
+            span,
+        } = stmt.borrow()
+        {
+            let (_, new_errs) = import::check_use_stmt(
+                project,
+                session,
+                typechecker_errors,
+                scope_id.get_module_id(),
+                scope_id,
+                tree,
+                *visibility,
+                span,
+            );
+            errs.extend(new_errs);
+        }
+    }
+
+    (declarations, errs)
+}
+
+/// Resolves the fields of structs, the variants of enums and the prototypes of functions and
+/// methods declared by [declare_names]
+fn declare_signatures(
+    project: &mut Project,
+    scope_id: ScopeId,
+    stmts: &[impl Borrow<Stmt>],
+    declarations: &mut [Option<Declaration>],
+) -> Vec<(ModuleId, RigError)> {
+    let module_id = scope_id.get_module_id();
+    let mut errs = Vec::new();
+
+    for (stmt, declaration) in stmts.iter().zip(declarations.iter_mut()) {
+        match (stmt.borrow(), declaration) {
+            (Stmt::StructStmt { fields, .. }, Some(Declaration::Struct(struct_idx))) => {
+                let (fields, new_errs) = resolve_fields(project, scope_id, fields);
+                errs.extend(new_errs);
+
+                project.get_module_mut(module_id).structs[*struct_idx].fields = fields;
+            }
+            (Stmt::EnumStmt { variants, .. }, Some(Declaration::Enum(enum_idx))) => {
+                let (variants, new_errs) = resolve_variants(project, scope_id, variants);
+                errs.extend(new_errs);
+
+                project.get_module_mut(module_id).enums[*enum_idx].variants = variants;
+            }
+            (
+                Stmt::ModStmt { .. },
+                Some(Declaration::Module(_, inner_scope, body, inner_declarations)),
+            ) => {
+                errs.extend(declare_signatures(
+                    project,
+                    *inner_scope,
+                    body,
+                    inner_declarations,
+                ));
+            }
+            (Stmt::FnStmt { prototype, .. }, Some(Declaration::Function(type_id))) => {
+                errs.extend(resolve_prototype(project, scope_id, *type_id, prototype));
+            }
+            (Stmt::ExternStmt { prototypes, .. }, Some(Declaration::Extern(declared))) => {
+                for (prototype, type_id) in prototypes.iter().zip(declared.iter()) {
+                    errs.extend(resolve_prototype(project, scope_id, *type_id, prototype));
+                }
+            }
+            (
+                Stmt::ImplStmt {
+                    struct_name,
+                    methods,
+                    span,
+                },
+                declaration,
+            ) => {
+                let (impl_declaration, new_errs) =
+                    declare_impl(project, scope_id, struct_name, methods, span);
+                errs.extend(new_errs);
+
+                *declaration = impl_declaration;
+            }
+            _ => (),
+        }
+    }
+
+    errs
+}
+
+/// `module::location::name`
+fn item_location(project: &Project, module_id: ModuleId, name: &str) -> Vec<String> {
+    let mut location = project.get_module(module_id).location.clone();
+    location.push(name.to_string());
+
+    location
+}
+
+/// Adds a type to the module the scope belongs to and returns its id
+fn declare_type(project: &mut Project, scope_id: ScopeId, ty: Type) -> TypeId {
+    let module = project.get_module_mut(scope_id.get_module_id());
+    module.types.push(ty);
+
+    TypeId(scope_id, module.types.len() - 1, false)
+}
+
+/// Declares a struct without fields. Fields are resolved by [declare_signatures] so that they
+/// can refer to any struct of the scope, including the struct itself
+fn declare_struct(
+    project: &mut Project,
+    scope_id: ScopeId,
+    name: &str,
+    visibility: Visibility,
+    span: &Span,
+) -> (usize, Option<(ModuleId, RigError)>) {
+    let module_id = scope_id.get_module_id();
+    let location = item_location(project, module_id, name);
+
+    let module = project.get_module_mut(module_id);
+    module.structs.push(StructType {
+        location,
+        fields: vec![],
+        methods: vec![],
+        helpers: vec![],
+        visibility,
+        span: span.clone(),
+    });
+    let struct_idx = module.structs.len() - 1;
+    let type_id = declare_type(project, scope_id, Type::Struct(struct_idx));
+
+    let redefinition = check_redefinition(project, scope_id, Namespace::Type, name, span);
+    if redefinition.is_none() {
+        project
+            .get_module_mut(module_id)
+            .get_scope_mut(scope_id)
+            .structs
+            .insert(name.to_string(), (visibility, type_id));
+    }
+
+    (struct_idx, redefinition)
+}
+
+/// Declares an enum without variants. Variants are resolved by [declare_signatures]
+fn declare_enum(
+    project: &mut Project,
+    scope_id: ScopeId,
+    name: &str,
+    visibility: Visibility,
+    span: &Span,
+) -> (usize, Option<(ModuleId, RigError)>) {
+    let module_id = scope_id.get_module_id();
+    let location = item_location(project, module_id, name);
+
+    let module = project.get_module_mut(module_id);
+    module.enums.push(EnumType {
+        location,
+        variants: vec![],
+        visibility,
+        span: span.clone(),
+    });
+    let enum_idx = module.enums.len() - 1;
+    let type_id = declare_type(project, scope_id, Type::Enum(enum_idx));
+
+    let redefinition = check_redefinition(project, scope_id, Namespace::Type, name, span);
+    if redefinition.is_none() {
+        project
+            .get_module_mut(module_id)
+            .get_scope_mut(scope_id)
+            .enums
+            .insert(name.to_string(), (visibility, type_id));
+    }
+
+    (enum_idx, redefinition)
+}
+
+fn resolve_fields(
+    project: &Project,
+    scope_id: ScopeId,
+    fields: &[StructField],
+) -> (Vec<StructFieldType>, Vec<(ModuleId, RigError)>) {
+    let mut errs = Vec::new();
+    let mut checked_fields = Vec::new();
+
+    for field in fields {
+        let (ty, new_errs) = resolve_type(project, scope_id, &field.ty);
+        errs.extend(new_errs);
+
+        checked_fields.push(StructFieldType {
+            name: field.name.clone(),
+            ty,
+            visibility: field.visibility,
+            span: field.span.clone(),
+        });
+    }
+
+    (checked_fields, errs)
+}
+
+fn resolve_variants(
+    project: &Project,
+    scope_id: ScopeId,
+    variants: &[EnumVariant],
+) -> (Vec<EnumVariantType>, Vec<(ModuleId, RigError)>) {
+    let mut errs = Vec::new();
+    let mut checked_variants = Vec::new();
+
+    for variant in variants {
+        let mut value = Vec::new();
+
+        for field in variant.fields.iter().flatten() {
+            let (value_ty, new_errs) = resolve_type(project, scope_id, &field.ty);
+            errs.extend(new_errs);
+
+            value.push(EnumVariantValueType {
+                name: field.name.clone(),
+                value_ty,
+                span: field.span.clone(),
+            });
+        }
+
+        checked_variants.push(EnumVariantType {
+            name: variant.name.clone(),
+            value,
+            span: variant.span.clone(),
+        });
+    }
+
+    (checked_variants, errs)
+}
+
+/// Creates a module and declares the names of its items. Modules without a body are loaded
+/// from their file
+fn declare_mod(
+    project: &mut Project,
+    session: &Session,
+    typechecker_errors: &mut HashMap<ModuleId, Vec<RigError>>,
+    scope_id: ScopeId,
+    name: &str,
+    body: &Option<Vec<Stmt>>,
+    visibility: Visibility,
+    span: &Span,
+) -> (Option<Declaration>, Vec<(ModuleId, RigError)>) {
+    let module_id = scope_id.get_module_id();
+    let parent = project.get_module(module_id);
+    let (absolute_path, source_code, ast, file_errors) = match body {
+        Some(body) => (
+            parent.absolute_path.clone(),
+            parent.source_code.clone(),
+            body.clone(),
+            vec![],
+        ),
+        None => match import::load_mod_file(project, module_id, name, span) {
+            Ok(parsed_module) => (
+                parsed_module.absolute_path,
+                parsed_module.file_content,
+                parsed_module.ast,
+                [parsed_module.lexer_errors, parsed_module.parser_errors].concat(),
+            ),
+            Err(errs) => return (None, errs),
+        },
+    };
+
+    let mut module = Module::new(
+        absolute_path,
+        item_location(project, module_id, name),
+        source_code,
+        ast.clone(),
+    );
+    module.parent_module = Some(module_id);
+    module.span = Some(span.clone());
+
+    // the body of a redefined module is still checked, but it can't be referred to
+    let mut errs = Vec::new();
+    errs.extend(check_redefinition(
+        project,
+        scope_id,
+        Namespace::Type,
+        name,
+        span,
+    ));
+    let inner_module_id = if body.is_some() {
+        project.insert_module(module)
+    } else {
+        project.insert_file_module(module)
+    };
+    if errs.is_empty() {
+        let parent = project.get_module_mut(module_id);
+        parent.imports.insert(
+            name.to_string(),
+            Import::Module(inner_module_id, visibility),
+        );
+        parent
+            .get_scope_mut(scope_id)
+            .imports
+            .push((visibility, inner_module_id));
+    }
+    errs.extend(
+        file_errors
+            .into_iter()
+            .map(|error| (inner_module_id, error)),
+    );
+
+    let inner_scope = project.get_module_mut(inner_module_id).new_scope();
+    let (declarations, new_errs) =
+        declare_names(project, session, typechecker_errors, inner_scope, &ast);
+    errs.extend(new_errs);
+
+    (
+        Some(Declaration::Module(
+            inner_module_id,
+            inner_scope,
+            ast,
+            declarations,
+        )),
+        errs,
+    )
+}
+
+/// Creates the scope of an `impl` block and declares its methods. Bodies are checked after
+/// every item is declared, so that methods can call each other
+fn declare_impl(
+    project: &mut Project,
+    scope_id: ScopeId,
+    struct_name: &str,
+    methods: &[Box<Stmt>],
+    span: &Span,
+) -> (Option<Declaration>, Vec<(ModuleId, RigError)>) {
+    let module_id = scope_id.get_module_id();
+    let struct_ty = match lookup_item(project, scope_id, struct_name, Namespace::Type) {
+        Some(type_id) if matches!(project.get_type(type_id), Type::Struct(_)) => type_id,
+        _ => {
+            return (
+                None,
+                vec![unknown_impl_struct(project, scope_id, struct_name, span)],
+            )
+        }
+    };
+    let struct_location = match project.get_struct(struct_ty) {
+        Some(struct_) => struct_.location.clone(),
+        None => bug!(struct_ty, "struct type expected"),
+    };
+    let struct_idx = match project.get_type(struct_ty) {
+        Type::Struct(idx) => *idx,
+        ty => bug!(ty, "struct type expected"),
+    };
+
+    // `Self` refers to the struct inside the block
+    let module = project.get_module_mut(module_id);
+    let impl_scope = module.new_child_scope(scope_id);
+    module.get_scope_mut(impl_scope).self_ty = Some(struct_ty);
+
+    let mut errs = Vec::new();
+    let mut declared = Vec::new();
+    for method in methods {
+        let prototype = match method.as_ref() {
+            Stmt::FnStmt { prototype, .. } => prototype,
+            stmt => bug!(stmt, "function expected in `impl` block"),
+        };
+
+        // the struct may already have a function with the same name from this or another
+        // `impl` block
+        let first = project
+            .get_struct(struct_ty)
+            .and_then(|struct_| find_associated_function(project, struct_, &prototype.name))
+            .and_then(|first| project.get_function(first));
+        if let Some(first) = first {
+            let error = RigError::error(
+                ErrorCode::E0008,
+                &format!(
+                    "`{}` is defined multiple times for `{}`",
+                    prototype.name,
+                    struct_location.join("::")
+                ),
+                prototype.span.clone(),
+            )
+            .with_primary_label(&format!("`{}` redefined here", prototype.name))
+            .with_label(
+                first.span.clone(),
+                &format!("first definition of `{}` is here", prototype.name),
+            );
+            errs.push((module_id, error));
+        }
+        let is_duplicate = first.is_some();
+
+        let mut location = struct_location.clone();
+        location.push(prototype.name.clone());
+
+        let type_id = push_function(project, impl_scope, prototype, location, false);
+        errs.extend(resolve_prototype(project, impl_scope, type_id, prototype));
+
+        // duplicates are still checked, but calls always resolve to the first definition
+        if !is_duplicate {
+            let struct_ = &mut project
+                .get_module_mut(struct_ty.get_scope_id().get_module_id())
+                .structs[struct_idx];
+            if prototype.fn_type == FnType::Method {
+                struct_.methods.push(type_id);
+            } else {
+                struct_.helpers.push(type_id);
+            }
+        }
+
+        declared.push(type_id);
+    }
+
+    (Some(Declaration::Impl(impl_scope, declared)), errs)
+}
+
+/// `impl` block for a name that isn't a struct in scope
+fn unknown_impl_struct(
+    project: &Project,
+    scope_id: ScopeId,
+    struct_name: &str,
+    span: &Span,
+) -> (ModuleId, RigError) {
+    let message = format!("Cannot find struct `{}` to implement", struct_name);
+    let structs = scope_chain(project, scope_id)
+        .into_iter()
+        .flat_map(|scope| scope.structs.iter())
+        .map(|(name, (_, type_id))| (name.as_str(), *type_id))
+        .collect::<Vec<_>>();
+    let similar = similar_name(struct_name, structs.iter().map(|(name, _)| *name))
+        .and_then(|similar| structs.iter().find(|(name, _)| *name == similar))
+        .and_then(|(name, type_id)| Some((name, &project.get_struct(*type_id)?.span)));
+
+    let error = match similar {
+        Some((similar, similar_span)) => RigError::with_hint(
+            ErrorType::Hard,
+            ErrorCode::E0015,
+            &message,
+            span.clone(),
+            &format!("did you mean `{}`?", similar),
+            similar_span.clone(),
+        ),
+        None => RigError::with_no_hint_and_notes(
+            ErrorType::Hard,
+            ErrorCode::E0015,
+            &message,
+            span.clone(),
+        ),
+    };
+
+    (scope_id.get_module_id(), error)
+}
+
+/// Declares a function and makes it visible by name in the scope, unless it redefines another
+/// function. Its prototype is resolved by [declare_signatures]
+fn declare_function(
+    project: &mut Project,
+    scope_id: ScopeId,
+    prototype: &Prototype,
+    visibility: Visibility,
+    is_extern: bool,
+) -> (TypeId, Option<(ModuleId, RigError)>) {
+    let location = item_location(project, scope_id.get_module_id(), &prototype.name);
+    let type_id = push_function(project, scope_id, prototype, location, is_extern);
+
+    let redefinition = check_redefinition(
+        project,
+        scope_id,
+        Namespace::Value,
+        &prototype.name,
+        &prototype.span,
+    );
+    if redefinition.is_none() {
+        project
+            .get_module_mut(scope_id.get_module_id())
+            .get_scope_mut(scope_id)
+            .functions
+            .insert(prototype.name.clone(), (visibility, type_id));
+    }
+
+    (type_id, redefinition)
+}
+
+/// Creates the [FunctionType] of the prototype without arguments. Its body is filled when the
+/// body is checked
+fn push_function(
+    project: &mut Project,
+    scope_id: ScopeId,
+    prototype: &Prototype,
+    location: Vec<String>,
+    is_extern: bool,
+) -> TypeId {
+    let module = project.get_module_mut(scope_id.get_module_id());
+    module.functions.push(FunctionType {
+        location,
+        args: vec![],
+        return_ty: None,
+        body: CheckedBlockStmt {
+            scope_id,
+            returns: false,
+            stmts: vec![],
+            span: prototype.span.clone(),
+        },
+        visibility: prototype.visibility,
+        fn_type: prototype.fn_type.clone(),
+        is_extern,
+        span: prototype.span.clone(),
+    });
+    let fn_idx = module.functions.len() - 1;
+
+    declare_type(project, scope_id, Type::Function(fn_idx))
+}
+
+/// Resolves the argument and return types of a function created by [push_function]
+fn resolve_prototype(
+    project: &mut Project,
+    scope_id: ScopeId,
+    type_id: TypeId,
+    prototype: &Prototype,
+) -> Vec<(ModuleId, RigError)> {
+    let mut errs = Vec::new();
+    let mut resolve = |ty: &Expr| {
+        let (type_id, new_errs) = resolve_type(project, scope_id, ty);
+        errs.extend(new_errs);
+
+        type_id
+    };
+
+    let args = prototype
+        .args
+        .iter()
+        .map(|arg| FunctionArgument {
+            name: arg.name.clone(),
+            ty: resolve(&arg.type_),
+            span: arg.span.clone(),
+        })
+        .collect();
+    let return_ty = prototype.return_ty.as_ref().map(resolve);
+
+    let fn_idx = match project.get_type(type_id) {
+        Type::Function(idx) => *idx,
+        ty => bug!(ty, "function type expected"),
+    };
+    let function = &mut project
+        .get_module_mut(type_id.get_scope_id().get_module_id())
+        .functions[fn_idx];
+    function.args = args;
+    function.return_ty = return_ty;
+
+    errs
+}
+
+/// Reports a redefinition if an item named `name` is already declared in the namespace of the
+/// scope. Redefinitions aren't added to the scope, the name keeps referring to the first
+/// definition
+fn check_redefinition(
+    project: &Project,
+    scope_id: ScopeId,
+    namespace: Namespace,
+    name: &str,
+    span: &Span,
+) -> Option<(ModuleId, RigError)> {
+    let module = project.get_module(scope_id.get_module_id());
+    let scope = module.get_scope(scope_id);
+
+    let first = match find_item(scope, name, namespace) {
+        Some(type_id) => match project.get_type(type_id) {
+            Type::Function(_) => project.get_function(type_id).map(|f| &f.span),
+            Type::Struct(_) => project.get_struct(type_id).map(|s| &s.span),
+            Type::Enum(_) => project.get_enum(type_id).map(|e| &e.span),
+            _ => None,
+        },
+        // modules are types too
+        None if namespace == Namespace::Type => scope
+            .imports
+            .iter()
+            .map(|(_, module_id)| project.get_module(*module_id))
+            .find(|module| module.location.last().map(String::as_str) == Some(name))
+            .and_then(|module| module.span.as_ref()),
+        None => None,
+    }?;
+
+    let error = RigError::error(
+        ErrorCode::E0008,
+        &format!("`{}` is defined multiple times in this scope", name),
+        span.clone(),
+    )
+    .with_primary_label(&format!("`{}` redefined here", name))
+    .with_label(
+        first.clone(),
+        &format!("first definition of `{}` is here", name),
+    );
+
+    Some((scope_id.get_module_id(), error))
+}
@@ -0,0 +1,117 @@
+use rig_error::{ErrorCode, ErrorType, RigError};
+use rig_project::Project;
+use rig_span::Span;
+use rig_types::checked_expr::CheckedExpr;
+use rig_types::ModuleId;
+use std::collections::HashMap;
+
+/// What the pattern of a `match` arm matches
+pub enum ArmPattern {
+    /// `_`
+    CatchAll(Span),
+
+    /// A variant of the matched enum
+    Variant(String, Span),
+
+    /// Any other value, compared to the matched value with `==`
+    Value(Span),
+}
+
+/// Reports arms that can never be reached and, if the matched value is an enum, variants that
+/// no arm matches
+pub fn check_arms(
+    project: &Project,
+    module_id: ModuleId,
+    matched: &CheckedExpr,
+    patterns: &[ArmPattern],
+) -> Vec<(ModuleId, RigError)> {
+    let enum_ = project.get_enum(matched.ty());
+    let mut errs = Vec::new();
+    let mut matched_variants: HashMap<&str, &Span> = HashMap::new();
+    let mut catch_all: Option<&Span> = None;
+
+    for pattern in patterns {
+        let (span, (label_span, label)) = match (pattern, catch_all) {
+            (
+                ArmPattern::CatchAll(span) | ArmPattern::Variant(_, span) | ArmPattern::Value(span),
+                Some(catch_all),
+            ) => (
+                span,
+                (catch_all, String::from("`_` matches every value here")),
+            ),
+            (ArmPattern::Variant(name, span), None) => match matched_variants.get(name.as_str()) {
+                Some(first) => (span, (*first, format!("`{}` is first matched here", name))),
+                None => {
+                    matched_variants.insert(name, span);
+                    continue;
+                }
+            },
+            (ArmPattern::CatchAll(span), None) => {
+                catch_all = Some(span);
+
+                match enum_ {
+                    Some(enum_) if enum_.variants.len() == matched_variants.len() => (
+                        span,
+                        (
+                            &enum_.span,
+                            format!(
+                                "every variant of `{}` is already matched",
+                                project.type_name(matched.ty())
+                            ),
+                        ),
+                    ),
+                    _ => continue,
+                }
+            }
+            (ArmPattern::Value(_), None) => continue,
+        };
+
+        let error = RigError::warning(ErrorCode::E0027, "Unreachable match arm", span.clone())
+            .with_primary_label("unreachable arm")
+            .with_label(label_span.clone(), &label);
+        errs.push((module_id, error));
+    }
+
+    let enum_ = match enum_ {
+        Some(enum_) if catch_all.is_none() => enum_,
+        _ => return errs,
+    };
+    let missing = enum_
+        .variants
+        .iter()
+        .filter(|variant| !matched_variants.contains_key(variant.name.as_str()))
+        .collect::<Vec<_>>();
+    if missing.is_empty() {
+        return errs;
+    }
+
+    let type_name = project.type_name(matched.ty());
+    let mut error = RigError::with_no_hint_and_notes(
+        ErrorType::Hard,
+        ErrorCode::E0026,
+        &format!(
+            "Non-exhaustive match, {} {} of `{}` not covered",
+            if missing.len() == 1 {
+                "variant"
+            } else {
+                "variants"
+            },
+            missing
+                .iter()
+                .map(|variant| format!("`{}`", variant.name))
+                .collect::<Vec<_>>()
+                .join(", "),
+            type_name
+        ),
+        matched.span().clone(),
+    );
+    for variant in missing {
+        error = error.with_label(
+            variant.span.clone(),
+            &format!("`{}` is declared here", variant.name),
+        );
+    }
+    errs.push((module_id, error));
+
+    errs
+}
@@ -0,0 +1,693 @@
+use crate::field::{check_get, check_set, check_struct_literal};
+use crate::resolve::{
+    find_associated_function, lookup_item, lookup_variable, resolve_item_path, Namespace,
+};
+use rig_ast::expr::Expr;
+use rig_ast::function_prototype::FnType;
+use rig_ast::op::{BinaryOperator, LogicalOperator, UnaryOperator};
+use rig_error::{ErrorCode, ErrorType, RigError};
+use rig_project::Project;
+use rig_span::Span;
+use rig_types::checked_expr::*;
+use rig_types::{FunctionType, ModuleId, ScopeId, Type, TypeId};
+use rig_utils::bug;
+
+pub fn typecheck_expr(
+    project: &Project,
+    scope_id: ScopeId,
+    expr: &Expr,
+) -> (CheckedExpr, Vec<(ModuleId, RigError)>) {
+    let module_id = scope_id.get_module_id();
+    let mut errs = Vec::new();
+
+    let checked = match expr {
+        Expr::AssignmentExpr { name, value, span } => {
+            let value = check(project, scope_id, value, &mut errs);
+            let ty = match lookup_variable(project, scope_id, name) {
+                Some(variable) => {
+                    errs.extend(expect_type(
+                        project,
+                        module_id,
+                        variable.ty,
+                        &value,
+                        &variable.span,
+                    ));
+
+                    variable.ty
+                }
+                None => {
+                    errs.push(unknown_name(module_id, name, span));
+                    TypeId::UNDEFINED
+                }
+            };
+
+            CheckedExpr::Assignment(CheckedAssignment {
+                name: name.clone(),
+                value: Box::new(value),
+                ty,
+                span: span.clone(),
+            })
+        }
+        Expr::BinaryExpr { lhs, op, rhs, span } => {
+            let lhs = check(project, scope_id, lhs, &mut errs);
+            let rhs = check(project, scope_id, rhs, &mut errs);
+            let ty = operator_type(
+                project,
+                module_id,
+                binary_type(op, lhs.ty(), rhs.ty()),
+                &op.to_string(),
+                &[&lhs, &rhs],
+                span,
+                &mut errs,
+            );
+
+            CheckedExpr::Binary(CheckedBinary {
+                lhs: Box::new(lhs),
+                op: op.clone(),
+                rhs: Box::new(rhs),
+                ty,
+                span: span.clone(),
+            })
+        }
+        Expr::LogicalExpr { lhs, op, rhs, span } => {
+            let lhs = check(project, scope_id, lhs, &mut errs);
+            let rhs = check(project, scope_id, rhs, &mut errs);
+            let ty = operator_type(
+                project,
+                module_id,
+                logical_type(op, lhs.ty(), rhs.ty()),
+                &op.to_string(),
+                &[&lhs, &rhs],
+                span,
+                &mut errs,
+            );
+
+            CheckedExpr::Logical(CheckedLogical {
+                lhs: Box::new(lhs),
+                op: op.clone(),
+                rhs: Box::new(rhs),
+                ty,
+                span: span.clone(),
+            })
+        }
+        Expr::UnaryExpr { op, rhs, span } => {
+            let rhs = check(project, scope_id, rhs, &mut errs);
+            let ty = operator_type(
+                project,
+                module_id,
+                unary_type(op, rhs.ty()),
+                &op.to_string(),
+                &[&rhs],
+                span,
+                &mut errs,
+            );
+
+            CheckedExpr::Unary(CheckedUnary {
+                op: op.clone(),
+                rhs: Box::new(rhs),
+                ty,
+                span: span.clone(),
+            })
+        }
+        Expr::GetExpr { object, name, span } => {
+            let get = check_get_expr(project, scope_id, object, name, false, span, &mut errs);
+            if let Some(function) = associated_function(project, &get) {
+                errs.push(misused_function(module_id, function, span));
+            }
+
+            CheckedExpr::Get(get)
+        }
+        Expr::SetExpr {
+            object,
+            name,
+            value,
+            span,
+        } => {
+            let object = check(project, scope_id, object, &mut errs);
+            let value = check(project, scope_id, value, &mut errs);
+            let (ty, new_errs) = check_set(project, module_id, &object, name, &value, span);
+            errs.extend(new_errs);
+
+            CheckedExpr::Set(CheckedSet {
+                object: Box::new(object),
+                name: name.clone(),
+                ty,
+                value: Box::new(value),
+                span: span.clone(),
+            })
+        }
+        Expr::PathExpr { path, span } => {
+            let ty = match resolve_item_path(project, scope_id, path, Namespace::Value) {
+                Some(ty) => ty,
+                None => {
+                    let name = path.join("::");
+                    errs.push(
+                        match resolve_item_path(project, scope_id, path, Namespace::Type) {
+                            Some(type_id) => {
+                                expected_value(project, module_id, &name, type_id, span)
+                            }
+                            None => unknown_name(module_id, &name, span),
+                        },
+                    );
+                    TypeId::UNDEFINED
+                }
+            };
+
+            CheckedExpr::Path(CheckedPath {
+                path: path.clone(),
+                ty,
+                span: span.clone(),
+            })
+        }
+        Expr::GroupingExpr { expr, span } => {
+            let expr = check(project, scope_id, expr, &mut errs);
+
+            CheckedExpr::Grouping(CheckedGroupingExpr {
+                ty: expr.ty(),
+                expr: Box::new(expr),
+                span: span.clone(),
+            })
+        }
+        Expr::StringLiteralExpr { value, span } => CheckedExpr::String(CheckedString {
+            value: value.clone(),
+            ty: TypeId::STRING,
+            span: span.clone(),
+        }),
+        Expr::IntegerLiteralExpr { value, span } => CheckedExpr::Int(CheckedInteger {
+            value: *value,
+            ty: TypeId::INTEGER,
+            span: span.clone(),
+        }),
+        Expr::BooleanLiteralExpr { value, span } => CheckedExpr::Boolean(CheckedBoolean {
+            value: *value,
+            ty: TypeId::BOOLEAN,
+            span: span.clone(),
+        }),
+        Expr::NullLiteralExpr { span } => CheckedExpr::Null(CheckedNull {
+            ty: TypeId::NULL,
+            span: span.clone(),
+        }),
+        Expr::FloatLiteralExpr { value, span } => CheckedExpr::Float(CheckedFloat {
+            value: *value,
+            ty: TypeId::FLOAT,
+            span: span.clone(),
+        }),
+        Expr::VariableExpr { name, span } => {
+            let ty = match lookup_variable(project, scope_id, name) {
+                Some(variable) => variable.ty,
+                None => match lookup_item(project, scope_id, name, Namespace::Value) {
+                    Some(ty) => ty,
+                    None => {
+                        errs.push(
+                            match lookup_item(project, scope_id, name, Namespace::Type) {
+                                Some(type_id) => {
+                                    expected_value(project, module_id, name, type_id, span)
+                                }
+                                None => unknown_name(module_id, name, span),
+                            },
+                        );
+                        TypeId::UNDEFINED
+                    }
+                },
+            };
+
+            CheckedExpr::Variable(CheckedVariable {
+                name: name.clone(),
+                ty,
+                span: span.clone(),
+            })
+        }
+        Expr::SelfExpr { span } => {
+            // `self` is declared as a variable in methods only
+            let ty = match lookup_variable(project, scope_id, "self") {
+                Some(variable) => variable.ty,
+                None => {
+                    errs.push(unknown_name(module_id, "self", span));
+                    TypeId::UNDEFINED
+                }
+            };
+
+            CheckedExpr::SelfLit(CheckedSelf {
+                ty,
+                span: span.clone(),
+            })
+        }
+        Expr::CallExpr { name, args, span } => {
+            let callee = match name.as_ref() {
+                Expr::GetExpr { object, name, span } => CheckedExpr::Get(check_get_expr(
+                    project, scope_id, object, name, true, span, &mut errs,
+                )),
+                name => check(project, scope_id, name, &mut errs),
+            };
+            let args = args
+                .iter()
+                .map(|arg| check(project, scope_id, arg, &mut errs))
+                .collect::<Vec<_>>();
+            let ty = call_type(project, module_id, &callee, &args, span, &mut errs);
+
+            CheckedExpr::Call(CheckedCall {
+                name: Box::new(callee),
+                args: args.into_iter().map(Box::new).collect(),
+                ty,
+                span: span.clone(),
+            })
+        }
+        Expr::StructExpr { name, vals, span } => {
+            let name = check_struct_name(project, scope_id, name, &mut errs);
+            let fields = vals
+                .iter()
+                .map(|field| {
+                    let value = check(project, scope_id, &field.val, &mut errs);
+
+                    CheckedStructField {
+                        name: field.name.clone(),
+                        ty: value.ty(),
+                        span: field.span.clone(),
+                        value: Box::new(value),
+                    }
+                })
+                .collect::<Vec<_>>();
+            errs.extend(check_struct_literal(
+                project, module_id, &name, &fields, span,
+            ));
+
+            CheckedExpr::Struct(CheckedStruct {
+                ty: name.ty(),
+                name: Box::new(name),
+                fields,
+                span: span.clone(),
+            })
+        }
+    };
+
+    (checked, errs)
+}
+
+fn check(
+    project: &Project,
+    scope_id: ScopeId,
+    expr: &Expr,
+    errs: &mut Vec<(ModuleId, RigError)>,
+) -> CheckedExpr {
+    let (checked, new_errs) = typecheck_expr(project, scope_id, expr);
+    errs.extend(new_errs);
+
+    checked
+}
+
+/// Whether a value of type `found` can be used where `expected` is declared. Undefined types
+/// are accepted, their error has already been reported
+pub fn is_assignable(expected: TypeId, found: TypeId) -> bool {
+    expected == found || expected == TypeId::UNDEFINED || found == TypeId::UNDEFINED
+}
+
+/// Reports a mismatch if `expr` isn't of the `expected` type, which was declared at `declared`
+pub fn expect_type(
+    project: &Project,
+    module_id: ModuleId,
+    expected: TypeId,
+    expr: &CheckedExpr,
+    declared: &Span,
+) -> Vec<(ModuleId, RigError)> {
+    if is_assignable(expected, expr.ty()) {
+        return vec![];
+    }
+
+    let expected = project.type_name(expected);
+    vec![(
+        module_id,
+        RigError::with_hint(
+            ErrorType::Hard,
+            ErrorCode::E0017,
+            &format!(
+                "Mismatched types: expected `{}`, found `{}`",
+                expected,
+                project.type_name(expr.ty())
+            ),
+            expr.span().clone(),
+            &format!("expected `{}` because of this", expected),
+            declared.clone(),
+        ),
+    )]
+}
+
+/// Reports a mismatch if `expr` isn't of the `expected` builtin type
+pub fn expect_builtin_type(
+    project: &Project,
+    module_id: ModuleId,
+    expected: TypeId,
+    expr: &CheckedExpr,
+) -> Vec<(ModuleId, RigError)> {
+    if is_assignable(expected, expr.ty()) {
+        return vec![];
+    }
+
+    vec![(
+        module_id,
+        RigError::with_no_hint_and_notes(
+            ErrorType::Hard,
+            ErrorCode::E0017,
+            &format!(
+                "Mismatched types: expected `{}`, found `{}`",
+                project.type_name(expected),
+                project.type_name(expr.ty())
+            ),
+            expr.span().clone(),
+        ),
+    )]
+}
+
+fn unknown_name(module_id: ModuleId, name: &str, span: &Span) -> (ModuleId, RigError) {
+    (
+        module_id,
+        RigError::with_no_hint_and_notes(
+            ErrorType::Hard,
+            ErrorCode::E0016,
+            &format!("Cannot find `{}` in this scope", name),
+            span.clone(),
+        ),
+    )
+}
+
+/// A struct or enum used where a value is expected
+fn expected_value(
+    project: &Project,
+    module_id: ModuleId,
+    name: &str,
+    type_id: TypeId,
+    span: &Span,
+) -> (ModuleId, RigError) {
+    let kind = match project.get_type(type_id) {
+        Type::Enum(_) => "enum",
+        _ => "struct",
+    };
+
+    (
+        module_id,
+        RigError::with_no_hint_and_notes(
+            ErrorType::Hard,
+            ErrorCode::E0016,
+            &format!("Expected a value, found {} `{}`", kind, name),
+            span.clone(),
+        ),
+    )
+}
+
+fn is_numeric(ty: TypeId) -> bool {
+    ty == TypeId::INTEGER || ty == TypeId::FLOAT
+}
+
+/// Result of an arithmetic operation, integers are promoted to floats when mixed with them
+fn numeric_type(lhs: TypeId, rhs: TypeId) -> Option<TypeId> {
+    if lhs == TypeId::INTEGER && rhs == TypeId::INTEGER {
+        Some(TypeId::INTEGER)
+    } else if is_numeric(lhs) && is_numeric(rhs) {
+        Some(TypeId::FLOAT)
+    } else {
+        None
+    }
+}
+
+fn binary_type(op: &BinaryOperator, lhs: TypeId, rhs: TypeId) -> Option<TypeId> {
+    match op {
+        BinaryOperator::Plus if lhs == TypeId::STRING && rhs == TypeId::STRING => {
+            Some(TypeId::STRING)
+        }
+        BinaryOperator::Plus
+        | BinaryOperator::Minus
+        | BinaryOperator::Multiply
+        | BinaryOperator::Divide
+        | BinaryOperator::Modulus => numeric_type(lhs, rhs),
+        BinaryOperator::Or
+        | BinaryOperator::Xor
+        | BinaryOperator::And
+        | BinaryOperator::LeftShift
+        | BinaryOperator::RightShift => {
+            (lhs == TypeId::INTEGER && rhs == TypeId::INTEGER).then_some(TypeId::INTEGER)
+        }
+    }
+}
+
+fn logical_type(op: &LogicalOperator, lhs: TypeId, rhs: TypeId) -> Option<TypeId> {
+    let valid = match op {
+        LogicalOperator::And | LogicalOperator::Or => {
+            lhs == TypeId::BOOLEAN && rhs == TypeId::BOOLEAN
+        }
+        LogicalOperator::Equal | LogicalOperator::NotEqual => true,
+        LogicalOperator::Greater
+        | LogicalOperator::Less
+        | LogicalOperator::GreaterEq
+        | LogicalOperator::LessEq => {
+            (is_numeric(lhs) && is_numeric(rhs)) || (lhs == TypeId::STRING && rhs == TypeId::STRING)
+        }
+    };
+
+    valid.then_some(TypeId::BOOLEAN)
+}
+
+fn unary_type(op: &UnaryOperator, rhs: TypeId) -> Option<TypeId> {
+    match op {
+        UnaryOperator::Not => (rhs == TypeId::BOOLEAN).then_some(TypeId::BOOLEAN),
+        UnaryOperator::Negate => is_numeric(rhs).then_some(rhs),
+    }
+}
+
+/// Reports an error when the operator can't be applied to its operands. Operands of undefined
+/// type make the result undefined without another error
+fn operator_type(
+    project: &Project,
+    module_id: ModuleId,
+    ty: Option<TypeId>,
+    op: &str,
+    operands: &[&CheckedExpr],
+    span: &Span,
+    errs: &mut Vec<(ModuleId, RigError)>,
+) -> TypeId {
+    if operands
+        .iter()
+        .any(|operand| operand.ty() == TypeId::UNDEFINED)
+    {
+        return TypeId::UNDEFINED;
+    }
+
+    match ty {
+        Some(ty) => ty,
+        None => {
+            let operand_types = operands
+                .iter()
+                .map(|operand| format!("`{}`", project.type_name(operand.ty())))
+                .collect::<Vec<_>>()
+                .join(" and ");
+
+            errs.push((
+                module_id,
+                RigError::with_no_hint_and_notes(
+                    ErrorType::Hard,
+                    ErrorCode::E0019,
+                    &format!("Cannot apply `{}` to {}", op, operand_types),
+                    span.clone(),
+                ),
+            ));
+
+            TypeId::UNDEFINED
+        }
+    }
+}
+
+/// Checks the arguments of a call against the parameters of the callee and returns the type
+/// of the call
+fn call_type(
+    project: &Project,
+    module_id: ModuleId,
+    callee: &CheckedExpr,
+    args: &[CheckedExpr],
+    span: &Span,
+    errs: &mut Vec<(ModuleId, RigError)>,
+) -> TypeId {
+    if callee.ty() == TypeId::UNDEFINED {
+        return TypeId::UNDEFINED;
+    }
+
+    let function = match project.get_function(callee.ty()) {
+        Some(function) => function,
+        None => {
+            errs.push((
+                module_id,
+                RigError::with_no_hint_and_notes(
+                    ErrorType::Hard,
+                    ErrorCode::E0020,
+                    &format!(
+                        "Expected a function, found `{}`",
+                        project.type_name(callee.ty())
+                    ),
+                    callee.span().clone(),
+                ),
+            ));
+
+            return TypeId::UNDEFINED;
+        }
+    };
+    let return_ty = function.return_ty.unwrap_or(TypeId::NULL);
+    let name = function.location.last().unwrap();
+
+    // methods receive `self` from the receiver of the call, associated functions don't have
+    // a receiver
+    let misused = match callee {
+        CheckedExpr::Get(get) if associated_function(project, get).is_some() => {
+            function.fn_type != FnType::Method
+        }
+        _ => function.fn_type == FnType::Method,
+    };
+    if misused {
+        errs.push(misused_function(module_id, function, span));
+
+        return return_ty;
+    }
+
+    if args.len() != function.args.len() {
+        let message = format!(
+            "`{}` takes {} but {} {} given",
+            name,
+            arguments(function.args.len()),
+            args.len(),
+            if args.len() == 1 { "was" } else { "were" }
+        );
+        let error = match (function.args.first(), function.args.last()) {
+            (Some(first), Some(last)) => RigError::with_hint(
+                ErrorType::Hard,
+                ErrorCode::E0018,
+                &message,
+                span.clone(),
+                "parameters are declared here",
+                Span::merge(first.span.clone(), last.span.clone()),
+            ),
+            _ => RigError::with_no_hint_and_notes(
+                ErrorType::Hard,
+                ErrorCode::E0018,
+                &message,
+                span.clone(),
+            ),
+        };
+        errs.push((module_id, error));
+
+        return return_ty;
+    }
+
+    for (arg, param) in args.iter().zip(&function.args) {
+        errs.extend(expect_type(project, module_id, param.ty, arg, &param.span));
+    }
+
+    return_ty
+}
+
+/// Checks the name of a struct literal, which is looked up among types first
+fn check_struct_name(
+    project: &Project,
+    scope_id: ScopeId,
+    name: &Expr,
+    errs: &mut Vec<(ModuleId, RigError)>,
+) -> CheckedExpr {
+    let module_id = scope_id.get_module_id();
+    let (path, span) = match name {
+        Expr::VariableExpr { name, span } => (vec![name.clone()], span),
+        Expr::PathExpr { path, span } => (path.clone(), span),
+        name => return check(project, scope_id, name, errs),
+    };
+
+    let ty = match resolve_item_path(project, scope_id, &path, Namespace::Type) {
+        Some(ty) => ty,
+        None => {
+            errs.push(unknown_name(module_id, &path.join("::"), span));
+            TypeId::UNDEFINED
+        }
+    };
+
+    match name {
+        Expr::VariableExpr { name, span } => CheckedExpr::Variable(CheckedVariable {
+            name: name.clone(),
+            ty,
+            span: span.clone(),
+        }),
+        _ => CheckedExpr::Path(CheckedPath {
+            path,
+            ty,
+            span: span.clone(),
+        }),
+    }
+}
+
+/// Checks `object.name`, which is the callee of a call if `is_callee` is set
+fn check_get_expr(
+    project: &Project,
+    scope_id: ScopeId,
+    object: &Expr,
+    name: &str,
+    is_callee: bool,
+    span: &Span,
+    errs: &mut Vec<(ModuleId, RigError)>,
+) -> CheckedGet {
+    let object = check(project, scope_id, object, errs);
+    let (ty, new_errs) = check_get(
+        project,
+        scope_id.get_module_id(),
+        &object,
+        name,
+        is_callee,
+        span,
+    );
+    errs.extend(new_errs);
+
+    CheckedGet {
+        object: Box::new(object),
+        name: name.to_string(),
+        ty,
+        span: span.clone(),
+    }
+}
+
+/// Returns the method or associated function `get` resolved to, if it didn't resolve to a field
+fn associated_function<'p>(project: &'p Project, get: &CheckedGet) -> Option<&'p FunctionType> {
+    let struct_ = project.get_struct(get.object.ty())?;
+    let function = find_associated_function(project, struct_, &get.name)?;
+
+    (function == get.ty)
+        .then(|| project.get_function(function))
+        .flatten()
+}
+
+/// A method used without a receiver, or an associated function used through a value
+fn misused_function(
+    module_id: ModuleId,
+    function: &FunctionType,
+    span: &Span,
+) -> (ModuleId, RigError) {
+    let (owner, name) = match function.location.as_slice() {
+        [.., owner, name] => (owner, name),
+        location => bug!(location, "associated function outside of a struct"),
+    };
+    let owner_path = function.location[..function.location.len() - 1].join("::");
+    let message = if function.fn_type == FnType::Method {
+        format!(
+            "`{}` is a method of `{}`, call it as `value.{}(...)`",
+            name, owner_path, name
+        )
+    } else {
+        format!(
+            "`{}` is an associated function of `{}`, call it as `{}::{}(...)`",
+            name, owner_path, owner, name
+        )
+    };
+
+    (
+        module_id,
+        RigError::with_no_hint_and_notes(ErrorType::Hard, ErrorCode::E0025, &message, span.clone()),
+    )
+}
+
+fn arguments(count: usize) -> String {
+    if count == 1 {
+        String::from("1 argument")
+    } else {
+        format!("{} arguments", count)
+    }
+}
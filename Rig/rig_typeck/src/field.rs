@@ -0,0 +1,350 @@
+use crate::expr::expect_type;
+use crate::resolve::{find_associated_function, similar_name};
+use rig_ast::visibility::Visibility;
+use rig_error::{ErrorCode, ErrorType, RigError};
+use rig_project::Project;
+use rig_span::Span;
+use rig_types::checked_expr::{CheckedExpr, CheckedStructField};
+use rig_types::{ModuleId, StructFieldType, StructType, Type, TypeId};
+use std::collections::HashMap;
+
+/// Type of `object.name`. Besides fields, methods and associated functions of a struct can be
+/// accessed this way. Callees(`object.name()`) prefer methods over fields with the same name,
+/// anything else prefers fields
+pub fn check_get(
+    project: &Project,
+    module_id: ModuleId,
+    object: &CheckedExpr,
+    name: &str,
+    is_callee: bool,
+    span: &Span,
+) -> (TypeId, Vec<(ModuleId, RigError)>) {
+    let struct_ = match project.get_struct(object.ty()) {
+        Some(struct_) => struct_,
+        None => {
+            return (
+                TypeId::UNDEFINED,
+                no_fields(project, module_id, object, name, span),
+            )
+        }
+    };
+
+    let function = find_associated_function(project, struct_, name);
+    let has_field = struct_.fields.iter().any(|field| field.name == name);
+    match function {
+        Some(function) if is_callee || !has_field => return (function, vec![]),
+        _ => (),
+    }
+
+    match find_field(project, module_id, object.ty(), struct_, name, span) {
+        Ok(field) => (field.ty, vec![]),
+        Err(errs) => (TypeId::UNDEFINED, errs),
+    }
+}
+
+/// Type of `object.name = value`
+pub fn check_set(
+    project: &Project,
+    module_id: ModuleId,
+    object: &CheckedExpr,
+    name: &str,
+    value: &CheckedExpr,
+    span: &Span,
+) -> (TypeId, Vec<(ModuleId, RigError)>) {
+    let struct_ = match project.get_struct(object.ty()) {
+        Some(struct_) => struct_,
+        None => {
+            return (
+                TypeId::UNDEFINED,
+                no_fields(project, module_id, object, name, span),
+            )
+        }
+    };
+
+    match find_field(project, module_id, object.ty(), struct_, name, span) {
+        Ok(field) => (
+            field.ty,
+            expect_type(project, module_id, field.ty, value, &field.span),
+        ),
+        Err(errs) => (TypeId::UNDEFINED, errs),
+    }
+}
+
+/// Checks the fields of a struct literal(`Point { x: 1, y: 2 }`) or an enum variant
+/// literal(`Shape::Circle { r: 1 }`) against the declaration
+pub fn check_struct_literal(
+    project: &Project,
+    module_id: ModuleId,
+    name: &CheckedExpr,
+    fields: &[CheckedStructField],
+    span: &Span,
+) -> Vec<(ModuleId, RigError)> {
+    let ty = name.ty();
+    let (declared, type_name, declaration_span) = match project.get_type(ty) {
+        Type::Struct(_) => {
+            let struct_ = project.get_struct(ty).unwrap();
+            let declared = struct_
+                .fields
+                .iter()
+                .map(|field| (field.name.as_str(), field.ty, field.visibility, &field.span))
+                .collect::<Vec<_>>();
+
+            (declared, project.type_name(ty), &struct_.span)
+        }
+        Type::Enum(_) => {
+            let enum_ = project.get_enum(ty).unwrap();
+            let variant = match name {
+                CheckedExpr::Path(path) => path
+                    .path
+                    .last()
+                    .and_then(|name| enum_.variants.iter().find(|v| &v.name == name)),
+                _ => None,
+            };
+            let variant = match variant {
+                Some(variant) => variant,
+                None => {
+                    return vec![(
+                        module_id,
+                        RigError::with_no_hint_and_notes(
+                            ErrorType::Hard,
+                            ErrorCode::E0015,
+                            &format!(
+                                "Expected a struct or an enum variant, found enum `{}`",
+                                project.type_name(ty)
+                            ),
+                            name.span().clone(),
+                        ),
+                    )]
+                }
+            };
+            let declared = variant
+                .value
+                .iter()
+                .map(|field| {
+                    (
+                        field.name.as_str(),
+                        field.value_ty,
+                        Visibility::Pub,
+                        &field.span,
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            (
+                declared,
+                format!("{}::{}", project.type_name(ty), variant.name),
+                &enum_.span,
+            )
+        }
+        Type::Undefined => return vec![],
+        _ => {
+            return vec![(
+                module_id,
+                RigError::with_no_hint_and_notes(
+                    ErrorType::Hard,
+                    ErrorCode::E0015,
+                    &format!("Expected a struct, found `{}`", project.type_name(ty)),
+                    name.span().clone(),
+                ),
+            )]
+        }
+    };
+
+    let mut errs = Vec::new();
+    let mut specified: HashMap<&str, &Span> = HashMap::new();
+    for field in fields {
+        if let Some(first) = specified.get(field.name.as_str()) {
+            let error = RigError::error(
+                ErrorCode::E0023,
+                &format!("Field `{}` is specified more than once", field.name),
+                field.span.clone(),
+            )
+            .with_primary_label("specified again here")
+            .with_label((*first).clone(), "first specified here");
+            errs.push((module_id, error));
+
+            continue;
+        }
+        specified.insert(&field.name, &field.span);
+
+        let (_, field_ty, visibility, field_span) =
+            match declared.iter().find(|(name, ..)| *name == field.name) {
+                Some(declared) => *declared,
+                None => {
+                    errs.push(unknown_field(
+                        module_id,
+                        &type_name,
+                        &field.name,
+                        &field.span,
+                        declared.iter().map(|(name, _, _, span)| (*name, *span)),
+                    ));
+                    continue;
+                }
+            };
+
+        if !is_accessible(module_id, ty, visibility) {
+            errs.push(private_field(
+                module_id,
+                &type_name,
+                &field.name,
+                &field.span,
+                field_span,
+            ));
+        }
+        errs.extend(expect_type(
+            project,
+            module_id,
+            field_ty,
+            &field.value,
+            field_span,
+        ));
+    }
+
+    let missing = declared
+        .iter()
+        .filter(|(name, ..)| !specified.contains_key(name))
+        .map(|(name, ..)| format!("`{}`", name))
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        let error = RigError::error(
+            ErrorCode::E0024,
+            &format!(
+                "Missing {} {} in literal of `{}`",
+                if missing.len() == 1 {
+                    "field"
+                } else {
+                    "fields"
+                },
+                missing.join(", "),
+                type_name
+            ),
+            span.clone(),
+        )
+        .with_label(
+            declaration_span.clone(),
+            &format!("`{}` is declared here", type_name),
+        );
+        errs.push((module_id, error));
+    }
+
+    errs
+}
+
+/// Private fields are only accessible from the module that declares the struct
+fn is_accessible(module_id: ModuleId, ty: TypeId, visibility: Visibility) -> bool {
+    visibility == Visibility::Pub || ty.get_scope_id().get_module_id() == module_id
+}
+
+fn find_field<'p>(
+    project: &Project,
+    module_id: ModuleId,
+    ty: TypeId,
+    struct_: &'p StructType,
+    name: &str,
+    span: &Span,
+) -> Result<&'p StructFieldType, Vec<(ModuleId, RigError)>> {
+    let type_name = project.type_name(ty);
+    let field = match struct_.fields.iter().find(|field| field.name == name) {
+        Some(field) => field,
+        None => {
+            return Err(vec![unknown_field(
+                module_id,
+                &type_name,
+                name,
+                span,
+                struct_
+                    .fields
+                    .iter()
+                    .map(|field| (field.name.as_str(), &field.span)),
+            )])
+        }
+    };
+
+    if !is_accessible(module_id, ty, field.visibility) {
+        return Err(vec![private_field(
+            module_id,
+            &type_name,
+            name,
+            span,
+            &field.span,
+        )]);
+    }
+
+    Ok(field)
+}
+
+/// `object` isn't a struct or its type is unknown
+fn no_fields(
+    project: &Project,
+    module_id: ModuleId,
+    object: &CheckedExpr,
+    name: &str,
+    span: &Span,
+) -> Vec<(ModuleId, RigError)> {
+    if object.ty() == TypeId::UNDEFINED {
+        return vec![];
+    }
+
+    vec![(
+        module_id,
+        RigError::with_no_hint_and_notes(
+            ErrorType::Hard,
+            ErrorCode::E0022,
+            &format!(
+                "`{}` has no field `{}`",
+                project.type_name(object.ty()),
+                name
+            ),
+            span.clone(),
+        ),
+    )]
+}
+
+fn unknown_field<'f>(
+    module_id: ModuleId,
+    type_name: &str,
+    name: &str,
+    span: &Span,
+    fields: impl Iterator<Item = (&'f str, &'f Span)> + Clone,
+) -> (ModuleId, RigError) {
+    let message = format!("`{}` has no field `{}`", type_name, name);
+    let similar = similar_name(name, fields.clone().map(|(name, _)| name))
+        .and_then(|similar| fields.into_iter().find(|(name, _)| *name == similar));
+
+    let error = match similar {
+        Some((similar, similar_span)) => RigError::with_hint(
+            ErrorType::Hard,
+            ErrorCode::E0022,
+            &message,
+            span.clone(),
+            &format!("did you mean `{}`?", similar),
+            similar_span.clone(),
+        ),
+        None => RigError::with_no_hint_and_notes(
+            ErrorType::Hard,
+            ErrorCode::E0022,
+            &message,
+            span.clone(),
+        ),
+    };
+
+    (module_id, error)
+}
+
+fn private_field(
+    module_id: ModuleId,
+    type_name: &str,
+    name: &str,
+    span: &Span,
+    field_span: &Span,
+) -> (ModuleId, RigError) {
+    let error = RigError::error(
+        ErrorCode::E0021,
+        &format!("Field `{}` of `{}` is private", name, type_name),
+        span.clone(),
+    )
+    .with_primary_label("private field")
+    .with_label(field_span.clone(), "field declared here without `pub`");
+
+    (module_id, error)
+}
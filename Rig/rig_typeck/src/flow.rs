@@ -0,0 +1,120 @@
+use rig_project::Project;
+use rig_types::checked_expr::{CheckedBoolean, CheckedExpr};
+use rig_types::checked_stmt::{CheckedBlockStmt, CheckedIfStmt, CheckedMatchStmt, CheckedStmt};
+
+/// Whether control never continues past the statement: every path through it returns or it
+/// loops forever
+pub fn stmt_returns(project: &Project, stmt: &CheckedStmt) -> bool {
+    match stmt {
+        CheckedStmt::Return(_) => true,
+        CheckedStmt::Block(block) => block.returns,
+        CheckedStmt::If(if_stmt) => every_branch(if_stmt, &|body| body.returns),
+        CheckedStmt::Match(match_stmt) => {
+            match_stmt.arms.iter().all(|arm| arm.body.returns) && is_exhaustive(project, match_stmt)
+        }
+        CheckedStmt::While(_) => is_endless_loop(stmt),
+        _ => false,
+    }
+}
+
+/// Whether control never reaches the statement after this one: every path through it
+/// returns, jumps with `break` or `continue`, or loops forever
+pub fn stmt_diverges(project: &Project, stmt: &CheckedStmt) -> bool {
+    match stmt {
+        CheckedStmt::Return(_) | CheckedStmt::Break(_) | CheckedStmt::Continue(_) => true,
+        CheckedStmt::Block(block) => block_diverges(project, block),
+        CheckedStmt::If(if_stmt) => every_branch(if_stmt, &|body| block_diverges(project, body)),
+        CheckedStmt::Match(match_stmt) => {
+            match_stmt
+                .arms
+                .iter()
+                .all(|arm| block_diverges(project, &arm.body))
+                && is_exhaustive(project, match_stmt)
+        }
+        CheckedStmt::While(_) => is_endless_loop(stmt),
+        _ => false,
+    }
+}
+
+pub fn block_diverges(project: &Project, block: &CheckedBlockStmt) -> bool {
+    block.stmts.iter().any(|stmt| stmt_diverges(project, stmt))
+}
+
+/// `loop { }`, or `while true { }`, without a `break` that ends it
+pub fn is_endless_loop(stmt: &CheckedStmt) -> bool {
+    match stmt {
+        CheckedStmt::While(while_stmt) => {
+            is_constant(&while_stmt.condition, true) && !breaks(&while_stmt.body.stmts)
+        }
+        _ => false,
+    }
+}
+
+/// Whether the condition is the literal `value`
+pub fn is_constant(condition: &CheckedExpr, value: bool) -> bool {
+    match condition {
+        CheckedExpr::Boolean(CheckedBoolean { value: found, .. }) => *found == value,
+        CheckedExpr::Grouping(grouping) => is_constant(&grouping.expr, value),
+        _ => false,
+    }
+}
+
+/// Whether `check` holds for the body of every branch, including the implicit empty `else`
+/// branch. `else` branches are parsed as `if true { ... }`
+fn every_branch(if_stmt: &CheckedIfStmt, check: &impl Fn(&CheckedBlockStmt) -> bool) -> bool {
+    if !check(&if_stmt.body) {
+        return false;
+    }
+
+    is_constant(&if_stmt.condition, true)
+        || if_stmt
+            .then_branch
+            .as_ref()
+            .is_some_and(|else_branch| every_branch(else_branch, check))
+}
+
+/// Whether the arms match every value, with `_` or by naming every variant of an enum
+fn is_exhaustive(project: &Project, match_stmt: &CheckedMatchStmt) -> bool {
+    let names = match_stmt
+        .arms
+        .iter()
+        .filter_map(|arm| match &arm.expr {
+            CheckedExpr::Variable(variable) => Some(variable.name.as_str()),
+            CheckedExpr::Path(path) => path.path.last().map(String::as_str),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if names.contains(&"_") {
+        return true;
+    }
+
+    // arm patterns of enums are always variants, or `_`
+    project
+        .get_enum(match_stmt.matched_expr.ty())
+        .is_some_and(|enum_| {
+            enum_
+                .variants
+                .iter()
+                .all(|variant| names.contains(&variant.name.as_str()))
+        })
+}
+
+/// Whether any of the statements breaks out of the loop they're in. `break` inside nested
+/// loops ends those loops instead
+fn breaks(stmts: &[CheckedStmt]) -> bool {
+    stmts.iter().any(|stmt| match stmt {
+        CheckedStmt::Break(_) => true,
+        CheckedStmt::Block(block) => breaks(&block.stmts),
+        CheckedStmt::If(if_stmt) => if_breaks(if_stmt),
+        CheckedStmt::Match(match_stmt) => match_stmt.arms.iter().any(|arm| breaks(&arm.body.stmts)),
+        _ => false,
+    })
+}
+
+fn if_breaks(if_stmt: &CheckedIfStmt) -> bool {
+    breaks(&if_stmt.body.stmts)
+        || if_stmt
+            .then_branch
+            .as_ref()
+            .is_some_and(|else_branch| if_breaks(else_branch))
+}
@@ -1,5 +1,6 @@
 use crate::typeck_module;
-use rig_ast::expr::Expr;
+use rig_ast::token::PATH_PREFIXES;
+use rig_ast::use_tree::UseTree;
 use rig_ast::visibility::Visibility;
 use rig_error::{ErrorCode, ErrorType, RigError};
 use rig_project::parsed_module::ParsedModule;
@@ -7,139 +8,553 @@ use rig_project::Project;
 use rig_session::Session;
 use rig_span::Span;
 use rig_types::checked_stmt::CheckedStmt;
-use rig_types::{Import, Module, ModuleId, ScopeId, TypeIdOrModuleId};
+use rig_types::{Import, Module, ModuleId, ResolutionError, ScopeId, TypeIdOrModuleId};
 use rig_utils::bug;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
-// TODO: handle modules inside subdirectories
 pub fn check_use_stmt(
     project: &mut Project,
     session: &Session,
     typechecker_errors: &mut HashMap<ModuleId, Vec<RigError>>,
     module_id: ModuleId,
-    scope_id: ScopeId,
-    path: &Expr,
+    _scope_id: ScopeId,
+    tree: &UseTree,
     visibility: Visibility,
     _span: &Span,
 ) -> (Option<CheckedStmt>, Vec<(ModuleId, RigError)>) {
     let mut errs = Vec::new();
-    let (path, span) = match path {
-        Expr::PathExpr { path, span } => (path, span),
-        expr => bug!(expr, "Unexpected expression in `use` statement"),
+    let mut imports = Vec::new();
+    flatten_use_tree(tree, &[], &mut imports);
+
+    for import in imports {
+        errs.extend(match import {
+            UseItem::Path { path, name, span } => import_path(
+                project,
+                session,
+                typechecker_errors,
+                module_id,
+                &path,
+                &name,
+                visibility,
+                span,
+            ),
+            UseItem::Glob { path, span } => import_glob(
+                project,
+                session,
+                typechecker_errors,
+                module_id,
+                &path,
+                visibility,
+                span,
+            ),
+        });
+    }
+
+    (None, errs)
+}
+
+/// Single path imported by a `use` tree
+enum UseItem<'t> {
+    /// Imported as `name`
+    Path {
+        path: Vec<String>,
+        name: String,
+        span: &'t Span,
+    },
+
+    /// Every public item of the module at `path` is imported
+    Glob { path: Vec<String>, span: &'t Span },
+}
+
+/// Paths of a `use` tree, with the prefixes of their groups
+fn flatten_use_tree<'t>(tree: &'t UseTree, prefix: &[String], imports: &mut Vec<UseItem<'t>>) {
+    match tree {
+        UseTree::Path { path, alias, span } => {
+            let path = [prefix, path].concat();
+            let name = alias
+                .clone()
+                .unwrap_or_else(|| path.last().unwrap().clone());
+
+            imports.push(UseItem::Path { path, name, span });
+        }
+        UseTree::Glob {
+            prefix: glob_prefix,
+            span,
+        } => imports.push(UseItem::Glob {
+            path: [prefix, glob_prefix].concat(),
+            span,
+        }),
+        UseTree::Group {
+            prefix: group_prefix,
+            children,
+            ..
+        } => {
+            let prefix = [prefix, group_prefix].concat();
+
+            for child in children {
+                flatten_use_tree(child, &prefix, imports);
+            }
+        }
+    }
+}
+
+/// Module the first name of a `use` path refers to. Modules declared or imported in the module
+/// come before files in the search paths
+fn resolve_first_module(
+    project: &mut Project,
+    session: &Session,
+    typechecker_errors: &mut HashMap<ModuleId, Vec<RigError>>,
+    module_id: ModuleId,
+    name: &str,
+    span: &Span,
+) -> Result<ModuleId, Vec<(ModuleId, RigError)>> {
+    if let Some(Import::Module(module_id, _)) = project.get_module(module_id).imports.get(name) {
+        return Ok(*module_id);
+    }
+
+    // search for module in search path
+    for search_path in &session.search_paths {
+        match module_files(search_path, name).as_slice() {
+            [file_path] => {
+                return load_use_file(
+                    project,
+                    session,
+                    typechecker_errors,
+                    module_id,
+                    name,
+                    file_path,
+                    span,
+                )
+            }
+            [first, second, ..] => {
+                return Err(vec![(
+                    module_id,
+                    ambiguous_module_file(name, first, second, span),
+                )])
+            }
+            [] => (),
+        }
+    }
+
+    Err(vec![(
+        module_id,
+        RigError::with_no_hint_and_notes(
+            ErrorType::Hard,
+            ErrorCode::E0009,
+            "Failed to import module",
+            span.clone(),
+        ),
+    )])
+}
+
+/// Resolves the module or item a `use` path refers to. Paths starting with `self`, `super` or
+/// `crate` are relative to `module_id`, other paths start with a module name
+fn resolve_use_path(
+    project: &mut Project,
+    session: &Session,
+    typechecker_errors: &mut HashMap<ModuleId, Vec<RigError>>,
+    module_id: ModuleId,
+    path: &[String],
+    span: &Span,
+) -> Result<TypeIdOrModuleId, Vec<(ModuleId, RigError)>> {
+    if PATH_PREFIXES.contains(&path[0].as_str()) {
+        let module = project.get_module(module_id);
+        return module
+            .try_import(&project.modules, path)
+            .map_err(|error| vec![resolution_error(module_id, error, span)]);
+    }
+
+    let resolved_module = resolve_first_module(
+        project,
+        session,
+        typechecker_errors,
+        module_id,
+        &path[0],
+        span,
+    )?;
+    if path.len() == 1 {
+        return Ok(TypeIdOrModuleId::ModuleId(resolved_module, Visibility::Pub));
+    }
+
+    let module = project.get_module(resolved_module);
+    module
+        .try_import(&project.modules, &path[1..])
+        .map_err(|error| vec![resolution_error(module_id, error, span)])
+}
+
+/// Imports the module or item at `path` as `name`
+fn import_path(
+    project: &mut Project,
+    session: &Session,
+    typechecker_errors: &mut HashMap<ModuleId, Vec<RigError>>,
+    module_id: ModuleId,
+    path: &[String],
+    name: &str,
+    visibility: Visibility,
+    span: &Span,
+) -> Vec<(ModuleId, RigError)> {
+    let import = match resolve_use_path(project, session, typechecker_errors, module_id, path, span)
+    {
+        Ok(TypeIdOrModuleId::TypeId(id, Visibility::Pub)) => Import::TypeId(id, visibility),
+        Ok(TypeIdOrModuleId::ModuleId(id, Visibility::Pub)) => Import::Module(id, visibility),
+        Ok(t) => bug!(t, "Imported private module"),
+        Err(errs) => return errs,
     };
 
-    let mut resolved_module = None;
-    let mut resolved_typeid = None;
-    // check if any module exists with same name in project
-    for module in &project.modules {
-        if path[0] == module.location.last().unwrap().as_str() {
-            resolved_module = Some(module.id);
+    // explicit imports take precedence over globs
+    let module = project.get_module_mut(module_id);
+    module.glob_imports.remove(name);
+    module.imports.insert(name.to_string(), import);
+
+    vec![]
+}
+
+/// Imports every public item and module of the module at `path`. Names that are already
+/// declared or imported explicitly keep referring to what they did, a name imported by two
+/// globs is ambiguous
+fn import_glob(
+    project: &mut Project,
+    session: &Session,
+    typechecker_errors: &mut HashMap<ModuleId, Vec<RigError>>,
+    module_id: ModuleId,
+    path: &[String],
+    visibility: Visibility,
+    span: &Span,
+) -> Vec<(ModuleId, RigError)> {
+    let glob_module =
+        match resolve_use_path(project, session, typechecker_errors, module_id, path, span) {
+            Ok(TypeIdOrModuleId::ModuleId(id, _)) => id,
+            Ok(TypeIdOrModuleId::TypeId(..)) => {
+                return vec![resolution_error(
+                    module_id,
+                    ResolutionError::InvalidImport,
+                    span,
+                )]
+            }
+            Err(errs) => return errs,
+        };
+
+    let mut errs = Vec::new();
+    for (name, import) in public_items(project, glob_module) {
+        let import = match import {
+            Import::Module(id, _) => Import::Module(id, visibility),
+            Import::TypeId(id, _) => Import::TypeId(id, visibility),
+        };
+
+        let module = project.get_module(module_id);
+        let is_declared = module
+            .scopes
+            .first()
+            .map(|scope| {
+                scope.find_function(&name).is_some()
+                    || scope.find_struct(&name).is_some()
+                    || scope.find_enum(&name).is_some()
+            })
+            .unwrap_or(false);
+        if is_declared {
+            continue;
+        }
+
+        match (module.imports.get(&name), module.glob_imports.get(&name)) {
+            (Some(previous), Some(first_glob)) if !same_import(*previous, import) => {
+                let error = RigError::error(
+                    ResolutionError::AmbiguousImport.to_error_code(),
+                    &format!("`{}` is imported by more than one glob", name),
+                    span.clone(),
+                )
+                .with_primary_label(&format!("`{}` imported again here", name))
+                .with_label(
+                    first_glob.clone(),
+                    &format!("`{}` is first imported here", name),
+                );
+                errs.push((module_id, error));
+            }
+            (Some(_), _) => (),
+            (None, _) => {
+                let module = project.get_module_mut(module_id);
+                module.imports.insert(name.clone(), import);
+                module.glob_imports.insert(name, span.clone());
+            }
         }
     }
-    if let Some(module_id) = resolved_module {
-        if path.len() == 1 {
-            let module = project.get_module_mut(module_id);
-
-            module.imports.insert(
-                path.last().unwrap().clone(),
-                Import::Module(module_id, visibility),
-            );
+
+    errs
+}
+
+/// Items and modules a glob import of the module brings in
+fn public_items(project: &Project, module_id: ModuleId) -> Vec<(String, Import)> {
+    let module = project.get_module(module_id);
+    let mut items = Vec::new();
+
+    if let Some(scope) = module.scopes.first() {
+        for (name, (visibility, type_id)) in scope
+            .functions
+            .iter()
+            .chain(&scope.structs)
+            .chain(&scope.enums)
+        {
+            if *visibility == Visibility::Pub {
+                items.push((name.clone(), Import::TypeId(*type_id, Visibility::Pub)));
+            }
         }
-    } else {
-        // search for module in search path
-        for search_path in &session.search_paths {
-            if let Ok(dir) = search_path.read_dir() {
-                for entry in dir {
-                    if let Ok(entry) = entry {
-                        let file_path = entry.path();
-
-                        if file_path.is_file() {
-                            if file_path.file_name().unwrap().to_str().unwrap()
-                                == path[0].clone() + ".rig"
-                            {
-                                if let Ok(file_content) = std::fs::read_to_string(&file_path) {
-                                    let parsed_module = ParsedModule::new(file_path, file_content);
-
-                                    if parsed_module.has_lexer_errors() {
-                                        parsed_module.print_parser_errors();
-                                        std::process::exit(1);
-                                    }
-
-                                    parsed_module.print_parser_errors();
-                                    let module_id = project.insert_module(Module::new(
-                                        parsed_module.absolute_path.clone(),
-                                        vec![parsed_module.module_name.clone()],
-                                        parsed_module.file_content.clone(),
-                                        parsed_module.ast.clone(),
-                                    ));
-
-                                    typeck_module(
-                                        project,
-                                        session,
-                                        typechecker_errors,
-                                        &parsed_module,
-                                        module_id,
-                                        scope_id,
-                                    );
-
-                                    resolved_module = Some(module_id);
-                                }
-                            }
-                        }
-                    }
-                }
+    }
+    for (name, import) in &module.imports {
+        match import {
+            Import::Module(_, Visibility::Pub) | Import::TypeId(_, Visibility::Pub) => {
+                items.push((name.clone(), *import))
             }
+            _ => (),
         }
     }
 
-    if let Some(resolved_module) = resolved_module {
-        if path.len() > 1 {
-            let module = project.get_module(resolved_module);
-            let resolved_ty = module.try_import(&project.modules, &path[1..path.len()]);
+    items
+}
 
-            if let Ok(type_id) = resolved_ty {
-                resolved_typeid = Some(type_id);
-            } else if let Err(error) = resolved_ty {
-                errs.push((
-                    module_id,
-                    RigError::with_no_hint_and_notes(
-                        ErrorType::Hard,
-                        error.to_error_code(),
-                        &error.to_string(),
-                        span.clone(),
+/// Whether two imports refer to the same item, regardless of their visibility
+fn same_import(first: Import, second: Import) -> bool {
+    match (first, second) {
+        (Import::Module(first, _), Import::Module(second, _)) => first == second,
+        (Import::TypeId(first, _), Import::TypeId(second, _)) => first == second,
+        _ => false,
+    }
+}
+
+fn resolution_error(
+    module_id: ModuleId,
+    error: ResolutionError,
+    span: &Span,
+) -> (ModuleId, RigError) {
+    (
+        module_id,
+        RigError::with_no_hint_and_notes(
+            ErrorType::Hard,
+            error.to_error_code(),
+            &error.to_string(),
+            span.clone(),
+        ),
+    )
+}
+
+/// Module of the file imported by a `use` statement of `module_id`. Files are loaded and
+/// typechecked once, later imports of the same file get the same module
+fn load_use_file(
+    project: &mut Project,
+    session: &Session,
+    typechecker_errors: &mut HashMap<ModuleId, Vec<RigError>>,
+    module_id: ModuleId,
+    name: &str,
+    file_path: &Path,
+    span: &Span,
+) -> Result<ModuleId, Vec<(ModuleId, RigError)>> {
+    if let Some(loaded) = project.find_file_module(file_path) {
+        return match import_cycle(project, module_id, loaded, span) {
+            Some(error) => Err(vec![error]),
+            None => Ok(loaded),
+        };
+    }
+
+    let file_content = match std::fs::read_to_string(file_path) {
+        Ok(file_content) => file_content,
+        Err(error) => {
+            return Err(vec![(
+                module_id,
+                RigError::with_no_hint_and_notes(
+                    ErrorType::Hard,
+                    ErrorCode::E0009,
+                    &format!(
+                        "Cannot read module `{}` from `{}`: {}",
+                        name,
+                        file_path.display(),
+                        error
                     ),
-                ));
-            }
+                    span.clone(),
+                ),
+            )])
+        }
+    };
+    let parsed_module = ParsedModule::new(file_path.to_path_buf(), file_content);
+    let loaded = project.insert_file_module(Module::new(
+        parsed_module.absolute_path.clone(),
+        vec![name.to_string()],
+        parsed_module.file_content.clone(),
+        parsed_module.ast.clone(),
+    ));
+
+    // errors in the file belong to the loaded module, the parts that could be parsed are
+    // still checked
+    let file_errors = [
+        parsed_module.lexer_errors.as_slice(),
+        &parsed_module.parser_errors,
+    ]
+    .concat();
+    if !file_errors.is_empty() {
+        typechecker_errors
+            .entry(loaded)
+            .or_default()
+            .extend(file_errors);
+    }
+
+    project.import_stack.push((module_id, span.clone()));
+    typeck_module(project, session, typechecker_errors, &parsed_module, loaded);
+    project.import_stack.pop();
+
+    Ok(loaded)
+}
+
+/// Reports importing a module whose items are still being declared, because it imports the
+/// module that imports it. Every import of the cycle is noted
+fn import_cycle(
+    project: &Project,
+    module_id: ModuleId,
+    imported: ModuleId,
+    span: &Span,
+) -> Option<(ModuleId, RigError)> {
+    // modules are checked together with the modules they're declared in
+    let contains = |outer: ModuleId, mut module_id: ModuleId| loop {
+        if module_id == outer {
+            break true;
+        }
+        match project.get_module(module_id).parent_module {
+            Some(parent) => module_id = parent,
+            None => break false,
+        }
+    };
+
+    let mut chain = project.import_stack.clone();
+    chain.push((module_id, span.clone()));
+    let start = chain
+        .iter()
+        .position(|(importer, _)| contains(imported, *importer))?;
+    let chain = &chain[start..];
+
+    let name = |module_id: ModuleId| project.get_module(module_id).location.join("::");
+    let mut error = RigError::with_no_hint_and_notes(
+        ErrorType::Hard,
+        ErrorCode::E0031,
+        &format!(
+            "Cycle detected while importing `{}`, it's still being checked",
+            name(imported)
+        ),
+        span.clone(),
+    );
+    // the last import of the chain is the one reported
+    for (idx, (importer, span)) in chain.iter().enumerate() {
+        let next = chain
+            .get(idx + 1)
+            .map(|(next, _)| *next)
+            .unwrap_or(imported);
+        let message = format!("`{}` imports `{}` here", name(*importer), name(next));
+
+        error = if idx + 1 == chain.len() {
+            error.with_primary_label(&message)
         } else {
-            let module = project.get_module_mut(module_id);
-            module.imports.insert(
-                path.last().unwrap().clone(),
-                Import::Module(resolved_module, visibility),
-            );
+            error.with_label(span.clone(), &message)
+        };
+    }
+
+    Some((module_id, error))
+}
+
+/// Loads the file of `mod name;` declared in the module
+pub fn load_mod_file(
+    project: &Project,
+    module_id: ModuleId,
+    name: &str,
+    span: &Span,
+) -> Result<ParsedModule, Vec<(ModuleId, RigError)>> {
+    let dir = module_dir(project, module_id);
+    let file_path = match module_files(&dir, name).as_slice() {
+        [file_path] => file_path.clone(),
+        [first, second, ..] => {
+            return Err(vec![(
+                module_id,
+                ambiguous_module_file(name, first, second, span),
+            )])
+        }
+        [] => {
+            return Err(vec![(
+                module_id,
+                RigError::with_hint(
+                    ErrorType::Hard,
+                    ErrorCode::E0009,
+                    &format!("Cannot find module `{}`", name),
+                    span.clone(),
+                    &format!(
+                        "create `{}` or `{}`",
+                        dir.join(format!("{}.rig", name)).display(),
+                        dir.join(name).join("mod.rig").display()
+                    ),
+                    span.clone(),
+                ),
+            )])
         }
-    } else {
-        errs.push((
+    };
+
+    match std::fs::read_to_string(&file_path) {
+        Ok(file_content) => Ok(ParsedModule::new(file_path, file_content)),
+        Err(error) => Err(vec![(
             module_id,
             RigError::with_no_hint_and_notes(
                 ErrorType::Hard,
                 ErrorCode::E0009,
-                "Failed to import module",
+                &format!(
+                    "Cannot read module `{}` from `{}`: {}",
+                    name,
+                    file_path.display(),
+                    error
+                ),
                 span.clone(),
             ),
-        ));
+        )]),
     }
+}
 
-    if let Some(type_id) = resolved_typeid {
-        let module = project.get_module_mut(module_id);
-        module.imports.insert(
-            path.last().unwrap().clone(),
-            match type_id {
-                TypeIdOrModuleId::TypeId(id, Visibility::Pub) => Import::TypeId(id, visibility),
-                TypeIdOrModuleId::ModuleId(id, Visibility::Pub) => Import::Module(id, visibility),
-                t => bug!(t, "Imported private module"),
-            },
-        );
+/// Directory the files of modules declared in the module are searched in. The entry point and
+/// `mod.rig` files own the directory they're in, other files own the directory named after
+/// them(`net/http.rig` owns `net/http/`). Inline modules are a directory inside the directory
+/// of their parent
+fn module_dir(project: &Project, module_id: ModuleId) -> PathBuf {
+    let module = project.get_module(module_id);
+    let file_path = Path::new(&module.absolute_path);
+
+    match module.parent_module {
+        Some(parent) if project.get_module(parent).absolute_path == module.absolute_path => {
+            module_dir(project, parent).join(module.location.last().unwrap())
+        }
+        _ if module_id == project.entry_point
+            || file_path.file_name().and_then(|name| name.to_str()) == Some("mod.rig") =>
+        {
+            file_path
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default()
+        }
+        _ => file_path.with_extension(""),
     }
+}
 
-    (None, errs)
+fn ambiguous_module_file(name: &str, first: &Path, second: &Path, span: &Span) -> RigError {
+    RigError::with_no_hint_and_notes(
+        ErrorType::Hard,
+        ErrorCode::E0030,
+        &format!(
+            "File for module `{}` found at both `{}` and `{}`",
+            name,
+            first.display(),
+            second.display()
+        ),
+        span.clone(),
+    )
+}
+
+/// Existing files of module `name` in `dir`, `name.rig` and `name/mod.rig`
+fn module_files(dir: &Path, name: &str) -> Vec<PathBuf> {
+    [
+        dir.join(format!("{}.rig", name)),
+        dir.join(name).join("mod.rig"),
+    ]
+    .into_iter()
+    .filter(|file_path| file_path.is_file())
+    .collect()
 }
@@ -1,13 +1,25 @@
+#![allow(clippy::too_many_arguments)]
+mod collect;
+mod exhaustiveness;
+mod expr;
+mod field;
+mod flow;
 mod import;
+mod reachability;
+mod resolve;
 mod stmt;
 
+use crate::collect::{collect_declarations, Declaration};
+use crate::reachability::check_reachability;
 use crate::stmt::typecheck_statement;
 
-use rig_error::RigError;
+use rig_error::context::DiagnosticsContext;
+use rig_error::{ErrorType, RigError};
 use rig_project::parsed_module::ParsedModule;
 use rig_project::Project;
 use rig_session::Session;
 
+use rig_ast::stmt::Stmt;
 use rig_types::{Module, ModuleId, ScopeId};
 use std::collections::HashMap;
 
@@ -36,10 +48,9 @@ impl<'tcx> TypeChecker<'tcx> {
             parsed_module.ast.clone(),
         );
 
-        let project = Project {
-            modules: vec![module],
-            entry_point: ModuleId(0), // entry points are always supposed to be the first module
-        };
+        // entry points are always supposed to be the first module
+        let mut project = Project::new();
+        project.insert_file_module(module);
 
         Self {
             entry_point: parsed_module,
@@ -50,18 +61,31 @@ impl<'tcx> TypeChecker<'tcx> {
     }
 
     pub fn do_typechecking(&mut self) {
-        let scope_id = self.project.get_entry_point_mut().new_scope();
-
         typeck_module(
             &mut self.project,
-            &self.session,
+            self.session,
             &mut self.typechecking_errors,
             &self.entry_point,
             ModuleId(0),
-            scope_id,
         );
     }
 
+    /// Returns whether any module has an error that prevents the program from running
+    pub fn has_errors(&self) -> bool {
+        self.typechecking_errors
+            .values()
+            .flatten()
+            .any(|error| error.error_type == ErrorType::Hard)
+    }
+
+    /// Adds the errors of every module to `context`
+    pub fn report_errors(&self, context: &mut DiagnosticsContext) {
+        for (module_id, errors) in &self.typechecking_errors {
+            let module = self.project.get_module(*module_id);
+            context.extend(errors, &module.source_code);
+        }
+    }
+
     pub fn print_errors(&self) {
         for (module_id, errors) in &self.typechecking_errors {
             let module = self.project.get_module(*module_id);
@@ -79,34 +103,94 @@ fn typeck_module(
     typechecker_errors: &mut HashMap<ModuleId, Vec<RigError>>,
     parsed_module: &ParsedModule,
     id: ModuleId,
-    scope_id: ScopeId,
 ) {
-    let module_id;
-    if !project.has_module(ModuleId(id.0)) {
+    let module_id = if !project.has_module(ModuleId(id.0)) {
         // FIXME(mdgaziur): do something other than cloning
 
-        module_id = project.insert_module(Module::new(
+        project.insert_module(Module::new(
             parsed_module.absolute_path.clone(),
             vec![parsed_module.module_name.clone()],
             parsed_module.file_content.clone(),
             parsed_module.ast.clone(),
-        ));
+        ))
     } else {
-        module_id = ModuleId(id.0);
-    }
+        ModuleId(id.0)
+    };
+
+    typeck_module_ast(
+        project,
+        session,
+        typechecker_errors,
+        module_id,
+        &parsed_module.ast,
+    );
+}
 
-    for node in &parsed_module.ast {
-        let (_, errors) = typecheck_statement(
+/// Typechecks the statements of a module in a new root scope and stores the result in
+/// [Module::checked_ast]
+fn typeck_module_ast(
+    project: &mut Project,
+    session: &Session,
+    typechecker_errors: &mut HashMap<ModuleId, Vec<RigError>>,
+    module_id: ModuleId,
+    ast: &[Stmt],
+) {
+    let scope_id = project.get_module_mut(module_id).new_scope();
+    let (declarations, errors) =
+        collect_declarations(project, session, typechecker_errors, scope_id, ast);
+    extend_errors(typechecker_errors, errors);
+
+    check_module_body(
+        project,
+        session,
+        typechecker_errors,
+        module_id,
+        scope_id,
+        ast,
+        &declarations,
+    );
+}
+
+/// Typechecks the statements of a module whose items are already declared in `scope_id`
+fn check_module_body(
+    project: &mut Project,
+    session: &Session,
+    typechecker_errors: &mut HashMap<ModuleId, Vec<RigError>>,
+    module_id: ModuleId,
+    scope_id: ScopeId,
+    ast: &[Stmt],
+    declarations: &[Option<Declaration>],
+) {
+    // globals are checked before any body so that functions can use globals declared after
+    // them, the checked statements are put back in their order afterwards
+    let (globals, items): (Vec<_>, Vec<_>) = ast
+        .iter()
+        .zip(declarations)
+        .enumerate()
+        .partition(|(_, (node, _))| matches!(node, Stmt::LetStmt { .. }));
+    let mut checked_ast = Vec::new();
+
+    for (idx, (node, declaration)) in globals.into_iter().chain(items) {
+        let (checked, errors) = typecheck_statement(
             project,
             session,
             typechecker_errors,
             module_id,
             scope_id,
             node,
+            declaration.as_ref(),
         );
 
+        checked_ast.extend(checked.map(|checked| (idx, checked)));
         extend_errors(typechecker_errors, errors);
     }
+    checked_ast.sort_by_key(|(idx, _)| *idx);
+
+    project.get_module_mut(module_id).checked_ast = checked_ast
+        .into_iter()
+        .map(|(_, checked)| checked)
+        .collect();
+    extend_errors(typechecker_errors, check_reachability(project, module_id));
 }
 
 fn extend_errors(
@@ -0,0 +1,192 @@
+use crate::flow::{is_constant, is_endless_loop, stmt_diverges};
+use rig_error::{ErrorCode, ErrorType, RigError};
+use rig_project::Project;
+use rig_span::Span;
+use rig_types::checked_stmt::{CheckedBlockStmt, CheckedIfStmt, CheckedStmt};
+use rig_types::ModuleId;
+
+/// Warns about code that can never be executed in the functions of the module: statements
+/// after `return`, `break`, `continue` or endless loops and branches behind constant
+/// conditions
+pub fn check_reachability(project: &Project, module_id: ModuleId) -> Vec<(ModuleId, RigError)> {
+    let mut errs = Vec::new();
+
+    // nested functions are in the list too, so function statements aren't visited. Functions
+    // are declared out of order, they're sorted to report in source order
+    let mut functions = project
+        .get_module(module_id)
+        .functions
+        .iter()
+        .filter(|function| !function.is_extern)
+        .collect::<Vec<_>>();
+    functions.sort_by_key(|function| {
+        (
+            function.span.starting_line,
+            function.span.starting_line_offset,
+        )
+    });
+
+    for function in functions {
+        check_block(project, module_id, &function.body, &mut errs);
+    }
+
+    errs
+}
+
+fn check_block(
+    project: &Project,
+    module_id: ModuleId,
+    block: &CheckedBlockStmt,
+    errs: &mut Vec<(ModuleId, RigError)>,
+) {
+    for (idx, stmt) in block.stmts.iter().enumerate() {
+        check_stmt(project, module_id, stmt, errs);
+
+        if !stmt_diverges(project, stmt) {
+            continue;
+        }
+
+        let mut rest = block.stmts[idx + 1..].iter().filter_map(CheckedStmt::span);
+        if let Some(first) = rest.next() {
+            let region = Span::merge(first.clone(), rest.next_back().unwrap_or(first).clone());
+            let (message, span) = diverging_cause(stmt);
+
+            errs.push(unreachable(
+                module_id,
+                message,
+                span.unwrap_or(first),
+                "this code is never executed",
+                region,
+            ));
+        }
+
+        break;
+    }
+}
+
+fn check_stmt(
+    project: &Project,
+    module_id: ModuleId,
+    stmt: &CheckedStmt,
+    errs: &mut Vec<(ModuleId, RigError)>,
+) {
+    match stmt {
+        CheckedStmt::Block(block) => check_block(project, module_id, block, errs),
+        CheckedStmt::If(if_stmt) => check_if(project, module_id, if_stmt, errs),
+        CheckedStmt::While(while_stmt) => {
+            if is_constant(&while_stmt.condition, false) {
+                errs.push(unreachable(
+                    module_id,
+                    "Unreachable code, the condition is always `false`",
+                    while_stmt.condition.span(),
+                    "the body of this loop is never executed",
+                    while_stmt.body.span.clone(),
+                ));
+            } else {
+                check_block(project, module_id, &while_stmt.body, errs);
+            }
+        }
+        CheckedStmt::For(for_stmt) => check_block(project, module_id, &for_stmt.body, errs),
+        CheckedStmt::Match(match_stmt) => {
+            for arm in &match_stmt.arms {
+                check_block(project, module_id, &arm.body, errs);
+            }
+        }
+        _ => (),
+    }
+}
+
+fn check_if(
+    project: &Project,
+    module_id: ModuleId,
+    if_stmt: &CheckedIfStmt,
+    errs: &mut Vec<(ModuleId, RigError)>,
+) {
+    if is_constant(&if_stmt.condition, false) {
+        errs.push(unreachable(
+            module_id,
+            "Unreachable code, the condition is always `false`",
+            if_stmt.condition.span(),
+            "this branch is never executed",
+            if_stmt.body.span.clone(),
+        ));
+    } else {
+        check_block(project, module_id, &if_stmt.body, errs);
+    }
+
+    let else_branch = match &if_stmt.then_branch {
+        Some(else_branch) => else_branch,
+        None => return,
+    };
+    if is_constant(&if_stmt.condition, true) {
+        errs.push(unreachable(
+            module_id,
+            "Unreachable code, the condition is always `true`",
+            if_stmt.condition.span(),
+            "this `else` branch is never executed",
+            else_branch.span.clone(),
+        ));
+    } else {
+        check_if(project, module_id, else_branch, errs);
+    }
+}
+
+/// Message for code after a statement that never completes and the span to report it at.
+/// `if` statements are reported at their last branch that can be taken, loops and `match`
+/// statements at their first line
+fn diverging_cause(stmt: &CheckedStmt) -> (&'static str, Option<&Span>) {
+    match stmt {
+        CheckedStmt::Return(return_) => ("Unreachable code after `return`", Some(&return_.span)),
+        CheckedStmt::Break(break_) => ("Unreachable code after `break`", Some(&break_.span)),
+        CheckedStmt::Continue(continue_) => {
+            ("Unreachable code after `continue`", Some(&continue_.span))
+        }
+        CheckedStmt::While(while_stmt) if is_endless_loop(stmt) => (
+            "Unreachable code after a loop that never ends",
+            Some(while_stmt.condition.span()),
+        ),
+        CheckedStmt::If(if_stmt) => (
+            "Unreachable code after a statement that returns or jumps on every path",
+            Some(last_taken_branch(if_stmt)),
+        ),
+        CheckedStmt::Match(match_stmt) => (
+            "Unreachable code after a statement that returns or jumps on every path",
+            Some(match_stmt.matched_expr.span()),
+        ),
+        _ => (
+            "Unreachable code after a statement that returns or jumps on every path",
+            None,
+        ),
+    }
+}
+
+/// Body of the last branch that can be taken. Branches after a condition that is always `true`
+/// never are, and they're already reported by [check_if]
+fn last_taken_branch(if_stmt: &CheckedIfStmt) -> &Span {
+    match &if_stmt.then_branch {
+        Some(else_branch) if !is_constant(&if_stmt.condition, true) => {
+            last_taken_branch(else_branch)
+        }
+        _ => &if_stmt.body.span,
+    }
+}
+
+fn unreachable(
+    module_id: ModuleId,
+    message: &str,
+    span: &Span,
+    hint: &str,
+    region: Span,
+) -> (ModuleId, RigError) {
+    (
+        module_id,
+        RigError::with_hint(
+            ErrorType::Soft,
+            ErrorCode::E0007,
+            message,
+            span.clone(),
+            hint,
+            region,
+        ),
+    )
+}
@@ -0,0 +1,298 @@
+use rig_ast::expr::Expr;
+use rig_ast::visibility::Visibility;
+use rig_error::{ErrorCode, ErrorType, RigError};
+use rig_project::Project;
+use rig_types::{Import, ModuleId, Scope, ScopeId, StructType, Type, TypeId, Variable};
+
+/// Names of the builtin types as written in type annotations
+const BUILTIN_TYPE_NAMES: [(&str, TypeId); 5] = [
+    ("int", TypeId::INTEGER),
+    ("float", TypeId::FLOAT),
+    ("bool", TypeId::BOOLEAN),
+    ("String", TypeId::STRING),
+    ("null", TypeId::NULL),
+];
+
+/// Returns the scope and its parents up to the root scope of its module
+pub fn scope_chain(project: &Project, scope_id: ScopeId) -> Vec<&Scope> {
+    let module = project.get_module(scope_id.get_module_id());
+    let mut chain = vec![];
+    let mut current = Some(scope_id);
+
+    while let Some(scope_id) = current {
+        let scope = module.get_scope(scope_id);
+        chain.push(scope);
+        current = scope.parent;
+    }
+
+    chain
+}
+
+pub fn lookup_variable<'p>(
+    project: &'p Project,
+    scope_id: ScopeId,
+    name: &str,
+) -> Option<&'p Variable> {
+    scope_chain(project, scope_id)
+        .into_iter()
+        .find_map(|scope| scope.find_variable(name))
+}
+
+/// Type `Self` refers to in the scope, if it's inside an `impl` block
+pub fn lookup_self_ty(project: &Project, scope_id: ScopeId) -> Option<TypeId> {
+    scope_chain(project, scope_id)
+        .into_iter()
+        .find_map(|scope| scope.self_ty)
+}
+
+/// Function the `return` statements in the scope return from
+pub fn lookup_function(project: &Project, scope_id: ScopeId) -> Option<TypeId> {
+    scope_chain(project, scope_id)
+        .into_iter()
+        .find_map(|scope| scope.function)
+}
+
+/// Returns whether the scope is inside a loop of the function it belongs to
+pub fn in_loop(project: &Project, scope_id: ScopeId) -> bool {
+    for scope in scope_chain(project, scope_id) {
+        if scope.is_loop {
+            return true;
+        }
+        if scope.function.is_some() {
+            return false;
+        }
+    }
+
+    false
+}
+
+/// Items live in one of two namespaces. Names are unique within a namespace of a scope, but a
+/// type and a value may share a name: which one the name refers to depends on where it's used
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Namespace {
+    /// Structs, enums and modules
+    Type,
+
+    /// Functions
+    Value,
+}
+
+impl Namespace {
+    fn of(project: &Project, type_id: TypeId) -> Self {
+        match project.get_type(type_id) {
+            Type::Function(_) => Namespace::Value,
+            _ => Namespace::Type,
+        }
+    }
+}
+
+/// Finds a function, struct or enum declared in the scope itself
+pub fn find_item(scope: &Scope, name: &str, namespace: Namespace) -> Option<TypeId> {
+    let item = match namespace {
+        Namespace::Type => scope.find_struct(name).or_else(|| scope.find_enum(name)),
+        Namespace::Value => scope.find_function(name),
+    };
+
+    item.map(|(_, type_id)| *type_id)
+}
+
+/// Looks up a function, struct or enum of `namespace` by name in the scope, its parents and the
+/// imports of the module
+pub fn lookup_item(
+    project: &Project,
+    scope_id: ScopeId,
+    name: &str,
+    namespace: Namespace,
+) -> Option<TypeId> {
+    if name == "Self" {
+        return lookup_self_ty(project, scope_id);
+    }
+
+    let item = scope_chain(project, scope_id)
+        .into_iter()
+        .find_map(|scope| find_item(scope, name, namespace));
+    if item.is_some() {
+        return item;
+    }
+
+    match project
+        .get_module(scope_id.get_module_id())
+        .imports
+        .get(name)
+    {
+        Some(Import::TypeId(type_id, _)) if Namespace::of(project, *type_id) == namespace => {
+            Some(*type_id)
+        }
+        _ => None,
+    }
+}
+
+/// Resolves the module part of a path, like `a::b` in `a::b::c`, from inside `module_id`
+pub fn resolve_module_path(
+    project: &Project,
+    module_id: ModuleId,
+    path: &[String],
+) -> Option<ModuleId> {
+    let module = project.get_module(module_id);
+    let (start, skipped) = module.relative_prefix(&project.modules, path).ok()?;
+    let Some((first, rest)) = path[skipped..].split_first() else {
+        return (skipped > 0).then_some(start);
+    };
+
+    // the current module and its parents can see their private modules
+    let mut current = match project.get_module(start).imports.get(first) {
+        Some(Import::Module(module_id, _)) => *module_id,
+        _ => return None,
+    };
+
+    for name in rest {
+        current = match project.get_module(current).imports.get(name) {
+            Some(Import::Module(module_id, Visibility::Pub)) => *module_id,
+            _ => return None,
+        };
+    }
+
+    Some(current)
+}
+
+/// Resolves a path to an item. Paths may refer to items of other modules(`a::b::function`),
+/// enum variants(`Enum::Variant`, resolved to the enum) and associated functions of
+/// structs(`Struct::function`). The last segment is looked up in `namespace`, the segments
+/// before it are always types or modules
+pub fn resolve_item_path(
+    project: &Project,
+    scope_id: ScopeId,
+    path: &[String],
+    namespace: Namespace,
+) -> Option<TypeId> {
+    let (name, prefix) = path.split_last()?;
+    if prefix.is_empty() {
+        return lookup_item(project, scope_id, name, namespace);
+    }
+
+    let module_id = scope_id.get_module_id();
+    if let Some(module_id) = resolve_module_path(project, module_id, prefix) {
+        let module = project.get_module(module_id);
+
+        return module
+            .scopes
+            .first()
+            .and_then(|root| find_item(root, name, namespace));
+    }
+
+    let owner = resolve_item_path(project, scope_id, prefix, Namespace::Type)?;
+    match project.get_type(owner) {
+        Type::Enum(_) => {
+            let enum_ = project.get_enum(owner)?;
+            enum_
+                .variants
+                .iter()
+                .any(|variant| &variant.name == name)
+                .then_some(owner)
+        }
+        Type::Struct(_) => find_associated_function(project, project.get_struct(owner)?, name),
+        _ => None,
+    }
+}
+
+/// Finds the candidate most similar to `name`, for "did you mean" hints
+pub fn similar_name<'c>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'c str>,
+) -> Option<&'c str> {
+    let max_distance = (name.chars().count() / 3).max(1);
+
+    candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Edit distance between two strings, where swapping two adjacent characters counts as a
+/// single edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    distances[0] = (0..=b.len()).collect();
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+
+            distances[i][j] = distance;
+        }
+    }
+
+    distances[a.len()][b.len()]
+}
+
+/// Finds a method or an associated function of the struct by name
+pub fn find_associated_function(
+    project: &Project,
+    struct_: &StructType,
+    name: &str,
+) -> Option<TypeId> {
+    struct_
+        .helpers
+        .iter()
+        .chain(&struct_.methods)
+        .find(|function| {
+            project
+                .get_function(**function)
+                .and_then(|function| function.location.last())
+                .map(String::as_str)
+                == Some(name)
+        })
+        .copied()
+}
+
+/// Resolves a type annotation like `int`, `Point` or `shapes::Circle`. Unknown types resolve
+/// to [TypeId::UNDEFINED]
+pub fn resolve_type(
+    project: &Project,
+    scope_id: ScopeId,
+    ty: &Expr,
+) -> (TypeId, Vec<(ModuleId, RigError)>) {
+    let path = match ty {
+        Expr::VariableExpr { name, .. } => {
+            if let Some((_, type_id)) = BUILTIN_TYPE_NAMES.iter().find(|(n, _)| n == name) {
+                return (*type_id, vec![]);
+            }
+
+            vec![name.clone()]
+        }
+        Expr::PathExpr { path, .. } => path.clone(),
+        _ => vec![],
+    };
+
+    match resolve_item_path(project, scope_id, &path, Namespace::Type) {
+        Some(type_id) if matches!(project.get_type(type_id), Type::Struct(_) | Type::Enum(_)) => {
+            (type_id, vec![])
+        }
+        _ => (
+            TypeId::UNDEFINED,
+            vec![(
+                scope_id.get_module_id(),
+                RigError::with_no_hint_and_notes(
+                    ErrorType::Hard,
+                    ErrorCode::E0015,
+                    &format!("Unknown type `{}`", ty.to_string(0)),
+                    ty.get_span(),
+                ),
+            )],
+        ),
+    }
+}
@@ -1,14 +1,33 @@
-use crate::import;
+use crate::check_module_body;
+use crate::collect::{collect_declarations, Declaration};
+use crate::exhaustiveness::{check_arms, ArmPattern};
+use crate::expr::{expect_builtin_type, expect_type, is_assignable, typecheck_expr};
+use crate::flow::stmt_returns;
+use crate::resolve::{
+    in_loop, lookup_function, lookup_item, lookup_self_ty, lookup_variable, resolve_item_path,
+    resolve_type, similar_name, Namespace,
+};
 
+use rig_ast::expr::Expr;
+use rig_ast::function_prototype::FnType;
+use rig_ast::match_arms::MatchArm;
 use rig_ast::stmt::Stmt;
+use rig_ast::visibility::Visibility;
 
-use rig_error::RigError;
+use rig_error::{ErrorCode, ErrorType, RigError};
 
 use rig_project::Project;
 use rig_session::Session;
 
-use rig_types::checked_stmt::CheckedStmt;
-use rig_types::{ModuleId, ScopeId};
+use rig_span::Span;
+use rig_types::checked_expr::{CheckedExpr, CheckedPath, CheckedVariable};
+use rig_types::checked_stmt::{
+    CheckedBlockStmt, CheckedBreakStmt, CheckedContinueStmt, CheckedExprStmt, CheckedFnStmt,
+    CheckedForStmt, CheckedIfStmt, CheckedLetStmt, CheckedMatchArms, CheckedMatchStmt,
+    CheckedPrintStmt, CheckedReturnStmt, CheckedStmt, CheckedWhileStmt,
+};
+use rig_types::{ModuleId, ScopeId, Type, TypeId, Variable};
+use rig_utils::bug;
 use std::collections::HashMap;
 
 pub fn typecheck_statement(
@@ -18,22 +37,757 @@ pub fn typecheck_statement(
     module_id: ModuleId,
     scope_id: ScopeId,
     stmt: &Stmt,
+    declaration: Option<&Declaration>,
 ) -> (Option<CheckedStmt>, Vec<(ModuleId, RigError)>) {
     match stmt {
-        Stmt::UseStmt {
-            path,
+        // declared by `collect_declarations`, nothing is left to check
+        Stmt::UseStmt { .. }
+        | Stmt::StructStmt { .. }
+        | Stmt::EnumStmt { .. }
+        | Stmt::ExternStmt { .. } => (None, vec![]),
+        Stmt::ImplStmt { methods, .. } => {
+            // `impl` blocks of unknown structs aren't declared, which is already reported
+            let (impl_scope, declared) = match declaration {
+                Some(Declaration::Impl(impl_scope, declared)) => (*impl_scope, declared),
+                _ => return (None, vec![]),
+            };
+
+            let mut errs = Vec::new();
+            for (method, type_id) in methods.iter().zip(declared) {
+                let body = match method.as_ref() {
+                    Stmt::FnStmt { body, .. } => body,
+                    stmt => bug!(stmt, "function expected in `impl` block"),
+                };
+
+                let (_, new_errs) = check_function_body(
+                    project,
+                    session,
+                    typechecker_errors,
+                    impl_scope,
+                    *type_id,
+                    body,
+                );
+                errs.extend(new_errs);
+            }
+
+            (None, errs)
+        }
+        Stmt::FnStmt { body, span, .. } => {
+            let type_id = match declaration {
+                Some(Declaration::Function(type_id)) => *type_id,
+                declaration => bug!(declaration, "function checked before it was declared"),
+            };
+            let (body, errs) = check_function_body(
+                project,
+                session,
+                typechecker_errors,
+                scope_id,
+                type_id,
+                body,
+            );
+
+            (
+                Some(CheckedStmt::Fn(CheckedFnStmt {
+                    ty: type_id,
+                    body,
+                    span: span.clone(),
+                })),
+                errs,
+            )
+        }
+        Stmt::LetStmt {
+            name,
+            ty,
+            value,
             visibility,
             span,
-        } => import::check_use_stmt(
+        } => {
+            let (expr, mut errs) = typecheck_expr(project, scope_id, value);
+
+            let var_ty = match ty {
+                Some(ty) => {
+                    let (type_id, new_errs) = resolve_type(project, scope_id, ty);
+                    errs.extend(new_errs);
+                    errs.extend(expect_type(
+                        project,
+                        module_id,
+                        type_id,
+                        &expr,
+                        &ty.get_span(),
+                    ));
+
+                    type_id
+                }
+                None => expr.ty(),
+            };
+
+            declare_variable(project, scope_id, name, var_ty, *visibility, span);
+
+            (
+                Some(CheckedStmt::Let(CheckedLetStmt {
+                    name: name.clone(),
+                    var_ty,
+                    expr: CheckedExprStmt {
+                        expr,
+                        span: value.get_span(),
+                    },
+                    span: span.clone(),
+                })),
+                errs,
+            )
+        }
+        Stmt::IfStmt { .. } => {
+            let (if_stmt, errs) =
+                check_if_stmt(project, session, typechecker_errors, scope_id, stmt);
+
+            (Some(CheckedStmt::If(if_stmt)), errs)
+        }
+        Stmt::WhileStmt {
+            condition,
+            body,
+            span,
+        } => {
+            let (condition, mut errs) = typecheck_expr(project, scope_id, condition);
+            errs.extend(expect_builtin_type(
+                project,
+                module_id,
+                TypeId::BOOLEAN,
+                &condition,
+            ));
+            let (body, new_errs) =
+                check_loop_body(project, session, typechecker_errors, scope_id, body, &[]);
+            errs.extend(new_errs);
+
+            (
+                Some(CheckedStmt::While(CheckedWhileStmt {
+                    condition,
+                    body,
+                    span: span.clone(),
+                })),
+                errs,
+            )
+        }
+        Stmt::ForStmt {
+            var,
+            iterable,
+            body,
+            span,
+        } => {
+            let (iterable, mut errs) = typecheck_expr(project, scope_id, iterable);
+
+            // strings are the only iterable values for now. Unknown types are already reported
+            let var_type = if is_assignable(TypeId::STRING, iterable.ty()) {
+                iterable.ty()
+            } else {
+                errs.push((
+                    module_id,
+                    RigError::with_no_hint_and_notes(
+                        ErrorType::Hard,
+                        ErrorCode::E0017,
+                        &format!(
+                            "Mismatched types: `{}` is not iterable",
+                            project.type_name(iterable.ty())
+                        ),
+                        iterable.span().clone(),
+                    ),
+                ));
+
+                TypeId::UNDEFINED
+            };
+
+            let (body, new_errs) = check_loop_body(
+                project,
+                session,
+                typechecker_errors,
+                scope_id,
+                body,
+                &[(var.clone(), var_type, span.clone())],
+            );
+            errs.extend(new_errs);
+
+            (
+                Some(CheckedStmt::For(CheckedForStmt {
+                    iterable,
+                    var_name: var.clone(),
+                    var_type,
+                    body,
+                    span: span.clone(),
+                })),
+                errs,
+            )
+        }
+        Stmt::MatchStmt {
+            matched,
+            arms,
+            span,
+        } => check_match_stmt(
             project,
             session,
             typechecker_errors,
-            module_id,
             scope_id,
-            path,
-            *visibility,
+            matched,
+            arms,
             span,
         ),
-        _ => todo!(),
+        Stmt::PrintStmt { expr, span } => {
+            let (expr, errs) = typecheck_expr(project, scope_id, expr);
+
+            (
+                Some(CheckedStmt::Print(CheckedPrintStmt {
+                    expr,
+                    span: span.clone(),
+                })),
+                errs,
+            )
+        }
+        Stmt::ReturnStmt { expr, span } => {
+            let (expr, mut errs) = typecheck_expr(project, scope_id, expr);
+            let function = lookup_function(project, scope_id)
+                .and_then(|function| project.get_function(function));
+            if let Some(function) = function {
+                errs.extend(expect_type(
+                    project,
+                    module_id,
+                    function.return_ty.unwrap_or(TypeId::NULL),
+                    &expr,
+                    &function.span,
+                ));
+            }
+
+            (
+                Some(CheckedStmt::Return(CheckedReturnStmt {
+                    expr,
+                    span: span.clone(),
+                })),
+                errs,
+            )
+        }
+        Stmt::BlockStmt { .. } => {
+            let (block, errs) =
+                check_block(project, session, typechecker_errors, scope_id, stmt, &[]);
+
+            (Some(CheckedStmt::Block(block)), errs)
+        }
+        Stmt::ExprStmt { expr, span } => {
+            let (expr, errs) = typecheck_expr(project, scope_id, expr);
+
+            (
+                Some(CheckedStmt::Expr(CheckedExprStmt {
+                    expr,
+                    span: span.clone(),
+                })),
+                errs,
+            )
+        }
+        Stmt::BreakStmt { span } => (
+            Some(CheckedStmt::Break(CheckedBreakStmt { span: span.clone() })),
+            expect_loop(project, scope_id, "break", span),
+        ),
+        Stmt::ContinueStmt { span } => (
+            Some(CheckedStmt::Continue(CheckedContinueStmt {
+                span: span.clone(),
+            })),
+            expect_loop(project, scope_id, "continue", span),
+        ),
+        Stmt::ModStmt { .. } => {
+            // modules that can't be found aren't declared, which is already reported
+            if let Some(Declaration::Module(inner_module_id, inner_scope, body, declarations)) =
+                declaration
+            {
+                check_module_body(
+                    project,
+                    session,
+                    typechecker_errors,
+                    *inner_module_id,
+                    *inner_scope,
+                    body,
+                    declarations,
+                );
+            }
+
+            (Some(CheckedStmt::Mod), vec![])
+        }
+    }
+}
+
+fn expect_loop(
+    project: &Project,
+    scope_id: ScopeId,
+    keyword: &str,
+    span: &Span,
+) -> Vec<(ModuleId, RigError)> {
+    if in_loop(project, scope_id) {
+        return vec![];
+    }
+
+    vec![(
+        scope_id.get_module_id(),
+        RigError::error(
+            ErrorCode::E0013,
+            &format!("`{}` outside of a loop", keyword),
+            span.clone(),
+        ),
+    )]
+}
+
+fn declare_variable(
+    project: &mut Project,
+    scope_id: ScopeId,
+    name: &str,
+    ty: TypeId,
+    visibility: Visibility,
+    span: &Span,
+) {
+    project
+        .get_module_mut(scope_id.get_module_id())
+        .get_scope_mut(scope_id)
+        .variables
+        .insert(
+            name.to_string(),
+            Variable {
+                name: name.to_string(),
+                ty,
+                visibility,
+                span: span.clone(),
+            },
+        );
+}
+
+/// Checks the body of a function declared by [collect_declarations] in `scope_id` and stores it
+/// in the function's [FunctionType]
+fn check_function_body(
+    project: &mut Project,
+    session: &Session,
+    typechecker_errors: &mut HashMap<ModuleId, Vec<RigError>>,
+    scope_id: ScopeId,
+    type_id: TypeId,
+    body: &Stmt,
+) -> (CheckedBlockStmt, Vec<(ModuleId, RigError)>) {
+    let function = match project.get_function(type_id) {
+        Some(function) => function,
+        None => bug!(
+            type_id,
+            "function body checked before the function was declared"
+        ),
+    };
+
+    let mut args = function
+        .args
+        .iter()
+        .map(|arg| (arg.name.clone(), arg.ty, arg.span.clone()))
+        .collect::<Vec<_>>();
+    if function.fn_type == FnType::Method {
+        let self_ty = lookup_self_ty(project, scope_id).unwrap_or(TypeId::UNDEFINED);
+        args.insert(0, (String::from("self"), self_ty, function.span.clone()));
+    }
+
+    let module = project.get_module_mut(scope_id.get_module_id());
+    let fn_scope = module.new_child_scope(scope_id);
+    module.get_scope_mut(fn_scope).function = Some(type_id);
+
+    let (body, mut errs) =
+        check_block_in_scope(project, session, typechecker_errors, fn_scope, body, &args);
+
+    // functions without a return type return `null` at the end of their body. Unknown return
+    // types are already reported
+    let function = project.get_function(type_id).unwrap();
+    if let Some(return_ty) = function.return_ty {
+        if !body.returns && !is_assignable(return_ty, TypeId::NULL) {
+            errs.push((
+                scope_id.get_module_id(),
+                RigError::with_hint(
+                    ErrorType::Hard,
+                    ErrorCode::E0029,
+                    &format!(
+                        "`{}` doesn't return a value on every path",
+                        function.location.last().unwrap()
+                    ),
+                    function.span.clone(),
+                    &format!(
+                        "the end of the body is reached without returning `{}`",
+                        project.type_name(return_ty)
+                    ),
+                    Span::for_single_char(
+                        &body.span.file_name,
+                        body.span.ending_line,
+                        body.span.ending_line_end_offset,
+                    ),
+                ),
+            ));
+        }
+    }
+
+    let fn_idx = match project.get_type(type_id) {
+        Type::Function(idx) => *idx,
+        ty => bug!(ty, "function type expected"),
+    };
+    project
+        .get_module_mut(type_id.get_scope_id().get_module_id())
+        .functions[fn_idx]
+        .body = body.clone();
+
+    (body, errs)
+}
+
+/// Checks a block statement in a new scope inside `scope_id`. `variables` are declared in the
+/// new scope before checking the statements
+fn check_block(
+    project: &mut Project,
+    session: &Session,
+    typechecker_errors: &mut HashMap<ModuleId, Vec<RigError>>,
+    scope_id: ScopeId,
+    block: &Stmt,
+    variables: &[(String, TypeId, Span)],
+) -> (CheckedBlockStmt, Vec<(ModuleId, RigError)>) {
+    let block_scope = project
+        .get_module_mut(scope_id.get_module_id())
+        .new_child_scope(scope_id);
+
+    check_block_in_scope(
+        project,
+        session,
+        typechecker_errors,
+        block_scope,
+        block,
+        variables,
+    )
+}
+
+fn check_loop_body(
+    project: &mut Project,
+    session: &Session,
+    typechecker_errors: &mut HashMap<ModuleId, Vec<RigError>>,
+    scope_id: ScopeId,
+    body: &Stmt,
+    variables: &[(String, TypeId, Span)],
+) -> (CheckedBlockStmt, Vec<(ModuleId, RigError)>) {
+    let module = project.get_module_mut(scope_id.get_module_id());
+    let body_scope = module.new_child_scope(scope_id);
+    module.get_scope_mut(body_scope).is_loop = true;
+
+    check_block_in_scope(
+        project,
+        session,
+        typechecker_errors,
+        body_scope,
+        body,
+        variables,
+    )
+}
+
+fn check_block_in_scope(
+    project: &mut Project,
+    session: &Session,
+    typechecker_errors: &mut HashMap<ModuleId, Vec<RigError>>,
+    scope_id: ScopeId,
+    block: &Stmt,
+    variables: &[(String, TypeId, Span)],
+) -> (CheckedBlockStmt, Vec<(ModuleId, RigError)>) {
+    let (stmts, span) = match block {
+        Stmt::BlockStmt { exprs, span } => (exprs, span),
+        stmt => bug!(stmt, "block statement expected"),
+    };
+
+    for (name, ty, span) in variables {
+        declare_variable(project, scope_id, name, *ty, Visibility::NotPub, span);
+    }
+
+    // items declared in the block can be used anywhere in it
+    let (declarations, mut errs) =
+        collect_declarations(project, session, typechecker_errors, scope_id, stmts);
+    let mut checked_stmts = Vec::new();
+    for (stmt, declaration) in stmts.iter().zip(&declarations) {
+        let (checked, new_errs) = typecheck_statement(
+            project,
+            session,
+            typechecker_errors,
+            scope_id.get_module_id(),
+            scope_id,
+            stmt,
+            declaration.as_ref(),
+        );
+
+        errs.extend(new_errs);
+        checked_stmts.extend(checked);
+    }
+
+    (
+        CheckedBlockStmt {
+            scope_id,
+            returns: checked_stmts.iter().any(|stmt| stmt_returns(project, stmt)),
+            stmts: checked_stmts,
+            span: span.clone(),
+        },
+        errs,
+    )
+}
+
+fn check_if_stmt(
+    project: &mut Project,
+    session: &Session,
+    typechecker_errors: &mut HashMap<ModuleId, Vec<RigError>>,
+    scope_id: ScopeId,
+    stmt: &Stmt,
+) -> (CheckedIfStmt, Vec<(ModuleId, RigError)>) {
+    let (condition, body, else_branch, span) = match stmt {
+        Stmt::IfStmt {
+            condition,
+            body,
+            else_branch,
+            span,
+        } => (condition, body, else_branch, span),
+        stmt => bug!(stmt, "`else` branch must be an if statement"),
+    };
+
+    let (condition, mut errs) = typecheck_expr(project, scope_id, condition);
+    errs.extend(expect_builtin_type(
+        project,
+        scope_id.get_module_id(),
+        TypeId::BOOLEAN,
+        &condition,
+    ));
+    let (body, new_errs) = check_block(project, session, typechecker_errors, scope_id, body, &[]);
+    errs.extend(new_errs);
+
+    let then_branch = else_branch.as_ref().map(|else_branch| {
+        let (else_branch, new_errs) =
+            check_if_stmt(project, session, typechecker_errors, scope_id, else_branch);
+        errs.extend(new_errs);
+
+        Box::new(else_branch)
+    });
+
+    (
+        CheckedIfStmt {
+            condition,
+            body,
+            then_branch,
+            span: span.clone(),
+        },
+        errs,
+    )
+}
+
+fn check_match_stmt(
+    project: &mut Project,
+    session: &Session,
+    typechecker_errors: &mut HashMap<ModuleId, Vec<RigError>>,
+    scope_id: ScopeId,
+    matched: &Expr,
+    arms: &[MatchArm],
+    span: &Span,
+) -> (Option<CheckedStmt>, Vec<(ModuleId, RigError)>) {
+    let module_id = scope_id.get_module_id();
+    let (matched_expr, mut errs) = typecheck_expr(project, scope_id, matched);
+    let matched_ty = matched_expr.ty();
+    let mut checked_arms = Vec::new();
+    let mut patterns = Vec::new();
+
+    for arm in arms {
+        let (pattern, variables, arm_pattern) =
+            match variant_pattern(project, scope_id, matched_ty, &arm.match_) {
+                Some((pattern, variables)) => {
+                    let name = variant_name(&pattern).to_string();
+                    let arm_pattern = ArmPattern::Variant(name, pattern.span().clone());
+
+                    (Some(pattern), variables, arm_pattern)
+                }
+                None => match &arm.match_ {
+                    // `_` matches anything
+                    Expr::VariableExpr { name, span } if name == "_" => (
+                        Some(CheckedExpr::Variable(CheckedVariable {
+                            name: name.clone(),
+                            ty: matched_ty,
+                            span: span.clone(),
+                        })),
+                        vec![],
+                        ArmPattern::CatchAll(span.clone()),
+                    ),
+                    pattern => {
+                        if let Some(error) = unknown_variant(project, scope_id, matched_ty, pattern)
+                        {
+                            errs.push(error);
+                            (None, vec![], ArmPattern::Value(pattern.get_span()))
+                        } else {
+                            let (pattern, new_errs) = typecheck_expr(project, scope_id, pattern);
+                            errs.extend(new_errs);
+                            let arm_pattern = ArmPattern::Value(pattern.span().clone());
+
+                            (Some(pattern), vec![], arm_pattern)
+                        }
+                    }
+                },
+            };
+
+        let (body, new_errs) = check_block(
+            project,
+            session,
+            typechecker_errors,
+            scope_id,
+            &arm.body,
+            &variables,
+        );
+        errs.extend(new_errs);
+
+        // arms naming variants that don't exist never match, they're left out after checking
+        // their bodies
+        if let Some(pattern) = pattern {
+            patterns.push(arm_pattern);
+            checked_arms.push(CheckedMatchArms {
+                span: Span::merge(arm.match_.get_span(), body.span.clone()),
+                expr: pattern,
+                body,
+            });
+        }
+    }
+    errs.extend(check_arms(project, module_id, &matched_expr, &patterns));
+
+    (
+        Some(CheckedStmt::Match(CheckedMatchStmt {
+            matched_expr,
+            arms: checked_arms,
+            span: span.clone(),
+        })),
+        errs,
+    )
+}
+
+/// If the matched value is an enum and the pattern names one of its variants(`Variant` or
+/// `Enum::Variant`), returns the pattern and the fields of the variant. The fields are put
+/// into the scope of the arm
+#[allow(clippy::type_complexity)]
+fn variant_pattern(
+    project: &Project,
+    scope_id: ScopeId,
+    matched_ty: TypeId,
+    pattern: &Expr,
+) -> Option<(CheckedExpr, Vec<(String, TypeId, Span)>)> {
+    let enum_ = project.get_enum(matched_ty)?;
+    if !names_matched_enum(project, scope_id, matched_ty, pattern) {
+        return None;
     }
+
+    let checked = match pattern {
+        Expr::VariableExpr { name, span } => CheckedExpr::Variable(CheckedVariable {
+            name: name.clone(),
+            ty: matched_ty,
+            span: span.clone(),
+        }),
+        Expr::PathExpr { path, span } => CheckedExpr::Path(CheckedPath {
+            path: path.clone(),
+            ty: matched_ty,
+            span: span.clone(),
+        }),
+        _ => return None,
+    };
+
+    let name = variant_name(&checked);
+    let variant = enum_.variants.iter().find(|v| v.name == name)?;
+    let fields = variant
+        .value
+        .iter()
+        .map(|field| (field.name.clone(), field.value_ty, field.span.clone()))
+        .collect();
+
+    Some((checked, fields))
+}
+
+/// `false` if the pattern is a path whose prefix doesn't resolve to the enum of the matched value
+fn names_matched_enum(
+    project: &Project,
+    scope_id: ScopeId,
+    matched_ty: TypeId,
+    pattern: &Expr,
+) -> bool {
+    match pattern {
+        Expr::PathExpr { path, .. } => match path.split_last() {
+            Some((_, prefix)) => {
+                resolve_item_path(project, scope_id, prefix, Namespace::Type) == Some(matched_ty)
+            }
+            None => true,
+        },
+        _ => true,
+    }
+}
+
+/// Name of the variant a pattern returned by [variant_pattern] matches
+fn variant_name(pattern: &CheckedExpr) -> &str {
+    match pattern {
+        CheckedExpr::Variable(variable) => &variable.name,
+        CheckedExpr::Path(path) => path.path.last().unwrap(),
+        pattern => bug!(pattern, "variant pattern expected"),
+    }
+}
+
+/// A pattern that looks like a variant(`Variant` or `Enum::Variant`) of the matched enum but
+/// doesn't name any of its variants or anything else in scope
+fn unknown_variant(
+    project: &Project,
+    scope_id: ScopeId,
+    matched_ty: TypeId,
+    pattern: &Expr,
+) -> Option<(ModuleId, RigError)> {
+    let enum_ = project.get_enum(matched_ty)?;
+    let (name, span) = match pattern {
+        Expr::VariableExpr { name, span }
+            if lookup_variable(project, scope_id, name).is_none()
+                && lookup_item(project, scope_id, name, Namespace::Value).is_none() =>
+        {
+            (name, span)
+        }
+        Expr::PathExpr { path, span }
+            if !names_matched_enum(project, scope_id, matched_ty, pattern)
+                && resolve_item_path(project, scope_id, path, Namespace::Value)
+                    .is_none_or(|ty| project.get_enum(ty).is_some()) =>
+        {
+            // a variant of another enum, or a path that doesn't resolve at all
+            let message = format!(
+                "`{}` isn't a variant of `{}`, this arm is never matched",
+                path.join("::"),
+                project.type_name(matched_ty)
+            );
+
+            return Some((
+                scope_id.get_module_id(),
+                RigError::with_no_hint_and_notes(
+                    ErrorType::Soft,
+                    ErrorCode::E0028,
+                    &message,
+                    span.clone(),
+                ),
+            ));
+        }
+        Expr::PathExpr { path, span }
+            if resolve_item_path(project, scope_id, path, Namespace::Value).is_none() =>
+        {
+            (path.last()?, span)
+        }
+        _ => return None,
+    };
+
+    let message = format!(
+        "No variant `{}` in `{}`, this arm is never matched",
+        name,
+        project.type_name(matched_ty)
+    );
+    let similar = similar_name(name, enum_.variants.iter().map(|v| v.name.as_str()))
+        .and_then(|similar| enum_.variants.iter().find(|v| v.name == similar));
+    let error = match similar {
+        Some(similar) => RigError::with_hint(
+            ErrorType::Soft,
+            ErrorCode::E0028,
+            &message,
+            span.clone(),
+            &format!("did you mean `{}`?", similar.name),
+            similar.span.clone(),
+        ),
+        None => RigError::with_no_hint_and_notes(
+            ErrorType::Soft,
+            ErrorCode::E0028,
+            &message,
+            span.clone(),
+        ),
+    };
+
+    Some((scope_id.get_module_id(), error))
 }
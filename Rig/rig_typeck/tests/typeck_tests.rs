@@ -0,0 +1,1183 @@
+use rig_ast::function_prototype::FnType;
+use rig_error::ErrorCode;
+use rig_project::parsed_module::ParsedModule;
+use rig_session::{DebugInfo, Session};
+use rig_typeck::TypeChecker;
+use rig_types::checked_stmt::CheckedStmt;
+use rig_types::{ModuleId, Type, TypeId};
+use std::path::{Path, PathBuf};
+
+fn session() -> Session {
+    Session {
+        search_paths: vec![],
+        debug: DebugInfo::None,
+    }
+}
+
+fn typecheck<'s>(source: &str, session: &'s Session) -> TypeChecker<'s> {
+    let parsed_module = ParsedModule::new(PathBuf::from("test.rig"), source.to_string());
+    assert!(
+        !parsed_module.has_lexer_errors() && !parsed_module.has_parser_errors(),
+        "unexpected error while parsing the test program"
+    );
+
+    let mut typechecker = TypeChecker::new(parsed_module, session);
+    typechecker.do_typechecking();
+
+    typechecker
+}
+
+fn error_codes(typechecker: &TypeChecker) -> Vec<ErrorCode> {
+    typechecker
+        .typechecking_errors
+        .get(&ModuleId(0))
+        .map(|errors| errors.iter().map(|e| e.error_code).collect())
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_items_are_declared() {
+    let session = session();
+    let typechecker = typecheck(
+        "struct Point { x: int, y: float }
+        impl Point {
+            fn new() -> Self { return Point { x: 1, y: 2.0 }; }
+            fn x(self) -> int { return self.x; }
+        }
+        enum Shape { Circle { p: Point }, Empty }
+        extern { fn puts(s: String) -> int; }
+        fn main(shape: Shape) { print shape; }",
+        &session,
+    );
+    assert!(!typechecker.has_errors());
+
+    let project = &typechecker.project;
+    let module = project.get_entry_point();
+
+    let point = module.scopes[0].find_struct("Point").unwrap().1;
+    let point_ty = project.get_struct(point).unwrap();
+    assert_eq!(point_ty.location, ["test", "Point"]);
+    assert_eq!(point_ty.fields[0].ty, TypeId::INTEGER);
+    assert_eq!(point_ty.fields[1].ty, TypeId::FLOAT);
+
+    let new = project.get_function(point_ty.helpers[0]).unwrap();
+    assert_eq!(new.location, ["test", "Point", "new"]);
+    assert_eq!(new.return_ty, Some(point));
+    let x = project.get_function(point_ty.methods[0]).unwrap();
+    assert_eq!(x.fn_type, FnType::Method);
+    assert_eq!(x.body.stmts.len(), 1);
+
+    let shape = module.scopes[0].find_enum("Shape").unwrap().1;
+    let shape_ty = project.get_enum(shape).unwrap();
+    assert_eq!(shape_ty.variants[0].value[0].value_ty, point);
+    assert!(shape_ty.variants[1].value.is_empty());
+
+    let puts = module.scopes[0].find_function("puts").unwrap().1;
+    assert!(project.get_function(puts).unwrap().is_extern);
+
+    let main = module.scopes[0].find_function("main").unwrap().1;
+    assert_eq!(project.get_function(main).unwrap().args[0].ty, shape);
+
+    // only `main` is a statement of its own, the rest only declares items
+    assert_eq!(module.checked_ast.len(), 1);
+    assert!(matches!(module.checked_ast[0], CheckedStmt::Fn(_)));
+}
+
+#[test]
+fn test_scopes_and_variables() {
+    let session = session();
+    let typechecker = typecheck(
+        "let greeting = \"hi\";
+        fn main() {
+            let x: float = 1.0;
+            while true { let y = x; }
+            for c in greeting { print c; }
+        }",
+        &session,
+    );
+    assert!(!typechecker.has_errors());
+
+    let module = typechecker.project.get_entry_point();
+    let root = &module.scopes[0];
+    assert_eq!(root.find_variable("greeting").unwrap().ty, TypeId::STRING);
+
+    let body = match &module.checked_ast[1] {
+        CheckedStmt::Fn(function) => &function.body,
+        stmt => panic!("expected function, found {:?}", stmt),
+    };
+    let fn_scope = module.get_scope(body.scope_id);
+    assert_eq!(fn_scope.parent, Some(root.id));
+    assert_eq!(fn_scope.find_variable("x").unwrap().ty, TypeId::FLOAT);
+    assert!(root.find_variable("x").is_none());
+
+    let (while_body, for_body) = match &body.stmts[1..] {
+        [CheckedStmt::While(while_), CheckedStmt::For(for_)] => (&while_.body, &for_.body),
+        stmts => panic!("expected while and for loops, found {:?}", stmts),
+    };
+    let while_scope = module.get_scope(while_body.scope_id);
+    assert_eq!(while_scope.parent, Some(fn_scope.id));
+    assert_eq!(while_scope.find_variable("y").unwrap().ty, TypeId::FLOAT);
+    assert_eq!(
+        module
+            .get_scope(for_body.scope_id)
+            .find_variable("c")
+            .unwrap()
+            .ty,
+        TypeId::STRING
+    );
+}
+
+#[test]
+fn test_match_arms_bind_variant_fields() {
+    let session = session();
+    let typechecker = typecheck(
+        "enum Shape { Circle { r: int }, Empty }
+        fn main() {
+            match Shape::Empty {
+                Circle => { print r; }
+                _ => { print 0; }
+            }
+        }",
+        &session,
+    );
+    assert!(!typechecker.has_errors());
+
+    let module = typechecker.project.get_entry_point();
+    let match_ = match &module.checked_ast[0] {
+        CheckedStmt::Fn(function) => match &function.body.stmts[0] {
+            CheckedStmt::Match(match_) => match_,
+            stmt => panic!("expected match, found {:?}", stmt),
+        },
+        stmt => panic!("expected function, found {:?}", stmt),
+    };
+
+    let shape = module.scopes[0].find_enum("Shape").unwrap().1;
+    assert!(matches!(typechecker.project.get_type(shape), Type::Enum(0)));
+    assert_eq!(match_.arms[0].expr.ty(), shape);
+    assert_eq!(
+        module
+            .get_scope(match_.arms[0].body.scope_id)
+            .find_variable("r")
+            .unwrap()
+            .ty,
+        TypeId::INTEGER
+    );
+    assert!(module
+        .get_scope(match_.arms[1].body.scope_id)
+        .find_variable("r")
+        .is_none());
+}
+
+#[test]
+fn test_unknown_types() {
+    let session = session();
+    let typechecker = typecheck(
+        "struct Point { x: Number }
+        impl Missing { fn f() {} }
+        fn main(p: Point) -> Nothing { let x: Unknown = 1; }",
+        &session,
+    );
+
+    assert!(typechecker.has_errors());
+    assert_eq!(error_codes(&typechecker), [ErrorCode::E0015; 4]);
+}
+
+#[test]
+fn test_expression_types() {
+    let session = session();
+    let typechecker = typecheck(
+        "fn half(x: int) -> float { return x / 2.0; }
+        let a = 1 + 2 * 3;
+        let b = 1 + 2.5;
+        let c = \"a\" + \"b\";
+        let d = 1 < 2 && !(c == \"ab\");
+        let e = -half(3);
+        let f = 1 << 2 | 1;",
+        &session,
+    );
+    assert!(!typechecker.has_errors());
+
+    let root = &typechecker.project.get_entry_point().scopes[0];
+    let ty = |name| root.find_variable(name).unwrap().ty;
+    assert_eq!(ty("a"), TypeId::INTEGER);
+    assert_eq!(ty("b"), TypeId::FLOAT);
+    assert_eq!(ty("c"), TypeId::STRING);
+    assert_eq!(ty("d"), TypeId::BOOLEAN);
+    assert_eq!(ty("e"), TypeId::FLOAT);
+    assert_eq!(ty("f"), TypeId::INTEGER);
+}
+
+#[test]
+fn test_expression_errors() {
+    let session = session();
+    let typechecker = typecheck(
+        "fn add(a: int, b: int) -> int { return a + b; }
+        fn main() {
+            let x: int = \"no\";
+            let y = 1 + \"s\";
+            let z = !1;
+            add(1);
+            add(1, 2.5);
+            missing = 3;
+            x();
+            while 1 {}
+            # only the unknown name is reported, not the addition
+            let w = unknown + 1;
+        }",
+        &session,
+    );
+
+    assert_eq!(
+        error_codes(&typechecker),
+        [
+            ErrorCode::E0017,
+            ErrorCode::E0019,
+            ErrorCode::E0019,
+            ErrorCode::E0018,
+            ErrorCode::E0017,
+            ErrorCode::E0016,
+            ErrorCode::E0020,
+            ErrorCode::E0017,
+            ErrorCode::E0016,
+        ]
+    );
+
+    let errors = &typechecker.typechecking_errors[&ModuleId(0)];
+    assert_eq!(
+        errors[0].message,
+        "Mismatched types: expected `int`, found `String`"
+    );
+    // hints point at the declared types
+    let hint = errors[0].hint_span.as_ref().unwrap();
+    assert_eq!((hint.starting_line, hint.starting_line_offset), (3, 19));
+    assert_eq!(errors[3].message, "`add` takes 2 arguments but 1 was given");
+    let hint = errors[4].hint_span.as_ref().unwrap();
+    assert_eq!((hint.starting_line, hint.starting_line_offset), (1, 15));
+}
+
+#[test]
+fn test_loop_errors() {
+    let session = session();
+    let typechecker = typecheck(
+        "fn main(x: bool) {
+            for c in 5 { print c; }
+            for c in \"ok\" { if c == \"k\" { break; } continue; }
+            loop { while x { break; } break; }
+            if x { break; }
+            match x { _ => { continue; } }
+        }",
+        &session,
+    );
+
+    assert_eq!(
+        error_codes(&typechecker),
+        [ErrorCode::E0017, ErrorCode::E0013, ErrorCode::E0013,]
+    );
+
+    let errors = &typechecker.typechecking_errors[&ModuleId(0)];
+    assert_eq!(errors[0].message, "Mismatched types: `int` is not iterable");
+    assert_eq!(errors[1].message, "`break` outside of a loop");
+    assert_eq!(errors[2].message, "`continue` outside of a loop");
+}
+
+#[test]
+fn test_field_access() {
+    let session = session();
+    let typechecker = typecheck(
+        "struct Pair { first: int, second: String }
+        mod shapes {
+            pub struct Point { x: int, pub y: float }
+            pub fn origin() -> Point { return Point { x: 0, y: 0.0 }; }
+            fn x(p: Point) -> int { return p.x; }
+        }
+        fn main() {
+            let pair = Pair { second: \"b\", first: 1 };
+            pair.first = 2;
+            let second = pair.second;
+            let y = shapes::origin().y;
+        }",
+        &session,
+    );
+    assert!(!typechecker.has_errors());
+
+    let module = typechecker.project.get_entry_point();
+    let main = match &module.checked_ast[1] {
+        CheckedStmt::Fn(main) => main,
+        stmt => panic!("expected function, found {:?}", stmt),
+    };
+    let scope = module.get_scope(main.body.scope_id);
+    assert_eq!(scope.find_variable("second").unwrap().ty, TypeId::STRING);
+    assert_eq!(scope.find_variable("y").unwrap().ty, TypeId::FLOAT);
+}
+
+#[test]
+fn test_field_errors() {
+    let session = session();
+    let typechecker = typecheck(
+        "mod shapes {
+            pub struct Point { x: int, pub y: int }
+            pub fn origin() -> Point { return Point { x: 0, y: 0 }; }
+        }
+        struct Pair { first: int, second: String }
+        fn main() {
+            let p = shapes::origin();
+            print p.x;
+            let q = Pair { first: 1, frist: 2, first: 3 };
+            print q.secnd;
+            q.first = \"s\";
+            print \"s\".x;
+        }",
+        &session,
+    );
+
+    assert_eq!(
+        error_codes(&typechecker),
+        [
+            ErrorCode::E0021,
+            ErrorCode::E0022,
+            ErrorCode::E0023,
+            ErrorCode::E0024,
+            ErrorCode::E0022,
+            ErrorCode::E0017,
+            ErrorCode::E0022,
+        ]
+    );
+
+    let errors = &typechecker.typechecking_errors[&ModuleId(0)];
+    assert_eq!(
+        errors[0].message,
+        "Field `x` of `test::shapes::Point` is private"
+    );
+    assert_eq!(errors[0].labels[0].span.starting_line, 2);
+    assert_eq!(errors[1].hint.as_deref(), Some("did you mean `first`?"));
+    assert_eq!(
+        errors[3].message,
+        "Missing field `second` in literal of `test::Pair`"
+    );
+    assert_eq!(errors[4].hint.as_deref(), Some("did you mean `second`?"));
+}
+
+#[test]
+fn test_enum_variant_literals() {
+    let session = session();
+    let typechecker = typecheck(
+        "enum Shape { Circle { x: int, r: int }, Empty }
+        fn main() {
+            let a = Shape::Circle { x: 0, r: 1 };
+            let b = Shape::Circle { x: 0, r: \"one\" };
+            let c = Shape::Circle { x: 0 };
+            let d = Shape { x: 0, r: 1 };
+        }",
+        &session,
+    );
+
+    assert_eq!(
+        error_codes(&typechecker),
+        [ErrorCode::E0017, ErrorCode::E0024, ErrorCode::E0015]
+    );
+}
+
+#[test]
+fn test_methods_and_associated_functions() {
+    let session = session();
+    let typechecker = typecheck(
+        "struct Point { x: int, y: int }
+        impl Point {
+            fn new(x: int, y: int) -> Self { return Self { x: x, y: y }; }
+            fn origin() -> Self { return Self::new(0, 0); }
+        }
+        impl Point {
+            fn sum(self) -> int { return self.x + self.y; }
+            fn scale(self, by: float) -> float { return self.sum() * by; }
+        }
+        fn main() {
+            let p = Point::origin();
+            let sum = p.sum();
+            let scaled = Point::new(1, 2).scale(1.5);
+        }",
+        &session,
+    );
+    assert!(!typechecker.has_errors());
+
+    let module = typechecker.project.get_entry_point();
+    let point = module.scopes[0].find_struct("Point").unwrap().1;
+    let point_ty = typechecker.project.get_struct(point).unwrap();
+    assert_eq!((point_ty.helpers.len(), point_ty.methods.len()), (2, 2));
+
+    let main = match &module.checked_ast[0] {
+        CheckedStmt::Fn(main) => main,
+        stmt => panic!("expected function, found {:?}", stmt),
+    };
+    let scope = module.get_scope(main.body.scope_id);
+    assert_eq!(scope.find_variable("p").unwrap().ty, point);
+    assert_eq!(scope.find_variable("sum").unwrap().ty, TypeId::INTEGER);
+    assert_eq!(scope.find_variable("scaled").unwrap().ty, TypeId::FLOAT);
+}
+
+#[test]
+fn test_method_errors() {
+    let session = session();
+    let typechecker = typecheck(
+        "struct Point { x: int }
+        impl Point {
+            fn new() -> Self { return Self { x: self.x }; }
+            fn get(self) -> int { return self.x; }
+        }
+        impl Point { fn get(self) -> int { return 0; } }
+        impl Piont {}
+        fn main() {
+            let p = Point::new();
+            print Point::get(p);
+            print p.new();
+            print p.get(1);
+            let get = p.get;
+        }",
+        &session,
+    );
+
+    assert_eq!(
+        error_codes(&typechecker),
+        [
+            ErrorCode::E0008,
+            ErrorCode::E0015,
+            ErrorCode::E0016,
+            ErrorCode::E0025,
+            ErrorCode::E0025,
+            ErrorCode::E0018,
+            ErrorCode::E0025,
+        ]
+    );
+
+    let errors = &typechecker.typechecking_errors[&ModuleId(0)];
+    assert_eq!(errors[0].labels[0].span.starting_line, 4);
+    assert_eq!(errors[1].hint.as_deref(), Some("did you mean `Point`?"));
+    assert_eq!(
+        errors[3].message,
+        "`get` is a method of `test::Point`, call it as `value.get(...)`"
+    );
+    assert_eq!(
+        errors[4].message,
+        "`new` is an associated function of `test::Point`, call it as `Point::new(...)`"
+    );
+}
+
+#[test]
+fn test_match_exhaustiveness() {
+    let session = session();
+    let typechecker = typecheck(
+        "enum Shape { Circle { r: int }, Square { s: int }, Empty }
+        fn main(shape: Shape) {
+            match shape {
+                Circle => { print r; }
+                Shape::Square => { print s; }
+                Empty => { print 0; }
+            }
+            match shape {
+                Circle => { print r; }
+                _ => { print 0; }
+            }
+            match shape {
+                Square => { print s; }
+            }
+        }",
+        &session,
+    );
+
+    assert_eq!(error_codes(&typechecker), [ErrorCode::E0026]);
+    let error = &typechecker.typechecking_errors[&ModuleId(0)][0];
+    assert_eq!(
+        error.message,
+        "Non-exhaustive match, variants `Circle`, `Empty` of `test::Shape` not covered"
+    );
+    assert_eq!(error.labels.len(), 2);
+}
+
+#[test]
+fn test_unreachable_match_arms() {
+    let session = session();
+    let typechecker = typecheck(
+        "enum Shape { Circle { r: int }, Empty }
+        fn main(shape: Shape) {
+            match shape {
+                Circle => { print r; }
+                Cirlce => { print 1; }
+                Circle => { print 2; }
+                Empty => { print 3; }
+                _ => { print 4; }
+            }
+            match shape {
+                _ => { print 5; }
+                Empty => { print 6; }
+            }
+        }",
+        &session,
+    );
+
+    // none of them prevents the program from running
+    assert!(!typechecker.has_errors());
+    assert_eq!(
+        error_codes(&typechecker),
+        [
+            ErrorCode::E0028,
+            ErrorCode::E0027,
+            ErrorCode::E0027,
+            ErrorCode::E0027,
+        ]
+    );
+
+    let errors = &typechecker.typechecking_errors[&ModuleId(0)];
+    assert_eq!(errors[0].hint.as_deref(), Some("did you mean `Circle`?"));
+    assert_eq!(
+        errors[1].labels[0].message,
+        "`Circle` is first matched here"
+    );
+
+    // the arm naming an unknown variant is left out
+    let module = typechecker.project.get_entry_point();
+    let match_ = match &module.checked_ast[0] {
+        CheckedStmt::Fn(function) => match &function.body.stmts[0] {
+            CheckedStmt::Match(match_) => match_,
+            stmt => panic!("expected match, found {:?}", stmt),
+        },
+        stmt => panic!("expected function, found {:?}", stmt),
+    };
+    assert_eq!(match_.arms.len(), 4);
+}
+
+#[test]
+fn test_match_arms_of_another_enum() {
+    let session = session();
+    let typechecker = typecheck(
+        "enum Color { Red, Green }
+        enum Shape { Red, Square }
+        fn main(c: Color) {
+            match c {
+                Shape::Red => { print 1; }
+                Bogus::Green => { print 2; }
+                Color::Red => { print 3; }
+            }
+        }",
+        &session,
+    );
+
+    assert_eq!(
+        error_codes(&typechecker),
+        [ErrorCode::E0028, ErrorCode::E0028, ErrorCode::E0026]
+    );
+    let errors = &typechecker.typechecking_errors[&ModuleId(0)];
+    assert_eq!(
+        errors[0].message,
+        "`Shape::Red` isn't a variant of `test::Color`, this arm is never matched"
+    );
+    assert_eq!(
+        errors[2].message,
+        "Non-exhaustive match, variant `Green` of `test::Color` not covered"
+    );
+}
+
+#[test]
+fn test_definite_returns() {
+    let session = session();
+    let typechecker = typecheck(
+        "enum Sign { Positive, Negative }
+        fn early(x: int) -> int {
+            if x > 0 { return 1; }
+            return 0;
+        }
+        fn branches(x: int) -> int {
+            if x > 0 { return 1; } else if x < 0 { return 2; } else { return 3; }
+        }
+        fn forever() -> int { loop { let x = 1; } }
+        fn matched(sign: Sign) -> int {
+            match sign {
+                Positive => { return 1; }
+                Negative => { return 2; }
+            }
+        }
+        fn nothing() { }",
+        &session,
+    );
+    assert!(!typechecker.has_errors());
+
+    let module = typechecker.project.get_entry_point();
+    let returns = |idx: usize| match &module.checked_ast[idx] {
+        CheckedStmt::Fn(function) => function.body.returns,
+        stmt => panic!("expected function, found {:?}", stmt),
+    };
+    assert!(returns(0) && returns(1) && returns(2) && returns(3));
+    assert!(!returns(4));
+}
+
+#[test]
+fn test_missing_returns() {
+    let session = session();
+    let typechecker = typecheck(
+        "fn no_else(x: int) -> int {
+            if x > 0 { return 1; }
+        }
+        fn breaks() -> int { loop { break; } }
+        fn wrong_type() -> String { return 1; }
+        fn no_type() { return 1; }
+        fn in_loop(x: bool) -> int { while true { if x { break; } return 1; } }",
+        &session,
+    );
+
+    assert_eq!(
+        error_codes(&typechecker),
+        [
+            ErrorCode::E0029,
+            ErrorCode::E0029,
+            ErrorCode::E0017,
+            ErrorCode::E0017,
+            ErrorCode::E0029,
+        ]
+    );
+
+    let errors = &typechecker.typechecking_errors[&ModuleId(0)];
+    assert_eq!(
+        errors[0].message,
+        "`no_else` doesn't return a value on every path"
+    );
+    // the hint points at the end of the body
+    let hint = errors[0].hint_span.as_ref().unwrap();
+    assert_eq!((hint.starting_line, hint.starting_line_offset), (3, 8));
+}
+
+#[test]
+fn test_unreachable_code() {
+    let session = session();
+    let typechecker = typecheck(
+        "struct Point { x: int }
+        impl Point {
+            fn early(self) -> int {
+                return self.x;
+                print 1;
+                print 2;
+            }
+        }
+        fn main() {
+            loop { break; print 3; }
+            if false { print 4; } else { print 5; }
+            while true { continue; }
+            print 6;
+        }
+        fn constant() -> int {
+            if (true) { return 1; } else { return 2; }
+            print 7;
+        }
+        fn branches(x: bool) -> int {
+            if x { return 3; } else { return 4; }
+            print 8;
+        }",
+        &session,
+    );
+
+    // warnings don't prevent the program from running
+    assert!(!typechecker.has_errors());
+    assert_eq!(error_codes(&typechecker), [ErrorCode::E0007; 7]);
+
+    let errors = &typechecker.typechecking_errors[&ModuleId(0)];
+    assert_eq!(errors[0].message, "Unreachable code after `return`");
+    // the hint covers every unreachable statement
+    let hint = errors[0].hint_span.as_ref().unwrap();
+    assert_eq!((hint.starting_line, hint.ending_line), (5, 6));
+    assert_eq!(errors[1].message, "Unreachable code after `break`");
+    assert_eq!(
+        errors[2].message,
+        "Unreachable code, the condition is always `false`"
+    );
+    assert_eq!(
+        errors[3].message,
+        "Unreachable code after a loop that never ends"
+    );
+    assert_eq!(errors[3].hint_span.as_ref().unwrap().starting_line, 13);
+
+    // `if` statements are reported at their last branch that can be taken
+    assert_eq!(
+        errors[4].message,
+        "Unreachable code, the condition is always `true`"
+    );
+    assert_eq!(
+        errors[5].message,
+        "Unreachable code after a statement that returns or jumps on every path"
+    );
+    assert_eq!(
+        (
+            errors[5].span.starting_line,
+            errors[5].span.starting_line_offset
+        ),
+        (16, 22)
+    );
+    assert_eq!(
+        (
+            errors[6].span.starting_line,
+            errors[6].span.starting_line_offset
+        ),
+        (20, 36)
+    );
+}
+
+#[test]
+fn test_redefinitions() {
+    let session = session();
+    let typechecker = typecheck(
+        "struct Point { x: int }
+        enum Point { A }
+        fn helper() {}
+        extern { fn helper(); }
+        fn main() {
+            mod x {}
+            struct x { y: int }
+        }",
+        &session,
+    );
+
+    assert_eq!(error_codes(&typechecker), [ErrorCode::E0008; 3]);
+
+    let errors = &typechecker.typechecking_errors[&ModuleId(0)];
+    assert_eq!(
+        errors[0].message,
+        "`Point` is defined multiple times in this scope"
+    );
+    assert_eq!(
+        errors[0].labels[0].message,
+        "first definition of `Point` is here"
+    );
+    assert_eq!(errors[0].labels[0].span.starting_line, 1);
+    assert_eq!(errors[1].labels[0].span.starting_line, 3);
+    assert_eq!(errors[2].labels[0].span.starting_line, 6);
+
+    // the name keeps referring to the first definition
+    let root = &typechecker.project.get_entry_point().scopes[0];
+    let point = root.find_struct("Point").unwrap().1;
+    assert!(matches!(
+        typechecker.project.get_type(point),
+        Type::Struct(_)
+    ));
+    assert!(root.find_enum("Point").is_none());
+}
+
+#[test]
+fn test_types_and_values_share_names() {
+    let session = session();
+    let typechecker = typecheck(
+        "struct Point { x: int }
+        fn Point(x: int) -> Point { return Point { x: x }; }
+        fn main() {
+            let p: Point = Point(1);
+        }",
+        &session,
+    );
+    assert!(!typechecker.has_errors());
+}
+
+#[test]
+fn test_types_are_not_values() {
+    let session = session();
+    let typechecker = typecheck(
+        "struct P {}
+        enum Shape { Empty }
+        fn main() {
+            let x = P;
+            let y = Shape;
+            print P();
+        }",
+        &session,
+    );
+
+    assert_eq!(error_codes(&typechecker), [ErrorCode::E0016; 3]);
+
+    let errors = &typechecker.typechecking_errors[&ModuleId(0)];
+    assert_eq!(errors[0].message, "Expected a value, found struct `P`");
+    assert_eq!(errors[1].message, "Expected a value, found enum `Shape`");
+}
+
+#[test]
+fn test_items_are_declared_before_use() {
+    let session = session();
+    let typechecker = typecheck(
+        "fn main(tree: Tree) {
+            print tree.first() + shapes::area(3) + later(LIMIT);
+        }
+        impl Tree {
+            fn first(self) -> int { return self.root.value; }
+        }
+        struct Tree { root: Node }
+        struct Node { value: int, next: Tree }
+        fn later(x: int) -> int { return x; }
+        let LIMIT: int = 10;
+        mod shapes {
+            pub fn area(x: int) -> int { return square(x); }
+            fn square(x: int) -> int { return x * x; }
+        }",
+        &session,
+    );
+    assert!(
+        !typechecker.has_errors(),
+        "{:?}",
+        typechecker.typechecking_errors
+    );
+
+    // statements keep their order
+    let checked_ast = &typechecker.project.get_entry_point().checked_ast;
+    assert!(matches!(checked_ast[0], CheckedStmt::Fn(_)));
+    assert!(matches!(checked_ast[1], CheckedStmt::Fn(_)));
+    assert!(matches!(checked_ast[2], CheckedStmt::Let(_)));
+
+    let project = &typechecker.project;
+    let root = &project.get_entry_point().scopes[0];
+    let node = root.find_struct("Node").unwrap().1;
+    let tree = root.find_struct("Tree").unwrap().1;
+    assert_eq!(project.get_struct(node).unwrap().fields[1].ty, tree);
+    assert_eq!(project.get_struct(tree).unwrap().fields[0].ty, node);
+}
+
+/// New directory for the files of a test
+fn test_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("rig_typeck_{}_{}", name, std::process::id()))
+}
+
+/// Writes `files` into `dir` and typechecks `main.rig` in it
+fn typecheck_files<'s>(
+    dir: &Path,
+    files: &[(&str, &str)],
+    session: &'s Session,
+) -> TypeChecker<'s> {
+    for (path, source) in files {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, source).unwrap();
+    }
+
+    let main = dir.join("main.rig");
+    let source = std::fs::read_to_string(&main).unwrap();
+    let mut typechecker = TypeChecker::new(ParsedModule::new(main, source), session);
+    typechecker.do_typechecking();
+    std::fs::remove_dir_all(dir).unwrap();
+
+    typechecker
+}
+
+#[test]
+fn test_modules_are_loaded_from_files() {
+    let session = session();
+    let typechecker = typecheck_files(
+        &test_dir("mod_files"),
+        &[
+            (
+                "main.rig",
+                "mod net;
+                mod util;
+                use net::http::client;
+                fn main() { print client::get() + util::inner::three(); }",
+            ),
+            ("net/mod.rig", "pub mod http;"),
+            ("net/http.rig", "pub mod client;"),
+            ("net/http/client.rig", "pub fn get() -> int { return 7; }"),
+            ("util.rig", "pub mod inner;"),
+            ("util/inner.rig", "pub fn three() -> int { return 3; }"),
+        ],
+        &session,
+    );
+    assert!(
+        !typechecker.has_errors(),
+        "{:?}",
+        typechecker.typechecking_errors
+    );
+
+    let project = &typechecker.project;
+    let client = project
+        .modules
+        .iter()
+        .find(|module| module.location == ["main", "net", "http", "client"])
+        .unwrap();
+    assert!(client.absolute_path.ends_with("client.rig"));
+    assert_eq!(
+        project.get_module(client.parent_module.unwrap()).location,
+        ["main", "net", "http"]
+    );
+}
+
+#[test]
+fn test_module_files_errors() {
+    let session = session();
+    let typechecker = typecheck_files(
+        &test_dir("mod_files_errors"),
+        &[
+            (
+                "main.rig",
+                "mod missing;\nmod both;\nmod broken;\nfn main() {}",
+            ),
+            ("both.rig", ""),
+            ("both/mod.rig", ""),
+            ("broken.rig", "fn broken( {}"),
+        ],
+        &session,
+    );
+
+    assert_eq!(
+        error_codes(&typechecker),
+        [ErrorCode::E0009, ErrorCode::E0030]
+    );
+    let errors = &typechecker.typechecking_errors[&ModuleId(0)];
+    assert!(errors[0]
+        .hint
+        .as_deref()
+        .unwrap()
+        .ends_with("missing/mod.rig`"));
+
+    // errors in the file of a module belong to that module
+    let broken = typechecker
+        .project
+        .modules
+        .iter()
+        .find(|module| module.location == ["main", "broken"])
+        .unwrap();
+    assert_eq!(
+        typechecker.typechecking_errors[&broken.id][0].error_code,
+        ErrorCode::E0005
+    );
+}
+
+#[test]
+fn test_files_are_loaded_once() {
+    let dir = test_dir("diamond");
+    let session = Session {
+        search_paths: vec![dir.clone()],
+        debug: DebugInfo::None,
+    };
+    let typechecker = typecheck_files(
+        &dir,
+        &[
+            (
+                "main.rig",
+                "use left;
+                use right;
+                fn main() { print left::f() + right::g(); }",
+            ),
+            (
+                "left.rig",
+                "use shared;\npub fn f() -> int { return shared::v(); }",
+            ),
+            (
+                "right.rig",
+                "use shared;\npub fn g() -> int { return shared::v(); }",
+            ),
+            ("shared.rig", "pub fn v() -> int { return 2; }"),
+        ],
+        &session,
+    );
+    assert!(
+        !typechecker.has_errors(),
+        "{:?}",
+        typechecker.typechecking_errors
+    );
+
+    let shared = typechecker
+        .project
+        .modules
+        .iter()
+        .filter(|module| module.location == ["shared"])
+        .count();
+    assert_eq!(shared, 1);
+}
+
+#[test]
+fn test_import_cycles() {
+    let dir = test_dir("cycle");
+    let session = Session {
+        search_paths: vec![dir.clone()],
+        debug: DebugInfo::None,
+    };
+    let typechecker = typecheck_files(
+        &dir,
+        &[
+            ("main.rig", "use b;\nfn main() {}"),
+            ("b.rig", "use c;"),
+            ("c.rig", "use main;"),
+        ],
+        &session,
+    );
+
+    let c = typechecker
+        .project
+        .modules
+        .iter()
+        .find(|module| module.location == ["c"])
+        .unwrap();
+    let errors = &typechecker.typechecking_errors[&c.id];
+    assert_eq!(errors[0].error_code, ErrorCode::E0031);
+    let labels = errors[0]
+        .labels
+        .iter()
+        .map(|label| label.message.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(labels, ["`main` imports `b` here", "`b` imports `c` here"]);
+    assert_eq!(
+        errors[0].primary_label.as_deref(),
+        Some("`c` imports `main` here")
+    );
+    assert!(errors[0].labels[0].span.file_name.ends_with("main.rig"));
+}
+
+#[test]
+fn test_use_of_module_with_two_files() {
+    let dir = test_dir("use_both");
+    let session = Session {
+        search_paths: vec![dir.clone()],
+        debug: DebugInfo::None,
+    };
+    let typechecker = typecheck_files(
+        &dir,
+        &[
+            ("main.rig", "use both;\nfn main() {}"),
+            ("both.rig", ""),
+            ("both/mod.rig", ""),
+        ],
+        &session,
+    );
+
+    assert_eq!(error_codes(&typechecker), [ErrorCode::E0030]);
+}
+
+#[test]
+fn test_grouped_glob_and_aliased_imports() {
+    let session = session();
+    let typechecker = typecheck(
+        "mod shapes {
+            pub struct Point { x: int }
+            pub fn origin() -> int { return 0; }
+            fn hidden() -> int { return 1; }
+            pub mod deep {
+                pub fn one() -> int { return 1; }
+                pub fn two() -> int { return 2; }
+            }
+        }
+        use shapes::*;
+        use shapes::deep::{one, two as second};
+        use shapes::deep as d;
+        fn main(p: Point) {
+            print origin() + one() + second() + d::two();
+            print hidden();
+        }",
+        &session,
+    );
+
+    // private items aren't brought in by globs
+    assert_eq!(error_codes(&typechecker), [ErrorCode::E0016]);
+    let errors = &typechecker.typechecking_errors[&ModuleId(0)];
+    assert_eq!(errors[0].message, "Cannot find `hidden` in this scope");
+
+    let imports = &typechecker.project.get_entry_point().imports;
+    assert!(imports.contains_key("second"));
+    assert!(!imports.contains_key("two"));
+    assert!(imports.contains_key("deep"));
+}
+
+#[test]
+fn test_ambiguous_glob_imports() {
+    let session = session();
+    let typechecker = typecheck(
+        "mod first { pub fn f() {} pub fn g() {} }
+        mod second { pub fn f() {} }
+        use first::*;
+        use second::*;
+        fn g() {}
+        fn main() { f(); g(); }",
+        &session,
+    );
+
+    // local items and explicit imports take precedence over globs
+    assert_eq!(error_codes(&typechecker), [ErrorCode::E0011]);
+    let errors = &typechecker.typechecking_errors[&ModuleId(0)];
+    assert_eq!(errors[0].message, "`f` is imported by more than one glob");
+    assert_eq!(errors[0].span.starting_line, 4);
+    assert_eq!(errors[0].labels[0].span.starting_line, 3);
+}
+
+#[test]
+fn test_relative_paths() {
+    let session = session();
+    let typechecker = typecheck(
+        "fn h() -> int { return 100; }
+        pub struct Point { pub x: int }
+        mod outer {
+            fn f() -> int { return 1; }
+            pub mod inner {
+                use super::f;
+                use crate::Point;
+                fn g() -> int { return 10; }
+                pub fn total(p: super::super::Point, q: Point) -> int {
+                    return super::f() + self::g() + crate::h() + f() + p.x + q.x;
+                }
+            }
+        }
+        use self::outer::inner::total;
+        fn main() { print total(Point { x: 1 }, Point { x: 2 }) + self::h(); }",
+        &session,
+    );
+
+    assert!(typechecker.typechecking_errors.is_empty());
+    assert!(typechecker
+        .project
+        .get_entry_point()
+        .imports
+        .contains_key("total"));
+}
+
+#[test]
+fn test_relative_path_errors() {
+    let session = session();
+    let typechecker = typecheck(
+        "use super::f;
+        mod inner { fn hidden() {} }
+        use self::inner::hidden;",
+        &session,
+    );
+
+    // `self` only gives access to private items of the module itself, not its children
+    assert_eq!(
+        error_codes(&typechecker),
+        [ErrorCode::E0009, ErrorCode::E0010]
+    );
+    let errors = &typechecker.typechecking_errors[&ModuleId(0)];
+    assert_eq!(
+        errors[0].message,
+        "`super` refers to the parent of a module without a parent"
+    );
+}
+
+#[test]
+fn test_imported_file_errors() {
+    let dir = test_dir("imported_errors");
+    let session = Session {
+        search_paths: vec![dir.clone()],
+        debug: DebugInfo::None,
+    };
+    let typechecker = typecheck_files(
+        &dir,
+        &[
+            ("main.rig", "use lexed;\nuse parsed;\nfn main() {}"),
+            ("lexed.rig", "pub fn f() {}\n@"),
+            ("parsed.rig", "fn broken( {}"),
+        ],
+        &session,
+    );
+
+    // errors are kept with the module of the file they're in
+    let codes = |name: &str| {
+        let module = typechecker
+            .project
+            .modules
+            .iter()
+            .find(|module| module.location == [name])
+            .unwrap();
+        typechecker.typechecking_errors[&module.id]
+            .iter()
+            .map(|error| error.error_code)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(codes("lexed"), [ErrorCode::E0001]);
+    assert_eq!(codes("parsed")[0], ErrorCode::E0005);
+    assert!(error_codes(&typechecker).is_empty());
+}
@@ -158,3 +158,49 @@ pub struct CheckedUnary {
     pub ty: TypeId,
     pub span: Span,
 }
+
+impl CheckedExpr {
+    pub fn ty(&self) -> TypeId {
+        match self {
+            CheckedExpr::Variable(e) => e.ty,
+            CheckedExpr::Int(e) => e.ty,
+            CheckedExpr::Boolean(e) => e.ty,
+            CheckedExpr::Float(e) => e.ty,
+            CheckedExpr::String(e) => e.ty,
+            CheckedExpr::Null(e) => e.ty,
+            CheckedExpr::SelfLit(e) => e.ty,
+            CheckedExpr::Grouping(e) => e.ty,
+            CheckedExpr::Get(e) => e.ty,
+            CheckedExpr::Set(e) => e.ty,
+            CheckedExpr::Path(e) => e.ty,
+            CheckedExpr::Call(e) => e.ty,
+            CheckedExpr::Struct(e) => e.ty,
+            CheckedExpr::Assignment(e) => e.ty,
+            CheckedExpr::Binary(e) => e.ty,
+            CheckedExpr::Logical(e) => e.ty,
+            CheckedExpr::Unary(e) => e.ty,
+        }
+    }
+
+    pub fn span(&self) -> &Span {
+        match self {
+            CheckedExpr::Variable(e) => &e.span,
+            CheckedExpr::Int(e) => &e.span,
+            CheckedExpr::Boolean(e) => &e.span,
+            CheckedExpr::Float(e) => &e.span,
+            CheckedExpr::String(e) => &e.span,
+            CheckedExpr::Null(e) => &e.span,
+            CheckedExpr::SelfLit(e) => &e.span,
+            CheckedExpr::Grouping(e) => &e.span,
+            CheckedExpr::Get(e) => &e.span,
+            CheckedExpr::Set(e) => &e.span,
+            CheckedExpr::Path(e) => &e.span,
+            CheckedExpr::Call(e) => &e.span,
+            CheckedExpr::Struct(e) => &e.span,
+            CheckedExpr::Assignment(e) => &e.span,
+            CheckedExpr::Binary(e) => &e.span,
+            CheckedExpr::Logical(e) => &e.span,
+            CheckedExpr::Unary(e) => &e.span,
+        }
+    }
+}
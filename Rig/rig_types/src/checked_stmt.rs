@@ -19,6 +19,27 @@ pub enum CheckedStmt {
     Match(CheckedMatchStmt),
 }
 
+impl CheckedStmt {
+    /// `mod` statements don't keep their span
+    pub fn span(&self) -> Option<&Span> {
+        match self {
+            CheckedStmt::Fn(s) => Some(&s.span),
+            CheckedStmt::Let(s) => Some(&s.span),
+            CheckedStmt::If(s) => Some(&s.span),
+            CheckedStmt::While(s) => Some(&s.span),
+            CheckedStmt::For(s) => Some(&s.span),
+            CheckedStmt::Print(s) => Some(&s.span),
+            CheckedStmt::Return(s) => Some(&s.span),
+            CheckedStmt::Block(s) => Some(&s.span),
+            CheckedStmt::Expr(s) => Some(&s.span),
+            CheckedStmt::Break(s) => Some(&s.span),
+            CheckedStmt::Continue(s) => Some(&s.span),
+            CheckedStmt::Mod => None,
+            CheckedStmt::Match(s) => Some(&s.span),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CheckedFnStmt {
     pub ty: TypeId,
@@ -28,6 +49,7 @@ pub struct CheckedFnStmt {
 
 #[derive(Debug, Clone)]
 pub struct CheckedLetStmt {
+    pub name: String,
     pub var_ty: TypeId,
     pub expr: CheckedExprStmt,
     pub span: Span,
@@ -1,10 +1,11 @@
 use crate::checked_stmt::{CheckedBlockStmt, CheckedStmt};
+use rig_ast::function_prototype::FnType;
 use rig_ast::stmt::Stmt;
 use rig_ast::visibility::Visibility;
+use rig_error::ErrorCode;
 use rig_span::Span;
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
-use rig_error::ErrorCode;
 
 pub mod checked_expr;
 pub mod checked_stmt;
@@ -24,6 +25,12 @@ pub struct Module {
     pub ast: Vec<Stmt>,
     pub checked_ast: Vec<CheckedStmt>,
     pub imports: HashMap<String, Import>,
+
+    /// Names in [Module::imports] brought in by glob imports, with the span of the glob
+    pub glob_imports: HashMap<String, Span>,
+
+    /// `mod` statement that declares the module, if it's declared by one
+    pub span: Option<Span>,
 }
 
 impl Module {
@@ -47,12 +54,22 @@ impl Module {
 
         self.scopes.push(Scope {
             id: scope_id,
+            module_id: self.id,
             ..Default::default()
         });
 
         scope_id
     }
 
+    pub fn new_child_scope(&mut self, parent: ScopeId) -> ScopeId {
+        let scope_id = self.new_scope();
+
+        self.get_scope_mut(scope_id).parent = Some(parent);
+        self.get_scope_mut(parent).children.push(scope_id);
+
+        scope_id
+    }
+
     pub fn get_scope(&self, scope_id: ScopeId) -> &Scope {
         &self.scopes[scope_id.1]
     }
@@ -61,10 +78,67 @@ impl Module {
         &mut self.scopes[scope_id.1]
     }
 
-    pub fn try_import(&self, modules: &[Module], path: &[String]) -> Result<TypeIdOrModuleId, ResolutionError> {
+    pub fn try_import(
+        &self,
+        modules: &[Module],
+        path: &[String],
+    ) -> Result<TypeIdOrModuleId, ResolutionError> {
+        let (module_id, skipped) = self.relative_prefix(modules, path)?;
+        if skipped == 0 {
+            return self.try_import_from(modules, path, false);
+        }
+
+        let module = &modules[module_id.0];
+        match &path[skipped..] {
+            [] => Ok(TypeIdOrModuleId::ModuleId(module_id, Visibility::Pub)),
+            // private items of the module and its parents are visible inside it
+            rest => module.try_import_from(modules, rest, true),
+        }
+    }
+
+    /// Module the `self`, `super` and `crate` segments at the start of a path refer to from
+    /// this module, and the number of those segments. Paths without them start at this module
+    pub fn relative_prefix(
+        &self,
+        modules: &[Module],
+        path: &[String],
+    ) -> Result<(ModuleId, usize), ResolutionError> {
+        let mut current = self;
+        let mut skipped = 0;
+
+        for segment in path {
+            current = match segment.as_str() {
+                "self" => current,
+                "super" => match current.parent_module {
+                    Some(parent) => &modules[parent.0],
+                    None => return Err(ResolutionError::NoParentModule),
+                },
+                "crate" => {
+                    while let Some(parent) = current.parent_module {
+                        current = &modules[parent.0];
+                    }
+
+                    current
+                }
+                _ => break,
+            };
+            skipped += 1;
+        }
+
+        Ok((current.id, skipped))
+    }
+
+    /// Imports `path` from this module. Private items are only imported with `allow_private`,
+    /// which doesn't apply to the modules after the first segment
+    fn try_import_from(
+        &self,
+        modules: &[Module],
+        path: &[String],
+        allow_private: bool,
+    ) -> Result<TypeIdOrModuleId, ResolutionError> {
         if path.len() > 1 {
             // definitely a path referring to a module
-            let ty = self.try_import(modules, &path[0..1])?;
+            let ty = self.try_import_from(modules, &path[0..1], allow_private)?;
 
             return match ty {
                 TypeIdOrModuleId::TypeId(_, Visibility::Pub) => {
@@ -77,7 +151,7 @@ impl Module {
                 TypeIdOrModuleId::ModuleId(mod_id, Visibility::Pub) => {
                     if path.len() >= 2 {
                         let module = modules.get(mod_id.0).unwrap();
-                        module.try_import(modules, &path[1..path.len()])
+                        module.try_import_from(modules, &path[1..path.len()], false)
                     } else {
                         Ok(ty)
                     }
@@ -89,42 +163,46 @@ impl Module {
         let name = path.first().unwrap();
         if let Some(id) = self.imports.get(name) {
             return match id {
-                Import::TypeId(id, Visibility::Pub) => {
-                    Ok(TypeIdOrModuleId::TypeId(id.clone(), Visibility::Pub))
+                Import::TypeId(id, visibility)
+                    if allow_private || *visibility == Visibility::Pub =>
+                {
+                    Ok(TypeIdOrModuleId::TypeId(*id, Visibility::Pub))
                 }
-                Import::Module(id, Visibility::Pub) => {
-                    Ok(TypeIdOrModuleId::ModuleId(id.clone(), Visibility::Pub))
+                Import::Module(id, visibility)
+                    if allow_private || *visibility == Visibility::Pub =>
+                {
+                    Ok(TypeIdOrModuleId::ModuleId(*id, Visibility::Pub))
                 }
                 _ => Err(ResolutionError::AttemptToImportPrivateType),
             };
         }
 
-        for scope in self.scopes.iter().rev() {
+        // only items at the top level of a module can be imported
+        for scope in self.scopes.iter().take(1) {
             let function = scope.find_function(name);
             let enum_ = scope.find_enum(name);
             let struct_ = scope.find_struct(name);
 
-            match (function, enum_, struct_) {
-                (Some(_), Some(_), Some(_)) => return Err(ResolutionError::AmbiguousImport),
-                _ => (),
+            if let (Some(_), Some(_), Some(_)) = (function, enum_, struct_) {
+                return Err(ResolutionError::AmbiguousImport);
             }
 
             // don't care about visibility for now
             if let Some(function) = function {
-                if function.0 == Visibility::NotPub {
+                if function.0 == Visibility::NotPub && !allow_private {
                     return Err(ResolutionError::AttemptToImportPrivateType);
                 }
                 return Ok(TypeIdOrModuleId::TypeId(function.1, Visibility::Pub));
             }
             if let Some(enum_) = enum_ {
-                if enum_.0 == Visibility::NotPub {
+                if enum_.0 == Visibility::NotPub && !allow_private {
                     return Err(ResolutionError::AttemptToImportPrivateType);
                 }
 
                 return Ok(TypeIdOrModuleId::TypeId(enum_.1, Visibility::Pub));
             }
             if let Some(struct_) = struct_ {
-                if struct_.0 == Visibility::NotPub {
+                if struct_.0 == Visibility::NotPub && !allow_private {
                     return Err(ResolutionError::AttemptToImportPrivateType);
                 }
 
@@ -141,16 +219,27 @@ pub enum ResolutionError {
     AttemptToImportPrivateType,
     AmbiguousImport,
     InvalidImport,
-    FailedToImport
+    FailedToImport,
+    NoParentModule,
 }
 
 impl Display for ResolutionError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            ResolutionError::AttemptToImportPrivateType => write!(f, "Attempt to import private type"),
-            ResolutionError::AmbiguousImport => write!(f, "Ambiguous import(different types exist with same name"),
+            ResolutionError::AttemptToImportPrivateType => {
+                write!(f, "Attempt to import private type")
+            }
+            ResolutionError::AmbiguousImport => {
+                write!(f, "Ambiguous import(different types exist with same name")
+            }
             ResolutionError::InvalidImport => write!(f, "Cannot import from type"),
             ResolutionError::FailedToImport => write!(f, "Failed to import type/module"),
+            ResolutionError::NoParentModule => {
+                write!(
+                    f,
+                    "`super` refers to the parent of a module without a parent"
+                )
+            }
         }
     }
 }
@@ -162,6 +251,7 @@ impl ResolutionError {
             ResolutionError::AmbiguousImport => ErrorCode::E0011,
             ResolutionError::InvalidImport => ErrorCode::E0012,
             ResolutionError::FailedToImport => ErrorCode::E0009,
+            ResolutionError::NoParentModule => ErrorCode::E0009,
         }
     }
 }
@@ -193,7 +283,18 @@ pub struct Scope {
     pub functions: HashMap<String, (Visibility, TypeId)>,
     pub structs: HashMap<String, (Visibility, TypeId)>,
     pub enums: HashMap<String, (Visibility, TypeId)>,
+    pub variables: HashMap<String, Variable>,
+    /// Modules declared with `mod` in the scope
     pub imports: Vec<(Visibility, ModuleId)>,
+
+    /// Type `Self` refers to inside `impl` blocks
+    pub self_ty: Option<TypeId>,
+
+    /// Function whose body the scope is, `return` statements inside it return from it
+    pub function: Option<TypeId>,
+
+    /// Whether the scope is the body of a loop, `break` and `continue` inside it refer to it
+    pub is_loop: bool,
     pub parent: Option<ScopeId>,
     pub children: Vec<ScopeId>,
     pub module_id: ModuleId,
@@ -211,6 +312,10 @@ impl Scope {
     pub fn find_enum(&self, name: &str) -> Option<&(Visibility, TypeId)> {
         self.enums.get(name)
     }
+
+    pub fn find_variable(&self, name: &str) -> Option<&Variable> {
+        self.variables.get(name)
+    }
 }
 
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -230,13 +335,44 @@ impl ScopeId {
 pub struct Variable {
     pub name: String,
     pub ty: TypeId,
+    pub visibility: Visibility,
     pub span: Span,
 }
 
+/// [TypeId] refers to a type declared in a scope.
+///
+/// The second field is an index into [Module::types] of the scope's module. When the third
+/// field is `true` the id refers to a builtin type instead and the index is a position in
+/// [BUILTIN_TYPES].
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct TypeId(pub ScopeId, pub usize, pub bool);
 
+/// Types that are available everywhere without being declared
+pub const BUILTIN_TYPES: [Type; 6] = [
+    Type::Integer,
+    Type::Float,
+    Type::Null,
+    Type::String,
+    Type::Boolean,
+    Type::Undefined,
+];
+
 impl TypeId {
+    pub const INTEGER: TypeId = TypeId::builtin(0);
+    pub const FLOAT: TypeId = TypeId::builtin(1);
+    pub const NULL: TypeId = TypeId::builtin(2);
+    pub const STRING: TypeId = TypeId::builtin(3);
+    pub const BOOLEAN: TypeId = TypeId::builtin(4);
+    pub const UNDEFINED: TypeId = TypeId::builtin(5);
+
+    const fn builtin(idx: usize) -> Self {
+        TypeId(ScopeId(ModuleId(0), 0), idx, true)
+    }
+
+    pub fn is_builtin(&self) -> bool {
+        self.2
+    }
+
     pub fn get_scope_id(&self) -> ScopeId {
         self.0
     }
@@ -246,16 +382,24 @@ impl TypeId {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Type {
     Integer,
     Float,
     Null,
     String,
     Boolean,
-    Function,
-    Struct,
-    Enum,
+
+    /// Index into [Module::functions]
+    Function(usize),
+
+    /// Index into [Module::structs]
+    Struct(usize),
+
+    /// Index into [Module::enums]
+    Enum(usize),
+
+    /// Type of an expression that failed to typecheck
     Undefined,
 }
 
@@ -267,6 +411,12 @@ pub struct FunctionType {
     pub return_ty: Option<TypeId>,
     pub body: CheckedBlockStmt,
     pub visibility: Visibility,
+
+    /// Methods take `self` as an implicit first argument
+    pub fn_type: FnType,
+
+    /// Declared in an `extern` block, the body is always empty
+    pub is_extern: bool,
     pub span: Span,
 }
 
@@ -281,8 +431,11 @@ pub struct FunctionArgument {
 pub struct StructType {
     pub location: Vec<String>,
     pub fields: Vec<StructFieldType>,
-    pub methods: Vec<FunctionType>,
-    pub helpers: Vec<FunctionType>,
+    /// Functions that take `self`. They're declared in [Module::functions]
+    pub methods: Vec<TypeId>,
+
+    /// Associated functions without `self`. They're declared in [Module::functions]
+    pub helpers: Vec<TypeId>,
     pub visibility: Visibility,
     pub span: Span,
 }
@@ -291,6 +444,7 @@ pub struct StructType {
 pub struct StructFieldType {
     pub name: String,
     pub ty: TypeId,
+    pub visibility: Visibility,
     pub span: Span,
 }
 
@@ -306,10 +460,12 @@ pub struct EnumType {
 pub struct EnumVariantType {
     pub name: String,
     pub value: Vec<EnumVariantValueType>,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
 pub struct EnumVariantValueType {
     pub name: String,
     pub value_ty: TypeId,
+    pub span: Span,
 }
@@ -0,0 +1,159 @@
+use crate::value::Value;
+
+/// Number of allocations after which the first collection is triggered
+const INITIAL_GC_THRESHOLD: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObjectId(pub usize);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Object {
+    String(String),
+    Struct(StructObject),
+    Enum(EnumObject),
+    Iterator(IteratorObject),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructObject {
+    /// Index into the struct layouts of the running module
+    pub layout: usize,
+    pub fields: Vec<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumObject {
+    /// Index into the enum layouts of the running module
+    pub layout: usize,
+    pub variant: usize,
+    pub fields: Vec<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IteratorObject {
+    pub iterable: ObjectId,
+    pub position: usize,
+}
+
+impl Object {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Object::String(_) => "string",
+            Object::Struct(_) => "struct",
+            Object::Enum(_) => "enum",
+            Object::Iterator(_) => "iterator",
+        }
+    }
+
+    fn references(&self) -> Vec<ObjectId> {
+        let values = match self {
+            Object::String(_) => return vec![],
+            Object::Iterator(iterator) => return vec![iterator.iterable],
+            Object::Struct(s) => &s.fields,
+            Object::Enum(e) => &e.fields,
+        };
+
+        values
+            .iter()
+            .filter_map(|v| match v {
+                Value::Object(id) => Some(*id),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// [Heap] owns every object created by the program.
+///
+/// Objects are never moved, so an [ObjectId] stays valid until the object is
+/// collected. Unreachable objects are reclaimed by a mark and sweep collector.
+#[derive(Debug)]
+pub struct Heap {
+    objects: Vec<Option<Object>>,
+    free_slots: Vec<usize>,
+    allocations_since_gc: usize,
+    gc_threshold: usize,
+}
+
+impl Default for Heap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Heap {
+    pub fn new() -> Self {
+        Self {
+            objects: vec![],
+            free_slots: vec![],
+            allocations_since_gc: 0,
+            gc_threshold: INITIAL_GC_THRESHOLD,
+        }
+    }
+
+    pub fn allocate(&mut self, object: Object) -> ObjectId {
+        self.allocations_since_gc += 1;
+
+        if let Some(slot) = self.free_slots.pop() {
+            self.objects[slot] = Some(object);
+            ObjectId(slot)
+        } else {
+            self.objects.push(Some(object));
+            ObjectId(self.objects.len() - 1)
+        }
+    }
+
+    /// ## Panics
+    /// Panics if the object was already collected. That means a live value wasn't reported as
+    /// a root to [Heap::collect].
+    pub fn get(&self, id: ObjectId) -> &Object {
+        self.objects[id.0]
+            .as_ref()
+            .expect("Attempt to access a collected object")
+    }
+
+    /// ## Panics
+    /// Same as [Heap::get]
+    pub fn get_mut(&mut self, id: ObjectId) -> &mut Object {
+        self.objects[id.0]
+            .as_mut()
+            .expect("Attempt to access a collected object")
+    }
+
+    pub fn live_objects(&self) -> usize {
+        self.objects.len() - self.free_slots.len()
+    }
+
+    pub fn should_collect(&self) -> bool {
+        self.allocations_since_gc >= self.gc_threshold
+    }
+
+    /// Frees every object that isn't reachable from `roots`
+    pub fn collect<'a>(&mut self, roots: impl Iterator<Item = &'a Value>) {
+        let mut marked = vec![false; self.objects.len()];
+        let mut gray = roots
+            .filter_map(|v| match v {
+                Value::Object(id) => Some(*id),
+                _ => None,
+            })
+            .collect::<Vec<ObjectId>>();
+
+        while let Some(id) = gray.pop() {
+            if marked[id.0] {
+                continue;
+            }
+            marked[id.0] = true;
+            gray.extend(self.get(id).references());
+        }
+
+        for (slot, is_marked) in marked.into_iter().enumerate() {
+            if !is_marked && self.objects[slot].is_some() {
+                self.objects[slot] = None;
+                self.free_slots.push(slot);
+            }
+        }
+
+        self.allocations_since_gc = 0;
+        self.gc_threshold = (self.live_objects() * 2).max(INITIAL_GC_THRESHOLD);
+    }
+}
@@ -1,8 +1,706 @@
-#[cfg(test)]
-mod tests {
-    #[test]
-    fn it_works() {
-        let result = 2 + 2;
-        assert_eq!(result, 4);
+pub mod heap;
+pub mod value;
+
+use crate::heap::{EnumObject, Heap, IteratorObject, Object, ObjectId, StructObject};
+use crate::value::Value;
+use rig_bytecode_compiler::bytecode::{CompiledModule, Constant, OpCode};
+use std::fmt::{Display, Formatter};
+use std::io::Write;
+
+/// Maximum depth of nested calls before the program is aborted
+const MAX_FRAMES: usize = 1024;
+
+/// [RuntimeError] aborts the execution of a program
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeError {
+    /// Error message
+    pub message: String,
+
+    /// `(function name, line)` of every active call, innermost first
+    pub backtrace: Vec<(String, usize)>,
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "runtime error: {}", self.message)?;
+
+        for (function, line) in &self.backtrace {
+            write!(f, "\n    at {} (line {})", function, line)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CallFrame {
+    /// Index of the code object being executed
+    function: usize,
+
+    /// Offset of the next instruction
+    ip: usize,
+
+    /// Position of the first local slot on the stack
+    base: usize,
+}
+
+/// [Vm] is a stack based virtual machine that executes a [CompiledModule]
+pub struct Vm<'m> {
+    module: &'m CompiledModule,
+    stack: Vec<Value>,
+    frames: Vec<CallFrame>,
+    globals: Vec<Value>,
+    constants: Vec<Value>,
+    heap: Heap,
+}
+
+impl<'m> Vm<'m> {
+    pub fn new(module: &'m CompiledModule) -> Self {
+        let mut heap = Heap::new();
+        let constants = module
+            .constants
+            .iter()
+            .map(|constant| match constant {
+                Constant::Integer(i) => Value::Integer(*i),
+                Constant::Float(f) => Value::Float(*f),
+                Constant::String(s) => Value::Object(heap.allocate(Object::String(s.clone()))),
+                Constant::Function(idx) => Value::Function(*idx),
+            })
+            .collect();
+
+        Self {
+            module,
+            stack: vec![],
+            frames: vec![],
+            globals: vec![Value::Null; module.globals.len()],
+            constants,
+            heap,
+        }
+    }
+
+    /// Runs the entry point of the module. Everything printed by the program is written to `out`.
+    pub fn run(&mut self, out: &mut dyn Write) -> Result<(), RuntimeError> {
+        self.stack.clear();
+        self.frames.clear();
+
+        let entry_point = self.module.entry_point;
+        if entry_point >= self.module.functions.len() {
+            return Err(self.error(format!(
+                "Entry point function #{} doesn't exist",
+                entry_point
+            )));
+        }
+
+        self.stack.push(Value::Function(entry_point));
+        self.call(entry_point, 0)?;
+        self.execute(out)
+    }
+
+    pub fn heap(&self) -> &Heap {
+        &self.heap
+    }
+
+    pub fn global(&self, name: &str) -> Option<Value> {
+        self.module
+            .globals
+            .iter()
+            .position(|g| g == name)
+            .map(|slot| self.globals[slot])
+    }
+
+    /// Formats a value the same way `print` does
+    pub fn format_value(&self, value: Value) -> String {
+        self.format_value_inner(value, false)
+    }
+
+    fn format_value_inner(&self, value: Value, quote_strings: bool) -> String {
+        match value {
+            Value::Null => String::from("null"),
+            Value::Integer(i) => i.to_string(),
+            Value::Float(f) => f.to_string(),
+            Value::Boolean(b) => b.to_string(),
+            Value::Function(idx) => format!("<fn {}>", self.module.functions[idx].name),
+            Value::Object(id) => match self.heap.get(id) {
+                Object::String(s) if quote_strings => format!("{:?}", s),
+                Object::String(s) => s.clone(),
+                Object::Struct(s) => {
+                    let layout = &self.module.structs[s.layout];
+                    self.format_fields(&layout.name, &layout.fields, &s.fields)
+                }
+                Object::Enum(e) => {
+                    let layout = &self.module.enums[e.layout];
+                    let variant = &layout.variants[e.variant];
+                    self.format_fields(
+                        &format!("{}::{}", layout.name, variant.name),
+                        &variant.fields,
+                        &e.fields,
+                    )
+                }
+                Object::Iterator(_) => String::from("<iterator>"),
+            },
+        }
+    }
+
+    fn format_fields(&self, name: &str, field_names: &[String], values: &[Value]) -> String {
+        if field_names.is_empty() {
+            return name.to_string();
+        }
+
+        let fields = field_names
+            .iter()
+            .zip(values)
+            .map(|(name, value)| format!("{}: {}", name, self.format_value_inner(*value, true)))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        format!("{} {{ {} }}", name, fields)
+    }
+
+    fn execute(&mut self, out: &mut dyn Write) -> Result<(), RuntimeError> {
+        loop {
+            if self.heap.should_collect() {
+                self.collect_garbage();
+            }
+
+            let byte = self.read_byte();
+            let op = match OpCode::from_byte(byte) {
+                Some(op) => op,
+                None => return Err(self.error(format!("Invalid opcode {:#04x}", byte))),
+            };
+
+            match op {
+                OpCode::Constant => {
+                    let idx = self.read_u16() as usize;
+                    self.stack.push(self.constants[idx]);
+                }
+                OpCode::Null => self.stack.push(Value::Null),
+                OpCode::True => self.stack.push(Value::Boolean(true)),
+                OpCode::False => self.stack.push(Value::Boolean(false)),
+                OpCode::Pop => {
+                    self.pop();
+                }
+                OpCode::GetLocal => {
+                    let slot = self.read_u16() as usize;
+                    let base = self.frame().base;
+                    self.stack.push(self.stack[base + slot]);
+                }
+                OpCode::SetLocal => {
+                    let slot = self.read_u16() as usize;
+                    let base = self.frame().base;
+                    self.stack[base + slot] = self.peek(0);
+                }
+                OpCode::GetGlobal => {
+                    let slot = self.read_u16() as usize;
+                    self.stack.push(self.globals[slot]);
+                }
+                OpCode::SetGlobal => {
+                    let slot = self.read_u16() as usize;
+                    self.globals[slot] = self.peek(0);
+                }
+                OpCode::GetField => {
+                    let name = self.read_string_constant();
+                    let object = self.pop();
+                    let (id, field) = self.field_index(object, &name)?;
+
+                    match self.heap.get(id) {
+                        Object::Struct(s) => self.stack.push(s.fields[field]),
+                        _ => unreachable!(),
+                    }
+                }
+                OpCode::SetField => {
+                    let name = self.read_string_constant();
+                    let value = self.pop();
+                    let object = self.pop();
+                    let (id, field) = self.field_index(object, &name)?;
+
+                    if let Object::Struct(s) = self.heap.get_mut(id) {
+                        s.fields[field] = value;
+                    }
+                    self.stack.push(value);
+                }
+                OpCode::Struct => {
+                    let layout = self.read_u16() as usize;
+                    let field_count = self.module.structs[layout].fields.len();
+                    let fields = self.stack.split_off(self.stack.len() - field_count);
+                    let id = self
+                        .heap
+                        .allocate(Object::Struct(StructObject { layout, fields }));
+
+                    self.stack.push(Value::Object(id));
+                }
+                OpCode::EnumVariant => {
+                    let layout = self.read_u16() as usize;
+                    let variant = self.read_u16() as usize;
+                    let field_count = self.module.enums[layout].variants[variant].fields.len();
+                    let fields = self.stack.split_off(self.stack.len() - field_count);
+                    let id = self.heap.allocate(Object::Enum(EnumObject {
+                        layout,
+                        variant,
+                        fields,
+                    }));
+
+                    self.stack.push(Value::Object(id));
+                }
+                OpCode::IsVariant => {
+                    let variant = self.read_u16() as usize;
+                    let value = self.pop();
+                    let is_variant = self.expect_enum(value)?.variant == variant;
+
+                    self.stack.push(Value::Boolean(is_variant));
+                }
+                OpCode::GetVariantField => {
+                    let field = self.read_u16() as usize;
+                    let value = self.pop();
+                    let enum_value = self.expect_enum(value)?;
+
+                    match enum_value.fields.get(field) {
+                        Some(field) => self.stack.push(*field),
+                        None => {
+                            let layout = &self.module.enums[enum_value.layout];
+                            return Err(self.error(format!(
+                                "Variant `{}::{}` has no field #{}",
+                                layout.name, layout.variants[enum_value.variant].name, field
+                            )));
+                        }
+                    }
+                }
+                OpCode::Add
+                | OpCode::Subtract
+                | OpCode::Multiply
+                | OpCode::Divide
+                | OpCode::Modulus
+                | OpCode::BitAnd
+                | OpCode::BitOr
+                | OpCode::BitXor
+                | OpCode::ShiftLeft
+                | OpCode::ShiftRight => self.binary_op(op)?,
+                OpCode::Equal => {
+                    let b = self.pop();
+                    let a = self.pop();
+                    self.stack.push(Value::Boolean(self.values_equal(a, b)));
+                }
+                OpCode::NotEqual => {
+                    let b = self.pop();
+                    let a = self.pop();
+                    self.stack.push(Value::Boolean(!self.values_equal(a, b)));
+                }
+                OpCode::Greater | OpCode::GreaterEqual | OpCode::Less | OpCode::LessEqual => {
+                    self.comparison_op(op)?
+                }
+                OpCode::Not => match self.pop() {
+                    Value::Boolean(b) => self.stack.push(Value::Boolean(!b)),
+                    value => return Err(self.unary_type_error("!", value)),
+                },
+                OpCode::Negate => match self.pop() {
+                    Value::Integer(i) => match i.checked_neg() {
+                        Some(i) => self.stack.push(Value::Integer(i)),
+                        None => return Err(self.error("Integer overflow".to_string())),
+                    },
+                    Value::Float(f) => self.stack.push(Value::Float(-f)),
+                    value => return Err(self.unary_type_error("-", value)),
+                },
+                OpCode::Jump => {
+                    let offset = self.read_u16() as usize;
+                    self.frame_mut().ip += offset;
+                }
+                OpCode::JumpIfFalse => {
+                    let offset = self.read_u16() as usize;
+
+                    match self.peek(0) {
+                        Value::Boolean(false) => self.frame_mut().ip += offset,
+                        Value::Boolean(true) => (),
+                        value => {
+                            return Err(self.error(format!(
+                                "Expected a boolean condition, found {}",
+                                self.type_name(value)
+                            )))
+                        }
+                    }
+                }
+                OpCode::Loop => {
+                    let offset = self.read_u16() as usize;
+                    self.frame_mut().ip -= offset;
+                }
+                OpCode::Call => {
+                    let argc = self.read_byte() as usize;
+
+                    match self.peek(argc) {
+                        Value::Function(idx) => self.call(idx, argc)?,
+                        value => {
+                            return Err(self.error(format!(
+                                "Attempt to call a value of type {}",
+                                self.type_name(value)
+                            )))
+                        }
+                    }
+                }
+                OpCode::Return => {
+                    let result = self.pop();
+                    let frame = self.frames.pop().unwrap();
+
+                    // also removes the called function
+                    self.stack.truncate(frame.base - 1);
+
+                    if self.frames.is_empty() {
+                        return Ok(());
+                    }
+                    self.stack.push(result);
+                }
+                OpCode::Print => {
+                    let value = self.pop();
+                    let formatted = self.format_value(value);
+
+                    if let Err(e) = writeln!(out, "{}", formatted) {
+                        return Err(self.error(format!("Failed to write output: {}", e)));
+                    }
+                }
+                OpCode::GetIter => {
+                    let value = self.pop();
+
+                    match value {
+                        Value::Object(id) if matches!(self.heap.get(id), Object::String(_)) => {
+                            let iterator = self.heap.allocate(Object::Iterator(IteratorObject {
+                                iterable: id,
+                                position: 0,
+                            }));
+                            self.stack.push(Value::Object(iterator));
+                        }
+                        value => {
+                            return Err(self.error(format!(
+                                "Value of type {} is not iterable",
+                                self.type_name(value)
+                            )))
+                        }
+                    }
+                }
+                OpCode::ForIter => {
+                    let offset = self.read_u16() as usize;
+                    let iterator = match self.peek(0) {
+                        Value::Object(id) if matches!(self.heap.get(id), Object::Iterator(_)) => id,
+                        value => {
+                            return Err(self.error(format!(
+                                "Expected an iterator, found {}",
+                                self.type_name(value)
+                            )))
+                        }
+                    };
+
+                    match self.next_item(iterator) {
+                        Some(item) => {
+                            let id = self.heap.allocate(Object::String(item));
+                            self.stack.push(Value::Object(id));
+                        }
+                        None => {
+                            self.pop();
+                            self.frame_mut().ip += offset;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn call(&mut self, function: usize, argc: usize) -> Result<(), RuntimeError> {
+        let code = &self.module.functions[function];
+
+        if code.arity != argc {
+            return Err(self.error(format!(
+                "Function `{}` expects {} argument(s) but {} were given",
+                code.name, code.arity, argc
+            )));
+        }
+        if self.frames.len() >= MAX_FRAMES {
+            return Err(self.error("Stack overflow".to_string()));
+        }
+
+        let base = self.stack.len() - argc;
+        self.stack.resize(base + code.locals.max(argc), Value::Null);
+        self.frames.push(CallFrame {
+            function,
+            ip: 0,
+            base,
+        });
+
+        Ok(())
+    }
+
+    fn binary_op(&mut self, op: OpCode) -> Result<(), RuntimeError> {
+        let b = self.pop();
+        let a = self.pop();
+
+        let result = match (a, b) {
+            (Value::Integer(a), Value::Integer(b)) => Value::Integer(self.integer_op(op, a, b)?),
+            (Value::Float(x), Value::Float(y)) => self.float_op(op, x, y, a, b)?,
+            (Value::Integer(i), Value::Float(y)) => self.float_op(op, i as f64, y, a, b)?,
+            (Value::Float(x), Value::Integer(i)) => self.float_op(op, x, i as f64, a, b)?,
+            (Value::Object(a_id), Value::Object(b_id)) if op == OpCode::Add => {
+                match (self.heap.get(a_id), self.heap.get(b_id)) {
+                    (Object::String(a), Object::String(b)) => {
+                        let concatenated = format!("{}{}", a, b);
+                        Value::Object(self.heap.allocate(Object::String(concatenated)))
+                    }
+                    _ => return Err(self.binary_type_error(op, a, b)),
+                }
+            }
+            _ => return Err(self.binary_type_error(op, a, b)),
+        };
+
+        self.stack.push(result);
+        Ok(())
+    }
+
+    fn integer_op(&self, op: OpCode, a: i64, b: i64) -> Result<i64, RuntimeError> {
+        if (op == OpCode::Divide || op == OpCode::Modulus) && b == 0 {
+            return Err(self.error("Division by zero".to_string()));
+        }
+
+        let result = match op {
+            OpCode::Add => a.checked_add(b),
+            OpCode::Subtract => a.checked_sub(b),
+            OpCode::Multiply => a.checked_mul(b),
+            OpCode::Divide => a.checked_div(b),
+            OpCode::Modulus => a.checked_rem(b),
+            OpCode::BitAnd => Some(a & b),
+            OpCode::BitOr => Some(a | b),
+            OpCode::BitXor => Some(a ^ b),
+            OpCode::ShiftLeft | OpCode::ShiftRight => {
+                if !(0..64).contains(&b) {
+                    return Err(self.error(format!("Shift amount {} is out of range", b)));
+                }
+
+                if op == OpCode::ShiftLeft {
+                    Some(a << b)
+                } else {
+                    Some(a >> b)
+                }
+            }
+            _ => unreachable!(),
+        };
+
+        result.ok_or_else(|| self.error("Integer overflow".to_string()))
+    }
+
+    /// `lhs` and `rhs` are the operands before conversion, used to report unsupported operators
+    fn float_op(
+        &self,
+        op: OpCode,
+        a: f64,
+        b: f64,
+        lhs: Value,
+        rhs: Value,
+    ) -> Result<Value, RuntimeError> {
+        Ok(Value::Float(match op {
+            OpCode::Add => a + b,
+            OpCode::Subtract => a - b,
+            OpCode::Multiply => a * b,
+            OpCode::Divide => a / b,
+            OpCode::Modulus => a % b,
+            _ => return Err(self.binary_type_error(op, lhs, rhs)),
+        }))
+    }
+
+    fn comparison_op(&mut self, op: OpCode) -> Result<(), RuntimeError> {
+        let b = self.pop();
+        let a = self.pop();
+
+        let ordering = match (a, b) {
+            (Value::Integer(a), Value::Integer(b)) => a.partial_cmp(&b),
+            (Value::Float(a), Value::Float(b)) => a.partial_cmp(&b),
+            (Value::Integer(a), Value::Float(b)) => (a as f64).partial_cmp(&b),
+            (Value::Float(a), Value::Integer(b)) => a.partial_cmp(&(b as f64)),
+            (Value::Object(a_id), Value::Object(b_id)) => {
+                match (self.heap.get(a_id), self.heap.get(b_id)) {
+                    (Object::String(a), Object::String(b)) => a.partial_cmp(b),
+                    _ => return Err(self.binary_type_error(op, a, b)),
+                }
+            }
+            _ => return Err(self.binary_type_error(op, a, b)),
+        };
+
+        let result = match ordering {
+            Some(ordering) => match op {
+                OpCode::Greater => ordering.is_gt(),
+                OpCode::GreaterEqual => ordering.is_ge(),
+                OpCode::Less => ordering.is_lt(),
+                OpCode::LessEqual => ordering.is_le(),
+                _ => unreachable!(),
+            },
+            // NaN
+            None => false,
+        };
+
+        self.stack.push(Value::Boolean(result));
+        Ok(())
+    }
+
+    fn values_equal(&self, a: Value, b: Value) -> bool {
+        match (a, b) {
+            (Value::Integer(a), Value::Float(b)) | (Value::Float(b), Value::Integer(a)) => {
+                a as f64 == b
+            }
+            (Value::Object(a_id), Value::Object(b_id)) => {
+                match (self.heap.get(a_id), self.heap.get(b_id)) {
+                    (Object::String(a), Object::String(b)) => a == b,
+                    _ => a_id == b_id,
+                }
+            }
+            (a, b) => a == b,
+        }
+    }
+
+    fn field_index(&self, object: Value, name: &str) -> Result<(ObjectId, usize), RuntimeError> {
+        if let Value::Object(id) = object {
+            if let Object::Struct(s) = self.heap.get(id) {
+                let layout = &self.module.structs[s.layout];
+
+                return match layout.fields.iter().position(|f| f == name) {
+                    Some(field) => Ok((id, field)),
+                    None => Err(self.error(format!(
+                        "Struct `{}` has no field named `{}`",
+                        layout.name, name
+                    ))),
+                };
+            }
+        }
+
+        Err(self.error(format!(
+            "Attempt to access field `{}` of a value of type {}",
+            name,
+            self.type_name(object)
+        )))
+    }
+
+    fn expect_enum(&self, value: Value) -> Result<&EnumObject, RuntimeError> {
+        if let Value::Object(id) = value {
+            if let Object::Enum(e) = self.heap.get(id) {
+                return Ok(e);
+            }
+        }
+
+        Err(self.error(format!(
+            "Expected an enum value, found {}",
+            self.type_name(value)
+        )))
+    }
+
+    fn next_item(&mut self, iterator: ObjectId) -> Option<String> {
+        let (iterable, position) = match self.heap.get(iterator) {
+            Object::Iterator(it) => (it.iterable, it.position),
+            _ => unreachable!(),
+        };
+        let ch = match self.heap.get(iterable) {
+            Object::String(s) => s[position..].chars().next()?,
+            _ => unreachable!(),
+        };
+
+        if let Object::Iterator(it) = self.heap.get_mut(iterator) {
+            it.position += ch.len_utf8();
+        }
+
+        Some(ch.to_string())
+    }
+
+    fn collect_garbage(&mut self) {
+        let roots = self
+            .stack
+            .iter()
+            .chain(self.globals.iter())
+            .chain(self.constants.iter());
+
+        self.heap.collect(roots);
+    }
+
+    fn type_name(&self, value: Value) -> &'static str {
+        match value {
+            Value::Object(id) => self.heap.get(id).type_name(),
+            value => value.type_name(),
+        }
+    }
+
+    fn binary_type_error(&self, op: OpCode, a: Value, b: Value) -> RuntimeError {
+        let symbol = match op {
+            OpCode::Add => "+",
+            OpCode::Subtract => "-",
+            OpCode::Multiply => "*",
+            OpCode::Divide => "/",
+            OpCode::Modulus => "%",
+            OpCode::BitAnd => "&",
+            OpCode::BitOr => "|",
+            OpCode::BitXor => "^",
+            OpCode::ShiftLeft => "<<",
+            OpCode::ShiftRight => ">>",
+            OpCode::Greater => ">",
+            OpCode::GreaterEqual => ">=",
+            OpCode::Less => "<",
+            OpCode::LessEqual => "<=",
+            op => unreachable!("{:?} is not a binary operator", op),
+        };
+
+        self.error(format!(
+            "Unsupported operand types for `{}`: {} and {}",
+            symbol,
+            self.type_name(a),
+            self.type_name(b)
+        ))
+    }
+
+    fn unary_type_error(&self, op: &str, value: Value) -> RuntimeError {
+        self.error(format!(
+            "Unsupported operand type for `{}`: {}",
+            op,
+            self.type_name(value)
+        ))
+    }
+
+    fn error(&self, message: String) -> RuntimeError {
+        let backtrace = self
+            .frames
+            .iter()
+            .rev()
+            .map(|frame| {
+                let code = &self.module.functions[frame.function];
+                (code.name.clone(), code.line_at(frame.ip.saturating_sub(1)))
+            })
+            .collect();
+
+        RuntimeError { message, backtrace }
+    }
+
+    fn frame(&self) -> &CallFrame {
+        self.frames.last().unwrap()
+    }
+
+    fn frame_mut(&mut self) -> &mut CallFrame {
+        self.frames.last_mut().unwrap()
+    }
+
+    fn read_byte(&mut self) -> u8 {
+        let module = self.module;
+        let frame = self.frame_mut();
+        let byte = module.functions[frame.function].code[frame.ip];
+        frame.ip += 1;
+
+        byte
+    }
+
+    fn read_u16(&mut self) -> u16 {
+        u16::from_le_bytes([self.read_byte(), self.read_byte()])
+    }
+
+    fn read_string_constant(&mut self) -> String {
+        let idx = self.read_u16() as usize;
+
+        match &self.module.constants[idx] {
+            Constant::String(s) => s.clone(),
+            constant => panic!("Expected a string constant, found {:?}", constant),
+        }
+    }
+
+    fn pop(&mut self) -> Value {
+        self.stack.pop().expect("Value stack underflow")
+    }
+
+    fn peek(&self, distance: usize) -> Value {
+        self.stack[self.stack.len() - 1 - distance]
     }
 }
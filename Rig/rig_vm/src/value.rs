@@ -0,0 +1,29 @@
+use crate::heap::ObjectId;
+
+/// [Value] is a value that lives on the stack, in a global or in a field of a heap object.
+/// Anything bigger than a machine word lives on the heap and is referred to by an [ObjectId].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+    Null,
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+
+    /// Index into the code objects of the running module
+    Function(usize),
+
+    Object(ObjectId),
+}
+
+impl Value {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::Integer(_) => "integer",
+            Value::Float(_) => "float",
+            Value::Boolean(_) => "boolean",
+            Value::Function(_) => "function",
+            Value::Object(_) => "object",
+        }
+    }
+}
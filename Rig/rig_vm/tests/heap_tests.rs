@@ -0,0 +1,44 @@
+use rig_vm::heap::{Heap, Object, StructObject};
+use rig_vm::value::Value;
+use rig_vm::RuntimeError;
+
+#[test]
+fn test_unreachable_objects_are_collected() {
+    let mut heap = Heap::new();
+    let name = heap.allocate(Object::String(String::from("name")));
+    let point = heap.allocate(Object::Struct(StructObject {
+        layout: 0,
+        fields: vec![Value::Integer(1), Value::Object(name)],
+    }));
+    heap.allocate(Object::String(String::from("garbage")));
+
+    heap.collect([Value::Object(point)].iter());
+
+    assert_eq!(heap.live_objects(), 2);
+    assert_eq!(heap.get(name), &Object::String(String::from("name")));
+}
+
+#[test]
+fn test_collected_slots_are_reused() {
+    let mut heap = Heap::new();
+    let garbage = heap.allocate(Object::String(String::from("garbage")));
+    heap.collect([].iter());
+
+    let reused = heap.allocate(Object::String(String::from("new")));
+
+    assert_eq!(reused, garbage);
+    assert_eq!(heap.live_objects(), 1);
+}
+
+#[test]
+fn test_runtime_error_display() {
+    let err = RuntimeError {
+        message: String::from("Division by zero"),
+        backtrace: vec![(String::from("div"), 2), (String::from("main"), 7)],
+    };
+
+    assert_eq!(
+        err.to_string(),
+        "runtime error: Division by zero\n    at div (line 2)\n    at main (line 7)"
+    );
+}
@@ -0,0 +1,387 @@
+use rig_bytecode_compiler::bytecode::{
+    CodeObject, CompiledModule, Constant, EnumLayout, OpCode, StructLayout,
+};
+use rig_vm::value::Value;
+use rig_vm::Vm;
+
+fn module_with_main(constants: Vec<Constant>, main: CodeObject) -> CompiledModule {
+    CompiledModule {
+        name: String::from("test"),
+        constants,
+        functions: vec![main],
+        ..Default::default()
+    }
+}
+
+fn run(module: &CompiledModule) -> String {
+    let mut out = Vec::new();
+    let mut vm = Vm::new(module);
+
+    if let Err(e) = vm.run(&mut out) {
+        panic!("unexpected runtime error: {}", e);
+    }
+
+    String::from_utf8(out).unwrap()
+}
+
+fn write_return(code: &mut CodeObject) {
+    code.write_op(OpCode::Null, 1);
+    code.write_op(OpCode::Return, 1);
+}
+
+#[test]
+fn test_print_arithmetic() {
+    let mut main = CodeObject::new("main", 0);
+    // print 2 * 3 + 1;
+    main.write_op(OpCode::Constant, 1);
+    main.write_u16(0, 1);
+    main.write_op(OpCode::Constant, 1);
+    main.write_u16(1, 1);
+    main.write_op(OpCode::Multiply, 1);
+    main.write_op(OpCode::Constant, 1);
+    main.write_u16(2, 1);
+    main.write_op(OpCode::Add, 1);
+    main.write_op(OpCode::Print, 1);
+    write_return(&mut main);
+
+    let module = module_with_main(
+        vec![
+            Constant::Integer(2),
+            Constant::Integer(3),
+            Constant::Integer(1),
+        ],
+        main,
+    );
+
+    assert_eq!(run(&module), "7\n");
+}
+
+#[test]
+fn test_string_concatenation() {
+    let mut main = CodeObject::new("main", 0);
+    main.write_op(OpCode::Constant, 1);
+    main.write_u16(0, 1);
+    main.write_op(OpCode::Constant, 1);
+    main.write_u16(1, 1);
+    main.write_op(OpCode::Add, 1);
+    main.write_op(OpCode::Print, 1);
+    write_return(&mut main);
+
+    let module = module_with_main(
+        vec![
+            Constant::String(String::from("hello, ")),
+            Constant::String(String::from("world")),
+        ],
+        main,
+    );
+
+    assert_eq!(run(&module), "hello, world\n");
+}
+
+#[test]
+fn test_call_and_locals() {
+    // fn add(a, b) { return a + b; }
+    let mut add = CodeObject::new("add", 2);
+    add.write_op(OpCode::GetLocal, 1);
+    add.write_u16(0, 1);
+    add.write_op(OpCode::GetLocal, 1);
+    add.write_u16(1, 1);
+    add.write_op(OpCode::Add, 1);
+    add.write_op(OpCode::Return, 1);
+
+    // let x = add(40, 2); print x;
+    let mut main = CodeObject::new("main", 0);
+    main.locals = 1;
+    main.write_op(OpCode::Constant, 2);
+    main.write_u16(0, 2);
+    main.write_op(OpCode::Constant, 2);
+    main.write_u16(1, 2);
+    main.write_op(OpCode::Constant, 2);
+    main.write_u16(2, 2);
+    main.write_op(OpCode::Call, 2);
+    main.write_byte(2, 2);
+    main.write_op(OpCode::SetLocal, 2);
+    main.write_u16(0, 2);
+    main.write_op(OpCode::Pop, 2);
+    main.write_op(OpCode::GetLocal, 3);
+    main.write_u16(0, 3);
+    main.write_op(OpCode::Print, 3);
+    write_return(&mut main);
+
+    let mut module = module_with_main(
+        vec![
+            Constant::Function(1),
+            Constant::Integer(40),
+            Constant::Integer(2),
+        ],
+        main,
+    );
+    module.functions.push(add);
+
+    assert_eq!(run(&module), "42\n");
+}
+
+#[test]
+fn test_loop_with_globals() {
+    // counter = 0; while counter < 3 { print counter; counter = counter + 1; }
+    let mut main = CodeObject::new("main", 0);
+    main.write_op(OpCode::Constant, 1);
+    main.write_u16(0, 1);
+    main.write_op(OpCode::SetGlobal, 1);
+    main.write_u16(0, 1);
+    main.write_op(OpCode::Pop, 1);
+
+    let loop_start = main.code.len();
+    main.write_op(OpCode::GetGlobal, 2);
+    main.write_u16(0, 2);
+    main.write_op(OpCode::Constant, 2);
+    main.write_u16(1, 2);
+    main.write_op(OpCode::Less, 2);
+    main.write_op(OpCode::JumpIfFalse, 2);
+    let exit_jump = main.code.len();
+    main.write_u16(0, 2);
+    main.write_op(OpCode::Pop, 2);
+
+    main.write_op(OpCode::GetGlobal, 3);
+    main.write_u16(0, 3);
+    main.write_op(OpCode::Print, 3);
+    main.write_op(OpCode::GetGlobal, 4);
+    main.write_u16(0, 4);
+    main.write_op(OpCode::Constant, 4);
+    main.write_u16(2, 4);
+    main.write_op(OpCode::Add, 4);
+    main.write_op(OpCode::SetGlobal, 4);
+    main.write_u16(0, 4);
+    main.write_op(OpCode::Pop, 4);
+
+    main.write_op(OpCode::Loop, 5);
+    let offset = (main.code.len() + 2 - loop_start) as u16;
+    main.write_u16(offset, 5);
+
+    let exit = (main.code.len() - exit_jump - 2) as u16;
+    main.code[exit_jump..exit_jump + 2].copy_from_slice(&exit.to_le_bytes());
+    main.write_op(OpCode::Pop, 6);
+    write_return(&mut main);
+
+    let mut module = module_with_main(
+        vec![
+            Constant::Integer(0),
+            Constant::Integer(3),
+            Constant::Integer(1),
+        ],
+        main,
+    );
+    module.globals.push(String::from("counter"));
+
+    let mut out = Vec::new();
+    let mut vm = Vm::new(&module);
+    vm.run(&mut out).unwrap();
+
+    assert_eq!(String::from_utf8(out).unwrap(), "0\n1\n2\n");
+    assert_eq!(vm.global("counter"), Some(Value::Integer(3)));
+}
+
+#[test]
+fn test_struct_fields() {
+    // let p = Point { x: 1, y: 2 }; p.y = 5; print p.y; print p;
+    let mut main = CodeObject::new("main", 0);
+    main.locals = 1;
+    main.write_op(OpCode::Constant, 1);
+    main.write_u16(0, 1);
+    main.write_op(OpCode::Constant, 1);
+    main.write_u16(1, 1);
+    main.write_op(OpCode::Struct, 1);
+    main.write_u16(0, 1);
+    main.write_op(OpCode::SetLocal, 1);
+    main.write_u16(0, 1);
+    main.write_op(OpCode::Pop, 1);
+
+    main.write_op(OpCode::GetLocal, 2);
+    main.write_u16(0, 2);
+    main.write_op(OpCode::Constant, 2);
+    main.write_u16(2, 2);
+    main.write_op(OpCode::SetField, 2);
+    main.write_u16(3, 2);
+    main.write_op(OpCode::Pop, 2);
+
+    main.write_op(OpCode::GetLocal, 3);
+    main.write_u16(0, 3);
+    main.write_op(OpCode::GetField, 3);
+    main.write_u16(3, 3);
+    main.write_op(OpCode::Print, 3);
+    main.write_op(OpCode::GetLocal, 4);
+    main.write_u16(0, 4);
+    main.write_op(OpCode::Print, 4);
+    write_return(&mut main);
+
+    let mut module = module_with_main(
+        vec![
+            Constant::Integer(1),
+            Constant::Integer(2),
+            Constant::Integer(5),
+            Constant::String(String::from("y")),
+        ],
+        main,
+    );
+    module.structs.push(StructLayout {
+        name: String::from("Point"),
+        fields: vec![String::from("x"), String::from("y")],
+    });
+
+    assert_eq!(run(&module), "5\nPoint { x: 1, y: 5 }\n");
+}
+
+#[test]
+fn test_enum_variants() {
+    let mut main = CodeObject::new("main", 0);
+    main.write_op(OpCode::Constant, 1);
+    main.write_u16(0, 1);
+    main.write_op(OpCode::EnumVariant, 1);
+    main.write_u16(0, 1);
+    main.write_u16(1, 1);
+    main.write_op(OpCode::IsVariant, 1);
+    main.write_u16(1, 1);
+    main.write_op(OpCode::Print, 1);
+    main.write_op(OpCode::EnumVariant, 2);
+    main.write_u16(0, 2);
+    main.write_u16(0, 2);
+    main.write_op(OpCode::Print, 2);
+    write_return(&mut main);
+
+    let mut module = module_with_main(vec![Constant::String(String::from("hi"))], main);
+    module.enums.push(EnumLayout {
+        name: String::from("Message"),
+        variants: vec![
+            StructLayout {
+                name: String::from("Empty"),
+                fields: vec![],
+            },
+            StructLayout {
+                name: String::from("Text"),
+                fields: vec![String::from("text")],
+            },
+        ],
+    });
+
+    assert_eq!(run(&module), "true\nMessage::Empty\n");
+}
+
+#[test]
+fn test_string_iteration() {
+    let mut main = CodeObject::new("main", 0);
+    main.write_op(OpCode::Constant, 1);
+    main.write_u16(0, 1);
+    main.write_op(OpCode::GetIter, 1);
+    let loop_start = main.code.len();
+    main.write_op(OpCode::ForIter, 1);
+    let exit_jump = main.code.len();
+    main.write_u16(0, 1);
+    main.write_op(OpCode::Print, 2);
+    main.write_op(OpCode::Loop, 2);
+    let offset = (main.code.len() + 2 - loop_start) as u16;
+    main.write_u16(offset, 2);
+    let exit = (main.code.len() - exit_jump - 2) as u16;
+    main.code[exit_jump..exit_jump + 2].copy_from_slice(&exit.to_le_bytes());
+    write_return(&mut main);
+
+    let module = module_with_main(vec![Constant::String(String::from("héy"))], main);
+
+    assert_eq!(run(&module), "h\né\ny\n");
+}
+
+#[test]
+fn test_division_by_zero_is_reported() {
+    let mut main = CodeObject::new("main", 0);
+    main.write_op(OpCode::Constant, 7);
+    main.write_u16(0, 7);
+    main.write_op(OpCode::Constant, 7);
+    main.write_u16(1, 7);
+    main.write_op(OpCode::Divide, 7);
+    main.write_op(OpCode::Print, 7);
+    write_return(&mut main);
+
+    let module = module_with_main(vec![Constant::Integer(1), Constant::Integer(0)], main);
+    let err = Vm::new(&module).run(&mut Vec::new()).unwrap_err();
+
+    assert_eq!(err.message, "Division by zero");
+    assert_eq!(err.backtrace, vec![(String::from("main"), 7)]);
+}
+
+#[test]
+fn test_unsupported_float_operands_are_reported() {
+    let mut main = CodeObject::new("main", 0);
+    main.write_op(OpCode::Constant, 3);
+    main.write_u16(0, 3);
+    main.write_op(OpCode::Constant, 3);
+    main.write_u16(1, 3);
+    main.write_op(OpCode::BitAnd, 3);
+    main.write_op(OpCode::Print, 3);
+    write_return(&mut main);
+
+    let module = module_with_main(vec![Constant::Integer(2), Constant::Float(1.5)], main);
+    let err = Vm::new(&module).run(&mut Vec::new()).unwrap_err();
+
+    assert_eq!(
+        err.message,
+        "Unsupported operand types for `&`: integer and float"
+    );
+}
+
+#[test]
+fn test_garbage_is_collected() {
+    // concatenate strings in a loop so that the collector has to run
+    let mut main = CodeObject::new("main", 0);
+    main.locals = 1;
+    main.write_op(OpCode::Constant, 1);
+    main.write_u16(0, 1);
+    main.write_op(OpCode::SetLocal, 1);
+    main.write_u16(0, 1);
+    main.write_op(OpCode::Pop, 1);
+
+    let loop_start = main.code.len();
+    main.write_op(OpCode::GetLocal, 2);
+    main.write_u16(0, 2);
+    main.write_op(OpCode::Constant, 2);
+    main.write_u16(1, 2);
+    main.write_op(OpCode::Less, 2);
+    main.write_op(OpCode::JumpIfFalse, 2);
+    let exit_jump = main.code.len();
+    main.write_u16(0, 2);
+    main.write_op(OpCode::Pop, 2);
+    main.write_op(OpCode::Constant, 3);
+    main.write_u16(2, 3);
+    main.write_op(OpCode::Constant, 3);
+    main.write_u16(2, 3);
+    main.write_op(OpCode::Add, 3);
+    main.write_op(OpCode::Pop, 3);
+    main.write_op(OpCode::GetLocal, 3);
+    main.write_u16(0, 3);
+    main.write_op(OpCode::Constant, 3);
+    main.write_u16(3, 3);
+    main.write_op(OpCode::Add, 3);
+    main.write_op(OpCode::SetLocal, 3);
+    main.write_u16(0, 3);
+    main.write_op(OpCode::Pop, 3);
+    main.write_op(OpCode::Loop, 3);
+    let offset = (main.code.len() + 2 - loop_start) as u16;
+    main.write_u16(offset, 3);
+    let exit = (main.code.len() - exit_jump - 2) as u16;
+    main.code[exit_jump..exit_jump + 2].copy_from_slice(&exit.to_le_bytes());
+    main.write_op(OpCode::Pop, 4);
+    write_return(&mut main);
+
+    let module = module_with_main(
+        vec![
+            Constant::Integer(0),
+            Constant::Integer(10_000),
+            Constant::String(String::from("garbage")),
+            Constant::Integer(1),
+        ],
+        main,
+    );
+
+    let mut vm = Vm::new(&module);
+    vm.run(&mut Vec::new()).unwrap();
+
+    assert!(vm.heap().live_objects() < 10_000);
+}